@@ -0,0 +1,67 @@
+use std::collections::HashMap;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+use rust_differential_geometry::compilable::Compilable;
+use rust_differential_geometry::derivable::Derivable;
+use rust_differential_geometry::evaluable::Evaluable;
+use rust_differential_geometry::expression::{BinaryOperationType, Expression, Function};
+
+fn variable(variable: char) -> Expression {
+    Expression::monomial(1, variable, 1)
+}
+
+fn multiply(left: Expression, right: Expression) -> Expression {
+    Expression::binary(BinaryOperationType::Multiplication, left, right)
+}
+
+/// Squared norm of the unsimplified partial derivatives of the torus embedding, full of
+/// repeated subexpressions.
+fn torus_metric_trace() -> Expression {
+    let radius = Expression::Sum(vec![
+        variable('R'),
+        multiply(
+            variable('r'),
+            Expression::function(Function::Cosine, variable('v')),
+        ),
+    ]);
+    let embedding = [
+        multiply(
+            Expression::function(Function::Cosine, variable('u')),
+            radius.clone(),
+        ),
+        multiply(Expression::function(Function::Sine, variable('u')), radius),
+        multiply(
+            variable('r'),
+            Expression::function(Function::Sine, variable('v')),
+        ),
+    ];
+    Expression::Sum(
+        ['u', 'v']
+            .iter()
+            .flat_map(|parameter| embedding.iter().map(|x| x.derive(*parameter)))
+            .map(|derivative| Expression::Product(vec![derivative.clone(), derivative]))
+            .collect(),
+    )
+}
+
+fn evaluation(c: &mut Criterion) {
+    let expression = torus_metric_trace();
+    let variables = ['u', 'v', 'R', 'r'];
+    let arguments = [0.3, 1.1, 2., 0.5];
+    let values: HashMap<char, f64> = variables.into_iter().zip(arguments).collect();
+    let compiled = expression.compile(&variables).unwrap();
+
+    let mut group = c.benchmark_group("torus metric trace");
+    group.bench_function("tree walking", |b| {
+        b.iter(|| black_box(&expression).evaluate(black_box(&values)))
+    });
+    group.bench_function("compiled", |b| {
+        let mut registers = vec![];
+        b.iter(|| compiled.evaluate_with(black_box(&arguments), &mut registers))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, evaluation);
+criterion_main!(benches);
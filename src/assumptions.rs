@@ -0,0 +1,1080 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use num_traits::{CheckedAdd, CheckedDiv, CheckedMul};
+
+use crate::expression::{BinaryOperationType, Expression, Function, Rational, Variable};
+use crate::rewrite::{cos, exp, ln, mul, pow, sin, Bindings, Pattern, RewriteRule, RuleSet};
+use crate::simplifiable::fold_constants;
+
+/// One end of an interval.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Bound {
+    pub value: Rational,
+    pub closed: bool,
+}
+
+/// Interval of the real line, a missing bound being infinite.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Interval {
+    pub lower: Option<Bound>,
+    pub upper: Option<Bound>,
+}
+
+impl Interval {
+    pub fn open(lower: Rational, upper: Rational) -> Interval {
+        Interval {
+            lower: Some(Bound {
+                value: lower,
+                closed: false,
+            }),
+            upper: Some(Bound {
+                value: upper,
+                closed: false,
+            }),
+        }
+    }
+
+    pub fn closed(lower: Rational, upper: Rational) -> Interval {
+        Interval {
+            lower: Some(Bound {
+                value: lower,
+                closed: true,
+            }),
+            upper: Some(Bound {
+                value: upper,
+                closed: true,
+            }),
+        }
+    }
+
+    /// `(value, ∞)`
+    pub fn greater_than(value: Rational) -> Interval {
+        Interval {
+            lower: Some(Bound {
+                value,
+                closed: false,
+            }),
+            upper: None,
+        }
+    }
+
+    /// `[value, ∞)`
+    pub fn at_least(value: Rational) -> Interval {
+        Interval {
+            lower: Some(Bound {
+                value,
+                closed: true,
+            }),
+            upper: None,
+        }
+    }
+
+    pub fn contains(&self, value: Rational) -> bool {
+        self.lower
+            .is_none_or(|lower| value > lower.value || (lower.closed && value == lower.value))
+            && self
+                .upper
+                .is_none_or(|upper| value < upper.value || (upper.closed && value == upper.value))
+    }
+
+    pub fn intersection(&self, other: &Interval) -> Interval {
+        let lower = match (self.lower, other.lower) {
+            (Some(l1), Some(l2)) => Some(
+                if l1.value > l2.value || (l1.value == l2.value && !l1.closed) {
+                    l1
+                } else {
+                    l2
+                },
+            ),
+            (lower, None) | (None, lower) => lower,
+        };
+        let upper = match (self.upper, other.upper) {
+            (Some(u1), Some(u2)) => Some(
+                if u1.value < u2.value || (u1.value == u2.value && !u1.closed) {
+                    u1
+                } else {
+                    u2
+                },
+            ),
+            (upper, None) | (None, upper) => upper,
+        };
+        Interval { lower, upper }
+    }
+}
+
+/// What is known about the sign of an expression.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Sign {
+    Positive,
+    Negative,
+    Zero,
+    Nonnegative,
+    Nonpositive,
+    Nonzero,
+    Unknown,
+}
+
+impl Sign {
+    pub fn of(value: Rational) -> Sign {
+        if value > 0.into() {
+            Sign::Positive
+        } else if value < 0.into() {
+            Sign::Negative
+        } else {
+            Sign::Zero
+        }
+    }
+
+    pub fn is_positive(&self) -> bool {
+        *self == Sign::Positive
+    }
+
+    pub fn is_negative(&self) -> bool {
+        *self == Sign::Negative
+    }
+
+    pub fn is_nonnegative(&self) -> bool {
+        matches!(self, Sign::Positive | Sign::Zero | Sign::Nonnegative)
+    }
+
+    pub fn is_nonpositive(&self) -> bool {
+        matches!(self, Sign::Negative | Sign::Zero | Sign::Nonpositive)
+    }
+
+    pub fn is_nonzero(&self) -> bool {
+        matches!(self, Sign::Positive | Sign::Negative | Sign::Nonzero)
+    }
+
+    /// The signs still possible, as (negative, zero, positive).
+    fn possibilities(&self) -> (bool, bool, bool) {
+        match self {
+            Sign::Positive => (false, false, true),
+            Sign::Negative => (true, false, false),
+            Sign::Zero => (false, true, false),
+            Sign::Nonnegative => (false, true, true),
+            Sign::Nonpositive => (true, true, false),
+            Sign::Nonzero => (true, false, true),
+            Sign::Unknown => (true, true, true),
+        }
+    }
+
+    fn from_possibilities(negative: bool, zero: bool, positive: bool) -> Sign {
+        match (negative, zero, positive) {
+            (false, false, true) => Sign::Positive,
+            (true, false, false) => Sign::Negative,
+            (false, true, false) => Sign::Zero,
+            (false, true, true) => Sign::Nonnegative,
+            (true, true, false) => Sign::Nonpositive,
+            (true, false, true) => Sign::Nonzero,
+            // (false, false, false) is contradictory assumptions, nothing can be concluded
+            _ => Sign::Unknown,
+        }
+    }
+
+    /// Sign satisfying both `self` and `other`.
+    pub fn refine(&self, other: Sign) -> Sign {
+        let (n1, z1, p1) = self.possibilities();
+        let (n2, z2, p2) = other.possibilities();
+        Sign::from_possibilities(n1 && n2, z1 && z2, p1 && p2)
+    }
+
+    pub fn negate(&self) -> Sign {
+        let (negative, zero, positive) = self.possibilities();
+        Sign::from_possibilities(positive, zero, negative)
+    }
+
+    pub fn multiply(&self, other: Sign) -> Sign {
+        let (n1, z1, p1) = self.possibilities();
+        let (n2, z2, p2) = other.possibilities();
+        Sign::from_possibilities((n1 && p2) || (p1 && n2), z1 || z2, (n1 && n2) || (p1 && p2))
+    }
+
+    pub fn add(&self, other: Sign) -> Sign {
+        let (n1, z1, p1) = self.possibilities();
+        let (n2, z2, p2) = other.possibilities();
+        Sign::from_possibilities(n1 || n2, (z1 && z2) || (n1 && p2) || (p1 && n2), p1 || p2)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct VariableAssumptions {
+    real: bool,
+    integer: bool,
+    nonzero: bool,
+    interval: Interval,
+}
+
+/// Linear combination of variables with rational coefficients.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Linear {
+    coefficients: BTreeMap<Variable, Rational>,
+    constant: Rational,
+}
+
+impl Linear {
+    fn constant(constant: Rational) -> Linear {
+        Linear {
+            coefficients: BTreeMap::new(),
+            constant,
+        }
+    }
+
+    fn variable(variable: Variable, coefficient: Rational) -> Linear {
+        Linear {
+            coefficients: BTreeMap::from([(variable, coefficient)]),
+            constant: 0.into(),
+        }
+    }
+
+    fn as_constant(&self) -> Option<Rational> {
+        self.coefficients
+            .values()
+            .all(|coefficient| *coefficient == 0.into())
+            .then_some(self.constant)
+    }
+
+    fn coefficient(&self, variable: Variable) -> Rational {
+        self.coefficients
+            .get(&variable)
+            .copied()
+            .unwrap_or(0.into())
+    }
+
+    // The arithmetic returns `None` on overflow, the bound then being unknown.
+
+    fn add(&self, other: &Linear) -> Option<Linear> {
+        let mut sum = self.clone();
+        for (variable, coefficient) in &other.coefficients {
+            let entry = sum.coefficients.entry(*variable).or_insert(0.into());
+            *entry = entry.checked_add(coefficient)?;
+        }
+        sum.constant = sum.constant.checked_add(&other.constant)?;
+        Some(sum)
+    }
+
+    fn scale(&self, factor: Rational) -> Option<Linear> {
+        Some(Linear {
+            coefficients: self
+                .coefficients
+                .iter()
+                .map(|(variable, coefficient)| Some((*variable, coefficient.checked_mul(&factor)?)))
+                .collect::<Option<_>>()?,
+            constant: self.constant.checked_mul(&factor)?,
+        })
+    }
+
+    fn sub(&self, other: &Linear) -> Option<Linear> {
+        self.add(&other.scale((-1).into())?)
+    }
+}
+
+/// Lower and upper linear bounds of an expression, when known.
+type Bounds = (Option<Linear>, Option<Linear>);
+
+/// What is known about the variables of an expression. Without assumptions a variable may
+/// be complex, so only identities valid everywhere apply.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Assumptions {
+    variables: BTreeMap<Variable, VariableAssumptions>,
+    /// Linear forms known to be positive.
+    relations: Vec<Linear>,
+}
+
+impl Assumptions {
+    pub fn new() -> Assumptions {
+        Assumptions::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.variables.is_empty() && self.relations.is_empty()
+    }
+
+    fn update<F: FnOnce(&mut VariableAssumptions)>(mut self, variable: Variable, f: F) -> Self {
+        let assumptions = self.variables.entry(variable).or_default();
+        assumptions.real = true;
+        f(assumptions);
+        self
+    }
+
+    pub fn real(self, variable: Variable) -> Assumptions {
+        self.update(variable, |_| {})
+    }
+
+    pub fn integer(self, variable: Variable) -> Assumptions {
+        self.update(variable, |assumptions| assumptions.integer = true)
+    }
+
+    pub fn nonzero(self, variable: Variable) -> Assumptions {
+        self.update(variable, |assumptions| assumptions.nonzero = true)
+    }
+
+    pub fn positive(self, variable: Variable) -> Assumptions {
+        self.in_interval(variable, Interval::greater_than(0.into()))
+    }
+
+    pub fn nonnegative(self, variable: Variable) -> Assumptions {
+        self.in_interval(variable, Interval::at_least(0.into()))
+    }
+
+    pub fn in_interval(self, variable: Variable, interval: Interval) -> Assumptions {
+        self.update(variable, |assumptions| {
+            assumptions.interval = assumptions.interval.intersection(&interval)
+        })
+    }
+
+    /// `greater > smaller`, both being real.
+    pub fn greater_than(self, greater: Variable, smaller: Variable) -> Assumptions {
+        let mut assumptions = self.real(greater).real(smaller);
+        assumptions
+            .relations
+            .extend(Linear::variable(greater, 1.into()).sub(&Linear::variable(smaller, 1.into())));
+        assumptions
+    }
+
+    /// Interval of a real variable, `None` when it is not known to be real.
+    pub fn interval(&self, variable: Variable) -> Option<Interval> {
+        self.variables
+            .get(&variable)
+            .filter(|assumptions| assumptions.real)
+            .map(|assumptions| assumptions.interval)
+    }
+
+    fn variable_sign(&self, variable: Variable) -> Sign {
+        let Some(assumptions) = self.variables.get(&variable) else {
+            return Sign::Unknown;
+        };
+        let mut sign = if assumptions.nonzero {
+            Sign::Nonzero
+        } else {
+            Sign::Unknown
+        };
+        let interval = assumptions.interval;
+        if let Some(lower) = interval.lower {
+            sign = sign.refine(match Sign::of(lower.value) {
+                Sign::Positive => Sign::Positive,
+                Sign::Zero if !lower.closed => Sign::Positive,
+                Sign::Zero => Sign::Nonnegative,
+                _ => Sign::Unknown,
+            });
+        }
+        if let Some(upper) = interval.upper {
+            sign = sign.refine(match Sign::of(upper.value) {
+                Sign::Negative => Sign::Negative,
+                Sign::Zero if !upper.closed => Sign::Negative,
+                Sign::Zero => Sign::Nonpositive,
+                _ => Sign::Unknown,
+            });
+        }
+        sign
+    }
+
+    pub fn is_real(&self, expression: &Expression) -> bool {
+        match expression {
+            Expression::Constant(_) => true,
+            Expression::NamedConstant(name) => matches!(name.as_str(), "pi" | "e"),
+            Expression::Monomial(m) => self.interval(m.variable).is_some(),
+            Expression::BinaryOperation(operation) => {
+                let (left, right) = (&*operation.left_value, &*operation.right_value);
+                if !self.is_real(left) || !self.is_real(right) {
+                    return false;
+                }
+                match operation.operation {
+                    BinaryOperationType::Power => match right {
+                        Expression::Constant(exponent) if exponent.is_integer() => true,
+                        _ => self.sign(left).is_positive(),
+                    },
+                    _ => true,
+                }
+            }
+            Expression::Function {
+                function,
+                expression,
+            } => match function {
+                Function::Logarithm => self.sign(expression).is_positive(),
+                _ => self.is_real(expression),
+            },
+            Expression::Sum(operands) | Expression::Product(operands) => {
+                operands.iter().all(|operand| self.is_real(operand))
+            }
+        }
+    }
+
+    pub fn is_integer(&self, expression: &Expression) -> bool {
+        let is_integer_variable = |variable: &Variable| {
+            self.variables
+                .get(variable)
+                .is_some_and(|assumptions| assumptions.integer)
+        };
+        match expression {
+            Expression::Constant(value) => value.is_integer(),
+            Expression::Monomial(m) => m.factor.is_integer() && is_integer_variable(&m.variable),
+            Expression::BinaryOperation(operation) => match operation.operation {
+                BinaryOperationType::Addition
+                | BinaryOperationType::Substraction
+                | BinaryOperationType::Multiplication => {
+                    self.is_integer(&operation.left_value)
+                        && self.is_integer(&operation.right_value)
+                }
+                BinaryOperationType::Power => {
+                    self.is_integer(&operation.left_value)
+                        && matches!(*operation.right_value, Expression::Constant(exponent) if exponent.is_integer() && exponent >= 0.into())
+                }
+                BinaryOperationType::Division => false,
+            },
+            Expression::Sum(operands) | Expression::Product(operands) => {
+                operands.iter().all(|operand| self.is_integer(operand))
+            }
+            Expression::NamedConstant(_) | Expression::Function { .. } => false,
+        }
+    }
+
+    /// Sign of a real expression, from the signs of its factors and from linear bounds of its
+    /// sums, e.g. `R + r cos v > 0` when `R > r > 0`.
+    pub fn sign(&self, expression: &Expression) -> Sign {
+        let structural = self.structural_sign(expression);
+        if matches!(structural, Sign::Positive | Sign::Negative | Sign::Zero) {
+            return structural;
+        }
+        if !self.is_real(expression) {
+            return structural;
+        }
+        let (lower, upper) = self.bounds(expression);
+        let mut sign = structural;
+        if let Some(lower) = lower {
+            if self.proves_positive(&lower, true) {
+                sign = sign.refine(Sign::Positive);
+            } else if self.proves_positive(&lower, false) {
+                sign = sign.refine(Sign::Nonnegative);
+            }
+        }
+        if let Some(negated) = upper.and_then(|upper| upper.scale((-1).into())) {
+            if self.proves_positive(&negated, true) {
+                sign = sign.refine(Sign::Negative);
+            } else if self.proves_positive(&negated, false) {
+                sign = sign.refine(Sign::Nonpositive);
+            }
+        }
+        sign
+    }
+
+    fn structural_sign(&self, expression: &Expression) -> Sign {
+        match expression {
+            Expression::Constant(value) => Sign::of(*value),
+            Expression::NamedConstant(name) => match name.as_str() {
+                "pi" | "e" => Sign::Positive,
+                _ => Sign::Unknown,
+            },
+            Expression::Monomial(m) => Sign::of(m.factor).multiply(self.power_sign(
+                self.variable_sign(m.variable),
+                m.power.into(),
+                m.variable,
+            )),
+            Expression::BinaryOperation(operation) => {
+                let left = self.sign(&operation.left_value);
+                let right = self.sign(&operation.right_value);
+                match operation.operation {
+                    BinaryOperationType::Addition => left.add(right),
+                    BinaryOperationType::Substraction => left.add(right.negate()),
+                    BinaryOperationType::Multiplication => left.multiply(right),
+                    // the quotient is only defined where the denominator is nonzero
+                    BinaryOperationType::Division => left.multiply(right.refine(Sign::Nonzero)),
+                    BinaryOperationType::Power => match &*operation.right_value {
+                        Expression::Constant(exponent) if exponent.is_integer() => {
+                            if self.is_real(&operation.left_value) {
+                                let sign = self.integer_power_sign(left, *exponent.numer());
+                                if *exponent.numer() < 0 {
+                                    sign.refine(Sign::Nonzero)
+                                } else {
+                                    sign
+                                }
+                            } else {
+                                Sign::Unknown
+                            }
+                        }
+                        // principal roots of nonnegative numbers
+                        Expression::Constant(_) if left.is_nonnegative() => {
+                            if left.is_positive() {
+                                Sign::Positive
+                            } else {
+                                Sign::Nonnegative
+                            }
+                        }
+                        _ if left.is_positive() && self.is_real(&operation.right_value) => {
+                            Sign::Positive
+                        }
+                        _ => Sign::Unknown,
+                    },
+                }
+            }
+            Expression::Function {
+                function,
+                expression,
+            } => match function {
+                Function::Exponential if self.is_real(expression) => Sign::Positive,
+                Function::AbsoluteValue => {
+                    if self.sign(expression).is_nonzero() {
+                        Sign::Positive
+                    } else {
+                        Sign::Nonnegative
+                    }
+                }
+                _ => Sign::Unknown,
+            },
+            Expression::Sum(operands) => operands
+                .iter()
+                .map(|operand| self.sign(operand))
+                .reduce(|left, right| left.add(right))
+                .unwrap_or(Sign::Zero),
+            Expression::Product(operands) => operands
+                .iter()
+                .map(|operand| self.sign(operand))
+                .reduce(|left, right| left.multiply(right))
+                .unwrap_or(Sign::Positive),
+        }
+    }
+
+    fn power_sign(&self, base: Sign, exponent: i64, variable: Variable) -> Sign {
+        if self.interval(variable).is_none() {
+            return Sign::Unknown;
+        }
+        self.integer_power_sign(base, exponent)
+    }
+
+    fn integer_power_sign(&self, base: Sign, exponent: i64) -> Sign {
+        if exponent == 0 {
+            return Sign::Positive;
+        }
+        if exponent % 2 == 0 {
+            if base.is_nonzero() {
+                Sign::Positive
+            } else if base == Sign::Zero {
+                Sign::Zero
+            } else {
+                Sign::Nonnegative
+            }
+        } else {
+            base
+        }
+    }
+
+    fn bounds(&self, expression: &Expression) -> Bounds {
+        match expression {
+            Expression::Constant(value) => (
+                Some(Linear::constant(*value)),
+                Some(Linear::constant(*value)),
+            ),
+            Expression::NamedConstant(name) => match name.as_str() {
+                "pi" => (
+                    Some(Linear::constant(Rational::new(157, 50))),
+                    Some(Linear::constant(Rational::new(22, 7))),
+                ),
+                "e" => (
+                    Some(Linear::constant(Rational::new(271, 100))),
+                    Some(Linear::constant(Rational::new(68, 25))),
+                ),
+                _ => (None, None),
+            },
+            Expression::Monomial(m) => {
+                let Some(interval) = self.interval(m.variable) else {
+                    return (None, None);
+                };
+                let (lower, upper) = if m.power == 1 {
+                    let linear = Linear::variable(m.variable, 1.into());
+                    (Some(linear.clone()), Some(linear))
+                } else {
+                    let bound = |bound: Option<Bound>| bound.map(|bound| bound.value);
+                    numeric_power(bound(interval.lower), bound(interval.upper), m.power.into())
+                };
+                scale_bounds((lower, upper), m.factor)
+            }
+            Expression::BinaryOperation(operation) => self.operation_bounds(
+                operation.operation,
+                &operation.left_value,
+                &operation.right_value,
+            ),
+            Expression::Function {
+                function,
+                expression,
+            } => {
+                if !self.is_real(expression) {
+                    return (None, None);
+                }
+                match function {
+                    Function::Sine | Function::Cosine => (
+                        Some(Linear::constant((-1).into())),
+                        Some(Linear::constant(1.into())),
+                    ),
+                    Function::Exponential | Function::AbsoluteValue => {
+                        (Some(Linear::constant(0.into())), None)
+                    }
+                    Function::Logarithm => (None, None),
+                }
+            }
+            Expression::Sum(operands) | Expression::Product(operands) => {
+                let operation = match expression {
+                    Expression::Sum(_) => BinaryOperationType::Addition,
+                    _ => BinaryOperationType::Multiplication,
+                };
+                match operands.split_last() {
+                    None => self.bounds(&operation.identity_element()),
+                    Some((last, [])) => self.bounds(last),
+                    Some((last, rest)) => {
+                        let rest = match expression {
+                            Expression::Sum(_) => Expression::Sum(rest.to_vec()),
+                            _ => Expression::Product(rest.to_vec()),
+                        };
+                        self.operation_bounds(operation, &rest, last)
+                    }
+                }
+            }
+        }
+    }
+
+    fn operation_bounds(
+        &self,
+        operation: BinaryOperationType,
+        left: &Expression,
+        right: &Expression,
+    ) -> Bounds {
+        let (left_lower, left_upper) = self.bounds(left);
+        let (right_lower, right_upper) = self.bounds(right);
+        match operation {
+            BinaryOperationType::Addition => (
+                zip_with(left_lower, right_lower, |l, r| l.add(&r)),
+                zip_with(left_upper, right_upper, |l, r| l.add(&r)),
+            ),
+            BinaryOperationType::Substraction => (
+                zip_with(left_lower, right_upper, |l, r| l.sub(&r)),
+                zip_with(left_upper, right_lower, |l, r| l.sub(&r)),
+            ),
+            BinaryOperationType::Multiplication => {
+                let numeric = |bounds: &Bounds| match bounds {
+                    (Some(lower), Some(upper)) => lower.as_constant().zip(upper.as_constant()),
+                    _ => None,
+                };
+                let left_bounds = (left_lower, left_upper);
+                let right_bounds = (right_lower, right_upper);
+                if let Some(range) = numeric(&right_bounds) {
+                    self.scaled_bounds(left, left_bounds, range)
+                } else if let Some(range) = numeric(&left_bounds) {
+                    self.scaled_bounds(right, right_bounds, range)
+                } else {
+                    (None, None)
+                }
+            }
+            BinaryOperationType::Division => match right {
+                Expression::Constant(value) if *value != 0.into() => {
+                    scale_bounds((left_lower, left_upper), value.recip())
+                }
+                _ => (None, None),
+            },
+            BinaryOperationType::Power => match right {
+                Expression::Constant(exponent)
+                    if exponent.is_integer() && *exponent.numer() > 0 =>
+                {
+                    let constant =
+                        |bound: Option<Linear>| bound.and_then(|bound| bound.as_constant());
+                    let (lower, upper) = (constant(left_lower), constant(left_upper));
+                    if lower.is_some() && upper.is_some() {
+                        numeric_power(lower, upper, *exponent.numer())
+                    } else if exponent.numer() % 2 == 0 {
+                        (Some(Linear::constant(0.into())), None)
+                    } else {
+                        (None, None)
+                    }
+                }
+                Expression::Constant(_) if self.sign(left).is_nonnegative() => {
+                    (Some(Linear::constant(0.into())), None)
+                }
+                _ => (None, None),
+            },
+        }
+    }
+
+    /// Bounds of `expression * g` for `g` in the numeric range `[low, high]`.
+    fn scaled_bounds(
+        &self,
+        expression: &Expression,
+        (lower, upper): Bounds,
+        (low, high): (Rational, Rational),
+    ) -> Bounds {
+        if low == high {
+            return scale_bounds((lower, upper), low);
+        }
+        let zero = Rational::from(0);
+        let sign = self.sign(expression);
+        if sign.is_nonnegative() {
+            (
+                if low >= zero {
+                    lower.clone().and_then(|l| l.scale(low))
+                } else {
+                    upper.clone().and_then(|u| u.scale(low))
+                },
+                if high >= zero {
+                    upper.and_then(|u| u.scale(high))
+                } else {
+                    lower.and_then(|l| l.scale(high))
+                },
+            )
+        } else if sign.is_nonpositive() {
+            (
+                if high >= zero {
+                    lower.clone().and_then(|l| l.scale(high))
+                } else {
+                    upper.clone().and_then(|u| u.scale(high))
+                },
+                if low >= zero {
+                    upper.and_then(|u| u.scale(low))
+                } else {
+                    lower.and_then(|l| l.scale(low))
+                },
+            )
+        } else {
+            (None, None)
+        }
+    }
+
+    /// Whether `linear` is positive (or nonnegative when not `strict`): a nonnegative multiple
+    /// of at most one relation is subtracted and the remainder bounded by the variable
+    /// intervals.
+    fn proves_positive(&self, linear: &Linear, strict: bool) -> bool {
+        let mut candidates = vec![(Rational::from(0), Linear::default())];
+        for relation in &self.relations {
+            for (variable, coefficient) in &relation.coefficients {
+                let Some(multiple) = linear.coefficient(*variable).checked_div(coefficient) else {
+                    continue;
+                };
+                if multiple > 0.into() {
+                    candidates.extend(relation.scale(multiple).map(|scaled| (multiple, scaled)));
+                }
+            }
+        }
+        candidates.into_iter().any(|(multiple, subtracted)| {
+            let Some((value, strict_bound)) = linear
+                .sub(&subtracted)
+                .and_then(|difference| self.lower_bound(&difference))
+            else {
+                return false;
+            };
+            value > 0.into()
+                || (value == 0.into() && (!strict || strict_bound || multiple > 0.into()))
+        })
+    }
+
+    /// Numeric lower bound of `linear` over the variable intervals, and whether it is strict.
+    fn lower_bound(&self, linear: &Linear) -> Option<(Rational, bool)> {
+        let mut value = linear.constant;
+        let mut strict = false;
+        for (variable, coefficient) in &linear.coefficients {
+            if *coefficient == 0.into() {
+                continue;
+            }
+            let interval = self.interval(*variable)?;
+            let bound = if *coefficient > 0.into() {
+                interval.lower?
+            } else {
+                interval.upper?
+            };
+            value = value.checked_add(&coefficient.checked_mul(&bound.value)?)?;
+            strict |= !bound.closed;
+        }
+        Some((value, strict))
+    }
+
+    /// Domain-dependent rewrites that hold under these assumptions.
+    pub fn rule_set(&self) -> RuleSet {
+        let assumptions = Arc::new(self.clone());
+        let x = || Pattern::wildcard("x");
+        let half = || Pattern::Exact(Expression::Constant(Rational::new(1, 2)));
+        let holds = |test: fn(&Assumptions, &Expression) -> bool, name: &'static str| {
+            let assumptions = assumptions.clone();
+            move |bindings: &Bindings| {
+                bindings
+                    .get(name)
+                    .is_some_and(|bound| test(&assumptions, bound))
+            }
+        };
+        let is_real: fn(&Assumptions, &Expression) -> bool = |a, e| a.is_real(e);
+        let is_integer: fn(&Assumptions, &Expression) -> bool = |a, e| a.is_integer(e);
+        let is_positive: fn(&Assumptions, &Expression) -> bool = |a, e| a.sign(e).is_positive();
+        let is_nonnegative: fn(&Assumptions, &Expression) -> bool =
+            |a, e| a.is_real(e) && a.sign(e).is_nonnegative();
+        let is_nonpositive: fn(&Assumptions, &Expression) -> bool =
+            |a, e| a.is_real(e) && a.sign(e).is_nonpositive();
+        let both_nonnegative = {
+            let a = holds(is_nonnegative, "a");
+            let b = holds(is_nonnegative, "b");
+            move |bindings: &Bindings| a(bindings) && b(bindings)
+        };
+        let pi = || Pattern::Exact(Expression::NamedConstant("pi".to_string()));
+        RuleSet::new(
+            "assumptions",
+            vec![
+                RewriteRule::new(
+                    "square-root-of-square",
+                    pow(pow(x(), Pattern::integer(2)), half()),
+                    abs(x()),
+                )
+                .with_condition(holds(is_real, "x")),
+                RewriteRule::new(
+                    "square-of-square-root",
+                    pow(pow(x(), half()), Pattern::integer(2)),
+                    x(),
+                )
+                .with_condition(holds(is_nonnegative, "x")),
+                RewriteRule::new(
+                    "square-root-of-product",
+                    pow(mul(Pattern::wildcard("a"), Pattern::wildcard("b")), half()),
+                    mul(
+                        pow(Pattern::wildcard("a"), half()),
+                        pow(Pattern::wildcard("b"), half()),
+                    ),
+                )
+                .with_condition(both_nonnegative),
+                RewriteRule::new("absolute-value-of-nonnegative", abs(x()), x())
+                    .with_condition(holds(is_nonnegative, "x")),
+                RewriteRule::new(
+                    "absolute-value-of-nonpositive",
+                    abs(x()),
+                    mul(Pattern::integer(-1), x()),
+                )
+                .with_condition(holds(is_nonpositive, "x")),
+                RewriteRule::new("logarithm-of-exponential", ln(exp(x())), x())
+                    .with_condition(holds(is_real, "x")),
+                RewriteRule::new("exponential-of-logarithm", exp(ln(x())), x())
+                    .with_condition(holds(is_positive, "x")),
+                RewriteRule::new(
+                    "power-of-power",
+                    pow(pow(x(), Pattern::wildcard("a")), Pattern::wildcard("n")),
+                    pow(x(), mul(Pattern::wildcard("a"), Pattern::wildcard("n"))),
+                )
+                .with_condition(holds(is_integer, "n")),
+                RewriteRule::new(
+                    "sine-of-integer-multiple-of-pi",
+                    sin(mul(Pattern::wildcard("n"), pi())),
+                    Pattern::integer(0),
+                )
+                .with_condition(holds(is_integer, "n")),
+                RewriteRule::new(
+                    "cosine-of-integer-multiple-of-pi",
+                    cos(mul(Pattern::wildcard("n"), pi())),
+                    pow(Pattern::integer(-1), Pattern::wildcard("n")),
+                )
+                .with_condition(holds(is_integer, "n")),
+            ],
+        )
+    }
+}
+
+fn abs(argument: Pattern) -> Pattern {
+    Pattern::function(Function::AbsoluteValue, argument)
+}
+
+fn zip_with<F: FnOnce(Linear, Linear) -> Option<Linear>>(
+    left: Option<Linear>,
+    right: Option<Linear>,
+    f: F,
+) -> Option<Linear> {
+    left.zip(right).and_then(|(left, right)| f(left, right))
+}
+
+fn scale_bounds((lower, upper): Bounds, factor: Rational) -> Bounds {
+    let scale = |bound: Option<Linear>| bound.and_then(|bound| bound.scale(factor));
+    if factor < 0.into() {
+        (scale(upper), scale(lower))
+    } else {
+        (scale(lower), scale(upper))
+    }
+}
+
+/// Bounds of `x^exponent` for `x` in `[lower, upper]`, `exponent` positive.
+fn numeric_power(lower: Option<Rational>, upper: Option<Rational>, exponent: i64) -> Bounds {
+    let power = |value: Rational| {
+        fold_constants(BinaryOperationType::Power, &value, &exponent.into()).map(Linear::constant)
+    };
+    if exponent % 2 == 1 {
+        return (lower.and_then(power), upper.and_then(power));
+    }
+    let zero = Rational::from(0);
+    match (lower, upper) {
+        (Some(lower), upper) if lower >= zero => (power(lower), upper.and_then(power)),
+        (lower, Some(upper)) if upper <= zero => (power(upper), lower.and_then(power)),
+        (Some(lower), Some(upper)) => (
+            Some(Linear::constant(zero)),
+            power(if -lower > upper { lower } else { upper }),
+        ),
+        _ => (Some(Linear::constant(zero)), None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::expression::Monomial;
+    use crate::simplifiable::{Simplifiable, SimplificationOptions};
+
+    fn variable(variable: Variable) -> Expression {
+        Expression::monomial(1, variable, 1)
+    }
+
+    fn simplify(expression: &Expression, assumptions: Assumptions) -> Expression {
+        expression
+            .simplify_with(&SimplificationOptions::default().with_assumptions(assumptions))
+            .expression
+    }
+
+    #[test]
+    fn test_sign() {
+        let assumptions = Assumptions::new()
+            .real('v')
+            .positive('r')
+            .greater_than('R', 'r')
+            .in_interval('t', Interval::closed((-1).into(), 0.into()));
+        // R + r cos(v)
+        let radius = Expression::binary(
+            BinaryOperationType::Addition,
+            variable('R'),
+            Expression::binary(
+                BinaryOperationType::Multiplication,
+                variable('r'),
+                Expression::function(Function::Cosine, variable('v')),
+            ),
+        );
+        assert_eq!(assumptions.sign(&radius), Sign::Positive);
+        assert_eq!(Assumptions::new().sign(&radius), Sign::Unknown);
+        // R - 2r may have either sign
+        let difference = Expression::binary(
+            BinaryOperationType::Substraction,
+            variable('R'),
+            Expression::Monomial(Monomial {
+                factor: 2.into(),
+                variable: 'r',
+                power: 1,
+            }),
+        );
+        assert_eq!(assumptions.sign(&difference), Sign::Unknown);
+        assert_eq!(assumptions.sign(&variable('t')), Sign::Nonpositive);
+        // t - 1 < 0
+        let shifted = Expression::binary(
+            BinaryOperationType::Substraction,
+            variable('t'),
+            Expression::Constant(1.into()),
+        );
+        assert_eq!(assumptions.sign(&shifted), Sign::Negative);
+        // v^2 >= 0, exp(v) > 0
+        let square = Expression::Monomial(Monomial {
+            factor: 3.into(),
+            variable: 'v',
+            power: 2,
+        });
+        assert_eq!(assumptions.sign(&square), Sign::Nonnegative);
+        assert_eq!(
+            assumptions.sign(&Expression::function(Function::Exponential, variable('v'))),
+            Sign::Positive
+        );
+    }
+
+    #[test]
+    fn test_square_root_of_square() {
+        // sqrt(x^2)
+        let expr = Expression::binary(
+            BinaryOperationType::Power,
+            Expression::Monomial(Monomial {
+                factor: 1.into(),
+                variable: 'x',
+                power: 2,
+            }),
+            Expression::Constant(Rational::new(1, 2)),
+        );
+        assert_eq!(simplify(&expr, Assumptions::new().integer('n')), expr);
+        assert_eq!(
+            simplify(&expr, Assumptions::new().real('x')),
+            Expression::function(Function::AbsoluteValue, variable('x'))
+        );
+        assert_eq!(
+            simplify(&expr, Assumptions::new().positive('x')),
+            variable('x')
+        );
+        assert_eq!(
+            simplify(
+                &expr,
+                Assumptions::new().in_interval('x', Interval::closed((-2).into(), 0.into()))
+            ),
+            Expression::Monomial(Monomial {
+                factor: (-1).into(),
+                variable: 'x',
+                power: 1,
+            })
+        );
+    }
+
+    #[test]
+    fn test_logarithm_and_integer_rewrites() {
+        let expr = Expression::function(
+            Function::Logarithm,
+            Expression::function(Function::Exponential, variable('x')),
+        );
+        assert_eq!(simplify(&expr, Assumptions::new().integer('n')), expr);
+        assert_eq!(simplify(&expr, Assumptions::new().real('x')), variable('x'));
+
+        // sin(n pi)
+        let expr = Expression::function(
+            Function::Sine,
+            Expression::binary(
+                BinaryOperationType::Multiplication,
+                variable('n'),
+                Expression::NamedConstant("pi".to_string()),
+            ),
+        );
+        assert_eq!(simplify(&expr, Assumptions::new().real('n')), expr);
+        assert_eq!(
+            simplify(&expr, Assumptions::new().integer('n')),
+            Expression::Constant(0.into())
+        );
+    }
+
+    #[test]
+    fn test_interval() {
+        let interval = Interval::open(0.into(), 1.into())
+            .intersection(&Interval::at_least(Rational::new(1, 2)));
+        assert!(interval.contains(Rational::new(1, 2)));
+        assert!(!interval.contains(1.into()));
+        assert!(!interval.contains(Rational::new(1, 4)));
+    }
+
+    #[test]
+    fn test_bounds_overflow() {
+        let huge = Rational::from(i64::MAX);
+        let assumptions = Assumptions::new().in_interval(
+            'x',
+            Interval {
+                lower: Some(Bound {
+                    value: huge,
+                    closed: true,
+                }),
+                upper: None,
+            },
+        );
+        let square = Expression::binary(
+            BinaryOperationType::Power,
+            variable('x'),
+            Expression::Constant(2.into()),
+        );
+        // The bounds overflow and are dropped, the signs still follow from the structure.
+        assert_eq!(assumptions.sign(&square), Sign::Positive);
+        let sum = Expression::binary(
+            BinaryOperationType::Addition,
+            variable('x'),
+            Expression::Constant(huge),
+        );
+        assert_eq!(assumptions.sign(&sum), Sign::Positive);
+    }
+}
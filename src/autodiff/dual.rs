@@ -0,0 +1,135 @@
+use crate::autodiff::Scalar;
+
+/// Value and gradient with respect to several seeded variables, carried together so that a
+/// single evaluation gives the whole gradient. Constants have no derivatives stored.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dual {
+    pub value: f64,
+    pub derivatives: Vec<f64>,
+}
+
+impl Dual {
+    /// The `index`-th of `count` independent variables.
+    pub fn variable(value: f64, index: usize, count: usize) -> Dual {
+        let mut derivatives = vec![0.; count];
+        derivatives[index] = 1.;
+        Dual { value, derivatives }
+    }
+
+    fn combine(&self, other: &Dual, f: impl Fn(f64, f64) -> f64) -> Vec<f64> {
+        let length = self.derivatives.len().max(other.derivatives.len());
+        let derivative = |dual: &Dual, i: usize| dual.derivatives.get(i).copied().unwrap_or(0.);
+        (0..length)
+            .map(|i| f(derivative(self, i), derivative(other, i)))
+            .collect()
+    }
+}
+
+impl Scalar for Dual {
+    fn constant(value: f64) -> Dual {
+        Dual {
+            value,
+            derivatives: vec![],
+        }
+    }
+
+    fn value(&self) -> f64 {
+        self.value
+    }
+
+    fn add(&self, other: &Dual) -> Dual {
+        Dual {
+            value: self.value + other.value,
+            derivatives: self.combine(other, |left, right| left + right),
+        }
+    }
+
+    fn sub(&self, other: &Dual) -> Dual {
+        Dual {
+            value: self.value - other.value,
+            derivatives: self.combine(other, |left, right| left - right),
+        }
+    }
+
+    fn mul(&self, other: &Dual) -> Dual {
+        Dual {
+            value: self.value * other.value,
+            derivatives: self.combine(other, |left, right| left * other.value + self.value * right),
+        }
+    }
+
+    fn chain(&self, value: f64, first: f64, _second: f64) -> Dual {
+        Dual {
+            value,
+            derivatives: self
+                .derivatives
+                .iter()
+                .map(|derivative| first * derivative)
+                .collect(),
+        }
+    }
+}
+
+/// `value + first ε1 + second ε2 + mixed ε1ε2` with `ε1² = ε2² = 0`: seeding `ε1` on one
+/// variable and `ε2` on another gives their mixed second derivative exactly.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HyperDual {
+    pub value: f64,
+    pub first: f64,
+    pub second: f64,
+    pub mixed: f64,
+}
+
+impl Scalar for HyperDual {
+    fn constant(value: f64) -> HyperDual {
+        HyperDual {
+            value,
+            first: 0.,
+            second: 0.,
+            mixed: 0.,
+        }
+    }
+
+    fn value(&self) -> f64 {
+        self.value
+    }
+
+    fn add(&self, other: &HyperDual) -> HyperDual {
+        HyperDual {
+            value: self.value + other.value,
+            first: self.first + other.first,
+            second: self.second + other.second,
+            mixed: self.mixed + other.mixed,
+        }
+    }
+
+    fn sub(&self, other: &HyperDual) -> HyperDual {
+        HyperDual {
+            value: self.value - other.value,
+            first: self.first - other.first,
+            second: self.second - other.second,
+            mixed: self.mixed - other.mixed,
+        }
+    }
+
+    fn mul(&self, other: &HyperDual) -> HyperDual {
+        HyperDual {
+            value: self.value * other.value,
+            first: self.first * other.value + self.value * other.first,
+            second: self.second * other.value + self.value * other.second,
+            mixed: self.mixed * other.value
+                + self.first * other.second
+                + self.second * other.first
+                + self.value * other.mixed,
+        }
+    }
+
+    fn chain(&self, value: f64, first: f64, second: f64) -> HyperDual {
+        HyperDual {
+            value,
+            first: first * self.first,
+            second: first * self.second,
+            mixed: first * self.mixed + second * self.first * self.second,
+        }
+    }
+}
@@ -0,0 +1,195 @@
+use std::collections::HashMap;
+
+use ndarray::Array2;
+
+use crate::autodiff::dual::{Dual, HyperDual};
+use crate::autodiff::{evaluate_scalar, GradientEvaluator, Scalar};
+use crate::evaluable::{evaluate_variable, EvaluationError};
+use crate::expression::{Expression, Variable};
+
+/// Forward-mode automatic differentiation: derivatives are propagated from the variables up
+/// through the expression during evaluation.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ForwardMode;
+
+impl GradientEvaluator for ForwardMode {
+    /// One evaluation with dual numbers carrying every partial derivative.
+    fn gradient(
+        &self,
+        expression: &Expression,
+        variables: &[Variable],
+        values: &HashMap<Variable, f64>,
+    ) -> Result<(f64, Vec<f64>), EvaluationError> {
+        let mut duals: HashMap<Variable, Dual> = values
+            .iter()
+            .map(|(variable, value)| (*variable, Dual::constant(*value)))
+            .collect();
+        for (index, variable) in variables.iter().enumerate() {
+            let value = evaluate_variable(*variable, values)?;
+            duals.insert(*variable, Dual::variable(value, index, variables.len()));
+        }
+        let dual = evaluate_scalar(expression, &duals)?;
+        let mut gradient = dual.derivatives;
+        gradient.resize(variables.len(), 0.);
+        Ok((dual.value, gradient))
+    }
+}
+
+impl ForwardMode {
+    /// Hessian with respect to `variables`, one hyper-dual evaluation per pair of variables.
+    pub fn hessian(
+        &self,
+        expression: &Expression,
+        variables: &[Variable],
+        values: &HashMap<Variable, f64>,
+    ) -> Result<Array2<f64>, EvaluationError> {
+        let size = variables.len();
+        let mut hessian = Array2::zeros((size, size));
+        for i in 0..size {
+            for j in i..size {
+                let mut hyper_duals: HashMap<Variable, HyperDual> = values
+                    .iter()
+                    .map(|(variable, value)| (*variable, HyperDual::constant(*value)))
+                    .collect();
+                let mut seed = |variable: Variable, first: f64, second: f64| {
+                    let value = evaluate_variable(variable, values)?;
+                    let entry = hyper_duals
+                        .entry(variable)
+                        .or_insert(HyperDual::constant(value));
+                    entry.first += first;
+                    entry.second += second;
+                    Ok::<(), EvaluationError>(())
+                };
+                seed(variables[i], 1., 0.)?;
+                seed(variables[j], 0., 1.)?;
+                let mixed = evaluate_scalar(expression, &hyper_duals)?.mixed;
+                hessian[[i, j]] = mixed;
+                hessian[[j, i]] = mixed;
+            }
+        }
+        Ok(hessian)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::calculus;
+    use crate::expression::{BinaryOperationType, Function, Rational};
+
+    /// sin(x y) + exp(x) / y + sqrt(x^2 + y^2)
+    fn expression() -> Expression {
+        Expression::Sum(vec![
+            Expression::Function {
+                function: Function::Sine,
+                expression: Box::new(Expression::binary(
+                    BinaryOperationType::Multiplication,
+                    Expression::monomial(1, 'x', 1),
+                    Expression::monomial(1, 'y', 1),
+                )),
+            },
+            Expression::binary(
+                BinaryOperationType::Division,
+                Expression::Function {
+                    function: Function::Exponential,
+                    expression: Box::new(Expression::monomial(1, 'x', 1)),
+                },
+                Expression::monomial(1, 'y', 1),
+            ),
+            Expression::binary(
+                BinaryOperationType::Power,
+                Expression::Sum(vec![
+                    Expression::monomial(1, 'x', 2),
+                    Expression::monomial(1, 'y', 2),
+                ]),
+                Expression::Constant(Rational::new(1, 2)),
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_exact_derivatives_of_a_polynomial() {
+        // x^3 at 2
+        let values = HashMap::from([('x', 2.)]);
+        let (value, gradient) = ForwardMode
+            .gradient(&Expression::monomial(1, 'x', 3), &['x'], &values)
+            .unwrap();
+        assert_eq!((value, gradient), (8., vec![12.]));
+        let hessian = ForwardMode
+            .hessian(&Expression::monomial(1, 'x', 3), &['x'], &values)
+            .unwrap();
+        assert_eq!(hessian[[0, 0]], 12.);
+    }
+
+    #[test]
+    fn test_derivatives_at_zero() {
+        // x y at (0, 1)
+        let product = Expression::binary(
+            BinaryOperationType::Multiplication,
+            Expression::monomial(1, 'x', 1),
+            Expression::monomial(1, 'y', 1),
+        );
+        let values = HashMap::from([('x', 0.), ('y', 1.)]);
+        assert_eq!(
+            ForwardMode.gradient(&product, &['x', 'y'], &values),
+            Ok((0., vec![1., 0.]))
+        );
+        let hessian = ForwardMode.hessian(&product, &['x', 'y'], &values).unwrap();
+        assert_eq!(hessian, ndarray::array![[0., 1.], [1., 0.]]);
+
+        // x^0 and x^2 at 0
+        let values = HashMap::from([('x', 0.)]);
+        let constant = Expression::binary(
+            BinaryOperationType::Power,
+            Expression::monomial(1, 'x', 1),
+            Expression::Constant(0.into()),
+        );
+        assert_eq!(
+            ForwardMode.gradient(&constant, &['x'], &values),
+            Ok((1., vec![0.]))
+        );
+        assert_eq!(
+            ForwardMode.hessian(&constant, &['x'], &values).unwrap()[[0, 0]],
+            0.
+        );
+        assert_eq!(
+            ForwardMode
+                .hessian(&Expression::monomial(1, 'x', 2), &['x'], &values)
+                .unwrap()[[0, 0]],
+            2.
+        );
+    }
+
+    #[test]
+    fn test_matches_symbolic_derivatives() {
+        let expr = expression();
+        let variables = ['x', 'y'];
+        let values = HashMap::from([('x', 0.7), ('y', -1.3)]);
+        let (value, gradient) = ForwardMode.gradient(&expr, &variables, &values).unwrap();
+        let symbolic_gradient = calculus::gradient(&expr, &variables)
+            .evaluate(&values)
+            .unwrap();
+        assert!(
+            (value - crate::evaluable::Evaluable::evaluate(&expr, &values).unwrap()).abs() < 1e-12
+        );
+        for (forward, symbolic) in gradient.iter().zip(symbolic_gradient.iter()) {
+            assert!((forward - symbolic).abs() < 1e-12);
+        }
+        let hessian = ForwardMode.hessian(&expr, &variables, &values).unwrap();
+        let symbolic_hessian = calculus::hessian(&expr, &variables)
+            .evaluate(&values)
+            .unwrap();
+        for (forward, symbolic) in hessian.iter().zip(symbolic_hessian.iter()) {
+            assert!((forward - symbolic).abs() < 1e-10);
+        }
+    }
+
+    #[test]
+    fn test_unbound_variable() {
+        assert_eq!(
+            ForwardMode.gradient(&Expression::monomial(1, 'x', 2), &['x'], &HashMap::new()),
+            Err(EvaluationError::UnboundVariable('x'))
+        );
+    }
+}
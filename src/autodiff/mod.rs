@@ -0,0 +1,182 @@
+//! Numerical derivatives computed alongside the value of an expression.
+
+pub mod dual;
+pub mod forward;
+pub mod reverse;
+
+use std::collections::HashMap;
+
+use crate::derivable::Derivable;
+use crate::evaluable::{evaluate_named_constant, rational_to_f64, Evaluable, EvaluationError};
+use crate::expression::{BinaryOperationType, Expression, Function, Variable};
+
+/// Strategy computing the value and gradient of an expression at a point. Variables not listed
+/// are held at their value in `values`.
+pub trait GradientEvaluator {
+    fn gradient(
+        &self,
+        expression: &Expression,
+        variables: &[Variable],
+        values: &HashMap<Variable, f64>,
+    ) -> Result<(f64, Vec<f64>), EvaluationError>;
+}
+
+/// Differentiates symbolically, then evaluates every partial derivative.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Symbolic;
+
+impl GradientEvaluator for Symbolic {
+    fn gradient(
+        &self,
+        expression: &Expression,
+        variables: &[Variable],
+        values: &HashMap<Variable, f64>,
+    ) -> Result<(f64, Vec<f64>), EvaluationError> {
+        let gradient = variables
+            .iter()
+            .map(|variable| expression.derive(*variable).evaluate(values))
+            .collect::<Result<_, _>>()?;
+        Ok((expression.evaluate(values)?, gradient))
+    }
+}
+
+/// Number type an expression can be evaluated in.
+pub trait Scalar: Clone {
+    fn constant(value: f64) -> Self;
+    fn value(&self) -> f64;
+    fn add(&self, other: &Self) -> Self;
+    fn sub(&self, other: &Self) -> Self;
+    fn mul(&self, other: &Self) -> Self;
+    /// Applies a function whose value, first and second derivatives at `self.value()` are
+    /// `value`, `first` and `second`.
+    fn chain(&self, value: f64, first: f64, second: f64) -> Self;
+
+    fn recip(&self) -> Self {
+        let x = self.value();
+        self.chain(1. / x, -1. / (x * x), 2. / (x * x * x))
+    }
+
+    fn div(&self, other: &Self) -> Self {
+        self.mul(&other.recip())
+    }
+
+    fn powi(&self, exponent: i32) -> Self {
+        let x = self.value();
+        let n = f64::from(exponent);
+        // A vanishing coefficient cancels the power, which is infinite at 0 when negative.
+        let term = |coefficient: f64, power: i32| {
+            if coefficient == 0. {
+                0.
+            } else {
+                coefficient * x.powi(power)
+            }
+        };
+        self.chain(
+            x.powi(exponent),
+            term(n, exponent - 1),
+            term(n * (n - 1.), exponent - 2),
+        )
+    }
+
+    /// `self^exponent` as `exp(exponent ln(self))`, defined for positive bases.
+    fn powf(&self, exponent: &Self) -> Self {
+        exponent
+            .mul(&apply_function(Function::Logarithm, self))
+            .chain_function(Function::Exponential)
+    }
+
+    fn chain_function(&self, function: Function) -> Self {
+        apply_function(function, self)
+    }
+}
+
+fn apply_function<S: Scalar>(function: Function, x: &S) -> S {
+    let value = x.value();
+    match function {
+        Function::Sine => x.chain(value.sin(), value.cos(), -value.sin()),
+        Function::Cosine => x.chain(value.cos(), -value.sin(), -value.cos()),
+        Function::Exponential => x.chain(value.exp(), value.exp(), value.exp()),
+        Function::Logarithm => x.chain(value.ln(), 1. / value, -1. / (value * value)),
+        Function::AbsoluteValue => x.chain(value.abs(), value.signum(), 0.),
+    }
+}
+
+impl Scalar for f64 {
+    fn constant(value: f64) -> f64 {
+        value
+    }
+
+    fn value(&self) -> f64 {
+        *self
+    }
+
+    fn add(&self, other: &f64) -> f64 {
+        self + other
+    }
+
+    fn sub(&self, other: &f64) -> f64 {
+        self - other
+    }
+
+    fn mul(&self, other: &f64) -> f64 {
+        self * other
+    }
+
+    fn chain(&self, value: f64, _first: f64, _second: f64) -> f64 {
+        value
+    }
+}
+
+/// Evaluates `expression` in any `Scalar` type, variables taking the given values.
+pub fn evaluate_scalar<S: Scalar>(
+    expression: &Expression,
+    values: &HashMap<Variable, S>,
+) -> Result<S, EvaluationError> {
+    let variable = |variable: Variable| {
+        values
+            .get(&variable)
+            .cloned()
+            .ok_or(EvaluationError::UnboundVariable(variable))
+    };
+    Ok(match expression {
+        Expression::Constant(value) => S::constant(rational_to_f64(value)),
+        Expression::NamedConstant(name) => S::constant(evaluate_named_constant(name)?),
+        Expression::Monomial(m) => variable(m.variable)?
+            .powi(i32::from(m.power))
+            .mul(&S::constant(rational_to_f64(&m.factor))),
+        Expression::BinaryOperation(operation) => {
+            let left = evaluate_scalar(&operation.left_value, values)?;
+            match operation.operation {
+                BinaryOperationType::Power => match &*operation.right_value {
+                    // integer powers are defined for negative bases too
+                    Expression::Constant(exponent) if exponent.is_integer() => {
+                        left.powi(*exponent.numer() as i32)
+                    }
+                    exponent => left.powf(&evaluate_scalar(exponent, values)?),
+                },
+                operation_type => {
+                    let right = evaluate_scalar(&operation.right_value, values)?;
+                    match operation_type {
+                        BinaryOperationType::Addition => left.add(&right),
+                        BinaryOperationType::Substraction => left.sub(&right),
+                        BinaryOperationType::Multiplication => left.mul(&right),
+                        BinaryOperationType::Division => left.div(&right),
+                        BinaryOperationType::Power => unreachable!("handled above"),
+                    }
+                }
+            }
+        }
+        Expression::Function {
+            function,
+            expression,
+        } => evaluate_scalar(expression, values)?.chain_function(*function),
+        Expression::Sum(terms) => terms.iter().try_fold(S::constant(0.), |sum, term| {
+            Ok(sum.add(&evaluate_scalar(term, values)?))
+        })?,
+        Expression::Product(factors) => factors
+            .iter()
+            .try_fold(S::constant(1.), |product, factor| {
+                Ok(product.mul(&evaluate_scalar(factor, values)?))
+            })?,
+    })
+}
@@ -0,0 +1,200 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::autodiff::{evaluate_scalar, GradientEvaluator, Scalar};
+use crate::evaluable::{evaluate_variable, EvaluationError};
+use crate::expression::{Expression, Variable};
+
+/// Reverse-mode automatic differentiation: evaluation records every intermediate result on a
+/// tape, then a single backward sweep accumulates the adjoints of all variables at once.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ReverseMode;
+
+/// For every recorded node, its operands and the partial derivative with respect to each.
+type Tape = Rc<RefCell<Vec<Vec<(usize, f64)>>>>;
+
+/// Value recorded on a tape; constants are not recorded.
+#[derive(Clone)]
+struct Adjoint {
+    value: f64,
+    node: Option<(Tape, usize)>,
+}
+
+impl Adjoint {
+    fn record(value: f64, operands: &[(&Adjoint, f64)]) -> Adjoint {
+        let tape = operands
+            .iter()
+            .find_map(|(operand, _)| operand.node.as_ref().map(|(tape, _)| tape.clone()));
+        let node = tape.map(|tape| {
+            let edges = operands
+                .iter()
+                .filter_map(|(operand, partial)| {
+                    operand.node.as_ref().map(|(_, index)| (*index, *partial))
+                })
+                .collect();
+            let index = {
+                let mut nodes = tape.borrow_mut();
+                nodes.push(edges);
+                nodes.len() - 1
+            };
+            (tape, index)
+        });
+        Adjoint { value, node }
+    }
+}
+
+impl Scalar for Adjoint {
+    fn constant(value: f64) -> Adjoint {
+        Adjoint { value, node: None }
+    }
+
+    fn value(&self) -> f64 {
+        self.value
+    }
+
+    fn add(&self, other: &Adjoint) -> Adjoint {
+        Adjoint::record(self.value + other.value, &[(self, 1.), (other, 1.)])
+    }
+
+    fn sub(&self, other: &Adjoint) -> Adjoint {
+        Adjoint::record(self.value - other.value, &[(self, 1.), (other, -1.)])
+    }
+
+    fn mul(&self, other: &Adjoint) -> Adjoint {
+        Adjoint::record(
+            self.value * other.value,
+            &[(self, other.value), (other, self.value)],
+        )
+    }
+
+    fn chain(&self, value: f64, first: f64, _second: f64) -> Adjoint {
+        Adjoint::record(value, &[(self, first)])
+    }
+}
+
+impl GradientEvaluator for ReverseMode {
+    fn gradient(
+        &self,
+        expression: &Expression,
+        variables: &[Variable],
+        values: &HashMap<Variable, f64>,
+    ) -> Result<(f64, Vec<f64>), EvaluationError> {
+        let tape: Tape = Rc::new(RefCell::new(vec![vec![]; variables.len()]));
+        let mut adjoints: HashMap<Variable, Adjoint> = values
+            .iter()
+            .map(|(variable, value)| (*variable, Adjoint::constant(*value)))
+            .collect();
+        for (index, variable) in variables.iter().enumerate() {
+            let value = evaluate_variable(*variable, values)?;
+            let node = Some((tape.clone(), index));
+            adjoints.insert(*variable, Adjoint { value, node });
+        }
+        let result = evaluate_scalar(expression, &adjoints)?;
+        let Some((_, output)) = result.node else {
+            return Ok((result.value, vec![0.; variables.len()]));
+        };
+        let nodes = tape.borrow();
+        let mut sensitivities = vec![0.; nodes.len()];
+        sensitivities[output] = 1.;
+        for index in (0..=output).rev() {
+            for (operand, partial) in &nodes[index] {
+                sensitivities[*operand] += sensitivities[index] * partial;
+            }
+        }
+        sensitivities.truncate(variables.len());
+        Ok((result.value, sensitivities))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::autodiff::forward::ForwardMode;
+    use crate::autodiff::Symbolic;
+    use crate::expression::{BinaryOperation, BinaryOperationType, Function, Monomial};
+
+    /// x^2 y / (1 + cos(z)^2) + |x - y|, with x reused across branches
+    fn expression() -> Expression {
+        Expression::Sum(vec![
+            Expression::BinaryOperation(BinaryOperation {
+                operation: BinaryOperationType::Division,
+                left_value: Box::new(Expression::Product(vec![
+                    Expression::monomial(1, 'x', 2),
+                    Expression::monomial(1, 'y', 1),
+                ])),
+                right_value: Box::new(Expression::Sum(vec![
+                    Expression::Constant(1.into()),
+                    Expression::BinaryOperation(BinaryOperation {
+                        operation: BinaryOperationType::Power,
+                        left_value: Box::new(Expression::Function {
+                            function: Function::Cosine,
+                            expression: Box::new(Expression::monomial(1, 'z', 1)),
+                        }),
+                        right_value: Box::new(Expression::Constant(2.into())),
+                    }),
+                ])),
+            }),
+            Expression::Function {
+                function: Function::AbsoluteValue,
+                expression: Box::new(Expression::BinaryOperation(BinaryOperation {
+                    operation: BinaryOperationType::Substraction,
+                    left_value: Box::new(Expression::monomial(1, 'x', 1)),
+                    right_value: Box::new(Expression::monomial(1, 'y', 1)),
+                })),
+            },
+        ])
+    }
+
+    #[test]
+    fn test_strategies_agree() {
+        let expr = expression();
+        let variables = ['x', 'y', 'z'];
+        let values = HashMap::from([('x', 1.5), ('y', -0.4), ('z', 2.)]);
+        let evaluators: [&dyn GradientEvaluator; 3] = [&ForwardMode, &ReverseMode, &Symbolic];
+        let results: Vec<_> = evaluators
+            .iter()
+            .map(|evaluator| evaluator.gradient(&expr, &variables, &values).unwrap())
+            .collect();
+        for (value, gradient) in &results[1..] {
+            assert!((value - results[0].0).abs() < 1e-12);
+            for (derivative, expected) in gradient.iter().zip(&results[0].1) {
+                assert!((derivative - expected).abs() < 1e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn test_many_variables() {
+        // sum of (i + 1) v_i^2 over 26 variables
+        let variables: Vec<Variable> = ('a'..='z').collect();
+        let expr = Expression::Sum(
+            variables
+                .iter()
+                .enumerate()
+                .map(|(i, variable)| {
+                    Expression::Monomial(Monomial {
+                        factor: (i as i64 + 1).into(),
+                        variable: *variable,
+                        power: 2,
+                    })
+                })
+                .collect(),
+        );
+        let values = variables.iter().map(|variable| (*variable, 1.)).collect();
+        let (value, gradient) = ReverseMode.gradient(&expr, &variables, &values).unwrap();
+        assert_eq!(value, 351.);
+        let expected: Vec<f64> = (1..=26).map(|i| 2. * i as f64).collect();
+        assert_eq!(gradient, expected);
+    }
+
+    #[test]
+    fn test_constant_expression() {
+        let values = HashMap::from([('x', 3.)]);
+        let (value, gradient) = ReverseMode
+            .gradient(&Expression::Constant(2.into()), &['x'], &values)
+            .unwrap();
+        assert_eq!((value, gradient), (2., vec![0.]));
+    }
+}
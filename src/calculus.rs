@@ -0,0 +1,90 @@
+use crate::derivable::{Derivable, PartialDerivatives};
+use crate::expression::{Expression, Variable};
+use crate::matrix::SymbolicMatrix;
+
+/// Column vector of the partial derivatives of `expression`, in the order of `variables`.
+pub fn gradient(expression: &Expression, variables: &[Variable]) -> SymbolicMatrix {
+    SymbolicMatrix::column_vector(
+        variables
+            .iter()
+            .map(|variable| expression.derive(*variable))
+            .collect(),
+    )
+}
+
+/// Matrix whose row `i` is the transposed gradient of `expressions[i]`.
+pub fn jacobian(expressions: &[Expression], variables: &[Variable]) -> SymbolicMatrix {
+    SymbolicMatrix::from_fn(expressions.len(), variables.len(), |row, column| {
+        expressions[row].derive(variables[column])
+    })
+}
+
+/// Symmetric matrix of the second partial derivatives, each computed once.
+pub fn hessian(expression: &Expression, variables: &[Variable]) -> SymbolicMatrix {
+    let mut derivatives = PartialDerivatives::new(expression.clone());
+    SymbolicMatrix::from_fn(variables.len(), variables.len(), |row, column| {
+        derivatives
+            .derivative(&[variables[row], variables[column]])
+            .clone()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::HashMap;
+
+    use ndarray::array;
+
+    use crate::expression::{BinaryOperationType, Function};
+
+    fn product(left: Expression, right: Expression) -> Expression {
+        Expression::binary(BinaryOperationType::Multiplication, left, right)
+    }
+
+    #[test]
+    fn test_gradient_and_hessian() {
+        // x^2 y^3
+        let expr = product(
+            Expression::monomial(1, 'x', 2),
+            Expression::monomial(1, 'y', 3),
+        );
+        let values = HashMap::from([('x', 2.), ('y', 1.)]);
+        assert_eq!(
+            gradient(&expr, &['x', 'y']).evaluate(&values),
+            Ok(array![[4.], [12.]])
+        );
+        let hessian = hessian(&expr, &['x', 'y']);
+        assert_eq!(hessian[(0, 1)], hessian[(1, 0)]);
+        assert_eq!(hessian.evaluate(&values), Ok(array![[2., 12.], [12., 24.]]));
+    }
+
+    #[test]
+    fn test_jacobian_of_polar_coordinates() {
+        // (r cos t, r sin t)
+        let map = [
+            product(
+                Expression::monomial(1, 'r', 1),
+                Expression::Function {
+                    function: Function::Cosine,
+                    expression: Box::new(Expression::monomial(1, 't', 1)),
+                },
+            ),
+            product(
+                Expression::monomial(1, 'r', 1),
+                Expression::Function {
+                    function: Function::Sine,
+                    expression: Box::new(Expression::monomial(1, 't', 1)),
+                },
+            ),
+        ];
+        let jacobian = jacobian(&map, &['r', 't']);
+        assert_eq!((jacobian.rows(), jacobian.columns()), (2, 2));
+        let values = HashMap::from([('r', 2.), ('t', 0.5)]);
+        let matrix = jacobian.evaluate(&values).unwrap();
+        let determinant = matrix[[0, 0]] * matrix[[1, 1]] - matrix[[0, 1]] * matrix[[1, 0]];
+        assert!((determinant - 2.).abs() < 1e-12);
+        assert!(jacobian.evaluate(&HashMap::from([('r', 1.)])).is_err());
+    }
+}
@@ -0,0 +1,657 @@
+use std::collections::BTreeMap;
+
+use num_traits::{CheckedAdd, CheckedMul, One, Zero};
+
+use crate::expression::{
+    BinaryOperation, BinaryOperationType, Expression, Function, Monomial, Rational, Variable,
+};
+use crate::simplifiable::fold_constants;
+
+pub trait Canonical {
+    /// Rewrites the expression as a sorted n-ary sum of sorted n-ary products.
+    ///
+    /// Constants are folded, like terms and like factors are merged, products are distributed
+    /// over sums, natural powers of sums are expanded and `sin^2` is replaced by `1 - cos^2`,
+    /// so that mathematically equal polynomial-trigonometric expressions have the same
+    /// canonical form.
+    fn to_canonical_form(&self) -> Self;
+}
+
+impl Canonical for Expression {
+    fn to_canonical_form(&self) -> Expression {
+        match self {
+            Expression::Constant(_) | Expression::NamedConstant(_) => self.clone(),
+            Expression::Monomial(m) => make_product(vec![
+                Expression::Constant(m.factor),
+                variable_power(m.variable, m.power as i64),
+            ]),
+            Expression::BinaryOperation(operation) => {
+                let left = operation.left_value.to_canonical_form();
+                let right = operation.right_value.to_canonical_form();
+                match operation.operation {
+                    BinaryOperationType::Addition => make_sum(vec![left, right]),
+                    BinaryOperationType::Substraction => make_sum(vec![
+                        left,
+                        make_product(vec![Expression::Constant((-1).into()), right]),
+                    ]),
+                    BinaryOperationType::Multiplication => make_product(vec![left, right]),
+                    BinaryOperationType::Division => make_product(vec![
+                        left,
+                        make_power(right, Expression::Constant((-1).into())),
+                    ]),
+                    BinaryOperationType::Power => make_power(left, right),
+                }
+            }
+            Expression::Function {
+                function,
+                expression,
+            } => make_function(*function, expression.to_canonical_form()),
+            Expression::Sum(terms) => {
+                make_sum(terms.iter().map(Expression::to_canonical_form).collect())
+            }
+            Expression::Product(factors) => {
+                make_product(factors.iter().map(Expression::to_canonical_form).collect())
+            }
+        }
+    }
+}
+
+fn variable_power(variable: Variable, power: i64) -> Expression {
+    let unit = Monomial {
+        factor: Rational::one(),
+        variable,
+        power: 1,
+    };
+    match u8::try_from(power) {
+        Ok(0) => Expression::Constant(Rational::one()),
+        Ok(power) => Expression::Monomial(Monomial { power, ..unit }),
+        Err(_) => power_expression(
+            Expression::Monomial(unit),
+            Expression::Constant(power.into()),
+        ),
+    }
+}
+
+fn power_expression(base: Expression, exponent: Expression) -> Expression {
+    Expression::BinaryOperation(BinaryOperation {
+        operation: BinaryOperationType::Power,
+        left_value: Box::new(base),
+        right_value: Box::new(exponent),
+    })
+}
+
+fn make_function(function: Function, argument: Expression) -> Expression {
+    match (function, &argument) {
+        (Function::Sine, Expression::Constant(value)) if value.is_zero() => {
+            Expression::Constant(Rational::zero())
+        }
+        (Function::Cosine, Expression::Constant(value)) if value.is_zero() => {
+            Expression::Constant(Rational::one())
+        }
+        _ => Expression::Function {
+            function,
+            expression: Box::new(argument),
+        },
+    }
+}
+
+/// Splits a canonical term into its rational coefficient and the rest of the term.
+fn split_coefficient(term: Expression) -> (Rational, Expression) {
+    match term {
+        Expression::Constant(value) => (value, Expression::Constant(Rational::one())),
+        Expression::Monomial(m) => (
+            m.factor,
+            Expression::Monomial(Monomial {
+                factor: Rational::one(),
+                ..m
+            }),
+        ),
+        Expression::Product(mut factors) => match factors.first() {
+            Some(Expression::Constant(value)) => {
+                let value = *value;
+                factors.remove(0);
+                if factors.len() == 1 {
+                    (value, factors.remove(0))
+                } else {
+                    (value, Expression::Product(factors))
+                }
+            }
+            _ => (Rational::one(), Expression::Product(factors)),
+        },
+        term => (Rational::one(), term),
+    }
+}
+
+fn with_coefficient(coefficient: Rational, rest: Expression) -> Expression {
+    if rest == Expression::Constant(Rational::one()) {
+        return Expression::Constant(coefficient);
+    }
+    if coefficient.is_one() {
+        return rest;
+    }
+    match rest {
+        Expression::Monomial(m) => Expression::Monomial(Monomial {
+            factor: coefficient,
+            ..m
+        }),
+        Expression::Product(factors) => Expression::Product(
+            std::iter::once(Expression::Constant(coefficient))
+                .chain(factors)
+                .collect(),
+        ),
+        rest => Expression::Product(vec![Expression::Constant(coefficient), rest]),
+    }
+}
+
+fn make_sum(terms: Vec<Expression>) -> Expression {
+    let mut collected = BTreeMap::<Expression, Rational>::new();
+    // Like terms whose coefficients cannot be added without overflow are left unmerged.
+    let mut overflowed = Vec::new();
+    for term in terms.into_iter().flat_map(|term| match term {
+        Expression::Sum(terms) => terms,
+        term => vec![term],
+    }) {
+        let (coefficient, rest) = split_coefficient(term);
+        let sum = collected.entry(rest.clone()).or_insert_with(Rational::zero);
+        match sum.checked_add(&coefficient) {
+            Some(value) => *sum = value,
+            None => overflowed.push(with_coefficient(coefficient, rest)),
+        }
+    }
+    let mut terms: Vec<Expression> = collected
+        .into_iter()
+        .filter(|(_, coefficient)| !coefficient.is_zero())
+        .map(|(rest, coefficient)| with_coefficient(coefficient, rest))
+        .chain(overflowed)
+        .collect();
+    terms.sort();
+    match terms.len() {
+        0 => Expression::Constant(Rational::zero()),
+        1 => terms.remove(0),
+        _ => Expression::Sum(terms),
+    }
+}
+
+fn make_product(factors: Vec<Expression>) -> Expression {
+    let mut flattened: Vec<Expression> = factors
+        .into_iter()
+        .flat_map(|factor| match factor {
+            Expression::Product(factors) => factors,
+            factor => vec![factor],
+        })
+        .collect();
+    if let Some(index) = flattened
+        .iter()
+        .position(|factor| matches!(factor, Expression::Sum(_)))
+    {
+        let terms = match flattened.remove(index) {
+            Expression::Sum(terms) => terms,
+            _ => unreachable!(),
+        };
+        return make_sum(
+            terms
+                .into_iter()
+                .map(|term| {
+                    let mut factors = flattened.clone();
+                    factors.push(term);
+                    make_product(factors)
+                })
+                .collect(),
+        );
+    }
+
+    let mut coefficient = Rational::one();
+    // Constants that cannot be folded into the coefficient without overflow stay as factors.
+    let mut overflowed = Vec::new();
+    let mut scale = |value: Rational| match coefficient.checked_mul(&value) {
+        Some(product) => coefficient = product,
+        None => overflowed.push(Expression::Constant(value)),
+    };
+    let mut powers = BTreeMap::<Expression, Expression>::new();
+    for factor in flattened {
+        let (base, exponent) = match factor {
+            Expression::Constant(value) => {
+                scale(value);
+                continue;
+            }
+            Expression::Monomial(m) => {
+                scale(m.factor);
+                (
+                    Expression::Monomial(Monomial {
+                        factor: Rational::one(),
+                        variable: m.variable,
+                        power: 1,
+                    }),
+                    Expression::Constant((m.power as i64).into()),
+                )
+            }
+            Expression::BinaryOperation(operation)
+                if operation.operation == BinaryOperationType::Power =>
+            {
+                (*operation.left_value, *operation.right_value)
+            }
+            factor => (factor, Expression::Constant(Rational::one())),
+        };
+        let exponent = match powers.remove(&base) {
+            Some(Expression::Constant(existing)) => match exponent {
+                Expression::Constant(exponent) => match existing.checked_add(&exponent) {
+                    Some(sum) => Expression::Constant(sum),
+                    None => make_sum(vec![
+                        Expression::Constant(existing),
+                        Expression::Constant(exponent),
+                    ]),
+                },
+                exponent => make_sum(vec![Expression::Constant(existing), exponent]),
+            },
+            Some(existing) => make_sum(vec![existing, exponent]),
+            None => exponent,
+        };
+        powers.insert(base, exponent);
+    }
+    if coefficient.is_zero() {
+        return Expression::Constant(Rational::zero());
+    }
+
+    let mut factors: Vec<Expression> = powers
+        .into_iter()
+        .map(|(base, exponent)| make_power(base, exponent))
+        .filter(|factor| *factor != Expression::Constant(Rational::one()))
+        .collect();
+    if factors.iter().any(|factor| {
+        matches!(
+            factor,
+            Expression::Sum(_) | Expression::Product(_) | Expression::Constant(_)
+        )
+    }) {
+        factors.push(Expression::Constant(coefficient));
+        factors.extend(overflowed);
+        return make_product(factors);
+    }
+    factors.extend(overflowed);
+    factors.sort();
+    match factors.len() {
+        0 => Expression::Constant(coefficient),
+        1 => with_coefficient(coefficient, factors.remove(0)),
+        _ => with_coefficient(coefficient, Expression::Product(factors)),
+    }
+}
+
+fn make_power(base: Expression, exponent: Expression) -> Expression {
+    let exponent_value = match exponent {
+        Expression::Constant(value) => value,
+        exponent => return power_expression(base, exponent),
+    };
+    if exponent_value.is_zero() {
+        return Expression::Constant(Rational::one());
+    }
+    if exponent_value.is_one() {
+        return base;
+    }
+    let integer_exponent = if exponent_value.is_integer() {
+        i32::try_from(exponent_value.to_integer()).ok()
+    } else {
+        None
+    };
+    match (base, integer_exponent) {
+        (Expression::Constant(value), Some(_)) => {
+            match fold_constants(BinaryOperationType::Power, &value, &exponent_value) {
+                Some(power) => Expression::Constant(power),
+                None => power_expression(
+                    Expression::Constant(value),
+                    Expression::Constant(exponent_value),
+                ),
+            }
+        }
+        (Expression::Monomial(m), Some(exponent)) => {
+            let power = variable_power(m.variable, m.power as i64 * exponent as i64);
+            if m.factor.is_one() {
+                power
+            } else {
+                match fold_constants(BinaryOperationType::Power, &m.factor, &exponent_value) {
+                    Some(factor) => make_product(vec![Expression::Constant(factor), power]),
+                    None => power_expression(
+                        Expression::Monomial(m),
+                        Expression::Constant(exponent_value),
+                    ),
+                }
+            }
+        }
+        (Expression::Sum(terms), Some(exponent)) if exponent > 0 => {
+            match sum_power(&terms, exponent as u32) {
+                Some(power) => power,
+                None => {
+                    power_expression(Expression::Sum(terms), Expression::Constant(exponent_value))
+                }
+            }
+        }
+        (Expression::Product(factors), Some(_)) => make_product(
+            factors
+                .into_iter()
+                .map(|factor| make_power(factor, Expression::Constant(exponent_value)))
+                .collect(),
+        ),
+        (Expression::BinaryOperation(operation), Some(_))
+            if operation.operation == BinaryOperationType::Power =>
+        {
+            match *operation.right_value {
+                Expression::Constant(inner) => match inner.checked_mul(&exponent_value) {
+                    Some(exponent) => {
+                        make_power(*operation.left_value, Expression::Constant(exponent))
+                    }
+                    None => power_expression(
+                        power_expression(*operation.left_value, Expression::Constant(inner)),
+                        Expression::Constant(exponent_value),
+                    ),
+                },
+                inner => power_expression(
+                    power_expression(*operation.left_value, inner),
+                    Expression::Constant(exponent_value),
+                ),
+            }
+        }
+        (
+            Expression::Function {
+                function: Function::Sine,
+                expression,
+            },
+            Some(exponent),
+        ) if exponent >= 2 => {
+            // sin^(2k + r) = sin^r (1 - cos^2)^k
+            let sine = Expression::Function {
+                function: Function::Sine,
+                expression: expression.clone(),
+            };
+            let cosine_square = power_expression(
+                Expression::Function {
+                    function: Function::Cosine,
+                    expression,
+                },
+                Expression::Constant(2.into()),
+            );
+            make_product(vec![
+                make_power(
+                    sine,
+                    Expression::Constant(Rational::from(exponent as i64 % 2)),
+                ),
+                make_power(
+                    make_sum(vec![
+                        Expression::Constant(Rational::one()),
+                        make_product(vec![Expression::Constant((-1).into()), cosine_square]),
+                    ]),
+                    Expression::Constant(Rational::from(exponent as i64 / 2)),
+                ),
+            ])
+        }
+        (base, _) => power_expression(base, Expression::Constant(exponent_value)),
+    }
+}
+
+/// `(sum of terms)^exponent` by repeated squaring, `None` when a coefficient overflows.
+fn sum_power(terms: &[Expression], mut exponent: u32) -> Option<Expression> {
+    let (mut power, mut square) = (vec![Expression::Constant(Rational::one())], terms.to_vec());
+    loop {
+        if exponent % 2 == 1 {
+            power = multiply_sums(&power, &square)?;
+        }
+        exponent /= 2;
+        if exponent == 0 {
+            return Some(make_sum(power));
+        }
+        square = multiply_sums(&square, &square)?;
+    }
+}
+
+/// Distributes the product of two canonical sums, collecting like terms as they are produced
+/// so that the result is never larger than its canonical form.
+fn multiply_sums(left: &[Expression], right: &[Expression]) -> Option<Vec<Expression>> {
+    let mut collected = BTreeMap::<Expression, Rational>::new();
+    for l in left {
+        for r in right {
+            let (left_coefficient, left_rest) = split_coefficient(l.clone());
+            let (right_coefficient, right_rest) = split_coefficient(r.clone());
+            let coefficient = left_coefficient.checked_mul(&right_coefficient)?;
+            let terms = match make_product(vec![left_rest, right_rest]) {
+                Expression::Sum(terms) => terms,
+                term => vec![term],
+            };
+            for term in terms {
+                let (factor, rest) = split_coefficient(term);
+                let sum = collected.entry(rest).or_insert_with(Rational::zero);
+                *sum = sum.checked_add(&coefficient.checked_mul(&factor)?)?;
+            }
+        }
+    }
+    Some(
+        collected
+            .into_iter()
+            .filter(|(_, coefficient)| !coefficient.is_zero())
+            .map(|(rest, coefficient)| with_coefficient(coefficient, rest))
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    fn function(function: Function, variable: Variable) -> Expression {
+        Expression::function(function, Expression::monomial(1, variable, 1))
+    }
+
+    #[test]
+    fn test_commutativity_and_associativity() {
+        let (x, y, z) = (
+            Expression::monomial(1, 'x', 1),
+            Expression::monomial(1, 'y', 1),
+            Expression::monomial(1, 'z', 1),
+        );
+        let xy = Expression::binary(BinaryOperationType::Multiplication, x.clone(), y.clone());
+        let yx = Expression::binary(BinaryOperationType::Multiplication, y.clone(), x.clone());
+        assert_ne!(xy, yx);
+        assert_eq!(xy.to_canonical_form(), yx.to_canonical_form());
+        assert_eq!(
+            xy.to_canonical_form(),
+            Expression::Product(vec![x.clone(), y.clone()])
+        );
+
+        let left = Expression::binary(
+            BinaryOperationType::Addition,
+            Expression::binary(BinaryOperationType::Addition, x.clone(), y.clone()),
+            z.clone(),
+        );
+        let right = Expression::Sum(vec![z.clone(), Expression::Sum(vec![y.clone(), x.clone()])]);
+        assert_eq!(left.to_canonical_form(), right.to_canonical_form());
+        assert_eq!(left.to_canonical_form(), Expression::Sum(vec![x, y, z]));
+    }
+
+    #[test]
+    fn test_constants_and_like_terms() {
+        let x = Expression::monomial(1, 'x', 1);
+        // 2 * 3 + x + 1/2 + x * x * 4 - x
+        let expr = Expression::Sum(vec![
+            Expression::binary(
+                BinaryOperationType::Multiplication,
+                Expression::Constant(2.into()),
+                Expression::Constant(3.into()),
+            ),
+            x.clone(),
+            Expression::Constant(Rational::new(1, 2)),
+            Expression::Product(vec![x.clone(), x.clone(), Expression::Constant(4.into())]),
+            Expression::binary(
+                BinaryOperationType::Substraction,
+                Expression::Constant(0.into()),
+                x,
+            ),
+        ]);
+        assert_eq!(
+            expr.to_canonical_form(),
+            Expression::Sum(vec![
+                Expression::Constant(Rational::new(13, 2)),
+                Expression::monomial(4, 'x', 2)
+            ])
+        );
+
+        let expr = Expression::binary(
+            BinaryOperationType::Division,
+            Expression::monomial(3, 'x', 2),
+            Expression::monomial(6, 'x', 1),
+        );
+        assert_eq!(
+            expr.to_canonical_form(),
+            Expression::Monomial(Monomial {
+                factor: Rational::new(1, 2),
+                variable: 'x',
+                power: 1,
+            })
+        );
+    }
+
+    #[test]
+    fn test_polynomial_trigonometric_equality() {
+        let (x, y) = (
+            Expression::monomial(1, 'x', 1),
+            Expression::monomial(1, 'y', 1),
+        );
+        // (x + y)^2 - 2xy == x^2 + y^2
+        let left = Expression::binary(
+            BinaryOperationType::Substraction,
+            Expression::binary(
+                BinaryOperationType::Power,
+                Expression::binary(BinaryOperationType::Addition, x.clone(), y.clone()),
+                Expression::Constant(2.into()),
+            ),
+            Expression::Product(vec![Expression::Constant(2.into()), x, y]),
+        );
+        let right = Expression::Sum(vec![
+            Expression::monomial(1, 'y', 2),
+            Expression::monomial(1, 'x', 2),
+        ]);
+        assert_eq!(left.to_canonical_form(), right.to_canonical_form());
+
+        // r^2 sin(v)^2 + r^2 cos(v)^2 == r^2
+        let square = |expression| {
+            Expression::binary(
+                BinaryOperationType::Power,
+                expression,
+                Expression::Constant(2.into()),
+            )
+        };
+        let expr = Expression::Sum(vec![
+            Expression::Product(vec![
+                Expression::monomial(1, 'r', 2),
+                square(function(Function::Sine, 'v')),
+            ]),
+            Expression::Product(vec![
+                square(function(Function::Cosine, 'v')),
+                Expression::monomial(1, 'r', 2),
+            ]),
+        ]);
+        assert_eq!(expr.to_canonical_form(), Expression::monomial(1, 'r', 2));
+    }
+
+    #[test]
+    fn test_canonical_form_is_idempotent_and_hashable() {
+        let expr = Expression::binary(
+            BinaryOperationType::Multiplication,
+            Expression::binary(
+                BinaryOperationType::Addition,
+                function(Function::Cosine, 'u'),
+                Expression::monomial(2, 'R', 1),
+            ),
+            Expression::binary(
+                BinaryOperationType::Power,
+                function(Function::Sine, 'u'),
+                Expression::Constant(3.into()),
+            ),
+        );
+        let canonical = expr.to_canonical_form();
+        assert_eq!(canonical.to_canonical_form(), canonical);
+
+        let forms: HashSet<Expression> = [
+            Expression::binary(
+                BinaryOperationType::Addition,
+                Expression::monomial(1, 'x', 1),
+                Expression::monomial(1, 'y', 1),
+            ),
+            Expression::binary(
+                BinaryOperationType::Addition,
+                Expression::monomial(1, 'y', 1),
+                Expression::monomial(1, 'x', 1),
+            ),
+        ]
+        .iter()
+        .map(Canonical::to_canonical_form)
+        .collect();
+        assert_eq!(forms.len(), 1);
+    }
+
+    #[test]
+    fn test_coefficient_overflow_keeps_unfolded_constants() {
+        let huge = || Expression::Constant(i64::MAX.into());
+        let x = || Expression::monomial(1, 'x', 1);
+        let product = Expression::binary(
+            BinaryOperationType::Multiplication,
+            Expression::binary(BinaryOperationType::Multiplication, huge(), x()),
+            huge(),
+        );
+        let canonical = product.to_canonical_form();
+        assert_eq!(canonical.to_canonical_form(), canonical);
+        assert!(matches!(canonical, Expression::Product(ref factors) if factors.len() == 3));
+
+        let sum = Expression::binary(
+            BinaryOperationType::Addition,
+            Expression::binary(BinaryOperationType::Multiplication, huge(), x()),
+            Expression::binary(BinaryOperationType::Multiplication, huge(), x()),
+        );
+        assert!(matches!(sum.to_canonical_form(), Expression::Sum(ref terms) if terms.len() == 2));
+
+        let power = Expression::binary(
+            BinaryOperationType::Power,
+            huge(),
+            Expression::Constant(2.into()),
+        );
+        assert_eq!(power.to_canonical_form(), power);
+    }
+
+    #[test]
+    fn test_large_powers_of_sums() {
+        let x_plus_y = Expression::binary(
+            BinaryOperationType::Addition,
+            Expression::monomial(1, 'x', 1),
+            Expression::monomial(1, 'y', 1),
+        );
+        let power = |exponent: i64| {
+            Expression::binary(
+                BinaryOperationType::Power,
+                x_plus_y.clone(),
+                Expression::Constant(exponent.into()),
+            )
+        };
+        match power(40).to_canonical_form() {
+            Expression::Sum(terms) => {
+                assert_eq!(terms.len(), 41);
+                // C(40, 20) x^20 y^20
+                assert!(terms.contains(&Expression::Product(vec![
+                    Expression::Constant(137846528820.into()),
+                    Expression::monomial(1, 'x', 20),
+                    Expression::monomial(1, 'y', 20),
+                ])));
+            }
+            canonical => panic!("expected a sum, got {:?}", canonical),
+        }
+        // The binomial coefficients overflow, so the power stays unexpanded.
+        assert_eq!(
+            power(1000).to_canonical_form(),
+            Expression::binary(
+                BinaryOperationType::Power,
+                Expression::Sum(vec![
+                    Expression::monomial(1, 'x', 1),
+                    Expression::monomial(1, 'y', 1)
+                ]),
+                Expression::Constant(1000.into()),
+            )
+        );
+    }
+}
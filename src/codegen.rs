@@ -0,0 +1,640 @@
+use std::collections::HashSet;
+
+use crate::dag::{eliminate_common_subexpressions, temporary_index};
+use crate::evaluable::{evaluate_named_constant, rational_to_f64, EvaluationError};
+use crate::expression::{BinaryOperationType, Expression, Function, Monomial, Rational, Variable};
+use crate::matrix::SymbolicMatrix;
+use crate::surface::Surface;
+
+/// Target language of the code generators.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Language {
+    Rust,
+    C,
+    /// Python with NumPy, the arguments being numbers or broadcastable arrays.
+    Python,
+    /// OpenGL Shading Language, in single precision.
+    Glsl,
+    /// WebGPU Shading Language, in single precision.
+    Wgsl,
+}
+
+/// Binding strength of printed expressions, from loosest to tightest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Precedence {
+    Sum,
+    Product,
+    Negation,
+    Power,
+    Atom,
+}
+
+impl Language {
+    pub fn is_shading_language(&self) -> bool {
+        matches!(self, Language::Glsl | Language::Wgsl)
+    }
+
+    fn name(&self, variable: Variable) -> String {
+        match temporary_index(variable) {
+            Some(index) => format!("t{index}"),
+            None => variable.to_string(),
+        }
+    }
+
+    fn number(&self, value: f64) -> (String, Precedence) {
+        let literal = format!("{value:?}");
+        let precedence = if value.is_sign_negative() {
+            Precedence::Negation
+        } else {
+            Precedence::Atom
+        };
+        (literal, precedence)
+    }
+
+    fn call(&self, function: &str, arguments: &[String]) -> (String, Precedence) {
+        let function = match self {
+            Language::Rust => format!("f64::{function}"),
+            Language::C | Language::Glsl | Language::Wgsl => function.to_string(),
+            Language::Python => format!("np.{function}"),
+        };
+        (
+            format!("{function}({})", arguments.join(", ")),
+            Precedence::Atom,
+        )
+    }
+
+    fn function(&self, function: Function, argument: String) -> (String, Precedence) {
+        let name = match (self, function) {
+            (_, Function::Sine) => "sin",
+            (_, Function::Cosine) => "cos",
+            (_, Function::Exponential) => "exp",
+            (Language::Rust, Function::Logarithm) => "ln",
+            (_, Function::Logarithm) => "log",
+            (Language::C, Function::AbsoluteValue) => "fabs",
+            (_, Function::AbsoluteValue) => "abs",
+        };
+        self.call(name, &[argument])
+    }
+
+    fn power(
+        &self,
+        base: &Expression,
+        exponent: &Expression,
+    ) -> Result<(String, Precedence), EvaluationError> {
+        if *exponent == Expression::Constant(Rational::new(1, 2)) {
+            return Ok(self.call("sqrt", &[self.print(base)?.0]));
+        }
+        match (self, exponent) {
+            (Language::Rust, Expression::Constant(n)) if n.is_integer() => {
+                Ok(self.call("powi", &[self.print(base)?.0, n.to_string()]))
+            }
+            (Language::Rust, _) => {
+                Ok(self.call("powf", &[self.print(base)?.0, self.print(exponent)?.0]))
+            }
+            // pow is undefined for negative bases in shaders, so small integer powers are
+            // multiplied out
+            (Language::Glsl | Language::Wgsl, Expression::Constant(n))
+                if n.is_integer() && n.numer().abs() <= 8 =>
+            {
+                let factor = self.operand(base, Precedence::Negation)?;
+                let copies = n.numer().unsigned_abs() as usize;
+                let product = match copies {
+                    0 => return Ok(self.number(1.)),
+                    1 => self.print(base)?,
+                    _ => (vec![factor; copies].join(" * "), Precedence::Product),
+                };
+                if *n.numer() > 0 {
+                    return Ok(product);
+                }
+                let (one, _) = self.number(1.);
+                let denominator = if product.1 < Precedence::Negation {
+                    format!("({})", product.0)
+                } else {
+                    product.0
+                };
+                Ok((format!("{one} / {denominator}"), Precedence::Product))
+            }
+            (Language::C | Language::Glsl | Language::Wgsl, _) => {
+                Ok(self.call("pow", &[self.print(base)?.0, self.print(exponent)?.0]))
+            }
+            (Language::Python, _) => {
+                // ** binds tighter than negation on its left but not on its right
+                let base = self.operand(base, Precedence::Atom)?;
+                let exponent = self.operand(exponent, Precedence::Negation)?;
+                Ok((format!("{base}**{exponent}"), Precedence::Power))
+            }
+        }
+    }
+
+    /// `expression`, parenthesized unless it binds at least as tightly as `minimum`.
+    fn operand(
+        &self,
+        expression: &Expression,
+        minimum: Precedence,
+    ) -> Result<String, EvaluationError> {
+        let (code, precedence) = self.print(expression)?;
+        Ok(if precedence < minimum {
+            format!("({code})")
+        } else {
+            code
+        })
+    }
+
+    /// Left-associative infix operation; the right operand is parenthesized at equal
+    /// precedence so that the evaluation order of the tree is kept.
+    fn infix(
+        &self,
+        operator: &str,
+        precedence: Precedence,
+        operands: &[Expression],
+    ) -> Result<(String, Precedence), EvaluationError> {
+        let mut code = self.operand(&operands[0], precedence)?;
+        for operand in &operands[1..] {
+            let right = self.operand(operand, next(precedence))?;
+            code = format!("{code} {operator} {right}");
+        }
+        Ok((code, precedence))
+    }
+
+    /// Product where a factor of -1 is printed as a negation, which is exact.
+    fn product(&self, factors: &[Expression]) -> Result<(String, Precedence), EvaluationError> {
+        let minus_one = Expression::Constant((-1).into());
+        match factors.iter().position(|factor| *factor == minus_one) {
+            Some(index) if factors.len() > 1 => {
+                let mut rest = factors.to_vec();
+                rest.remove(index);
+                let rest = match rest.as_slice() {
+                    [factor] => self.print(factor)?,
+                    _ => self.product(&rest)?,
+                };
+                Ok(negate(rest))
+            }
+            _ => self.infix("*", Precedence::Product, factors),
+        }
+    }
+
+    fn print(&self, expression: &Expression) -> Result<(String, Precedence), EvaluationError> {
+        match expression {
+            Expression::Constant(value) => Ok(self.number(rational_to_f64(value))),
+            Expression::NamedConstant(name) => Ok(match (self, name.as_str()) {
+                (Language::Rust, "pi") => ("std::f64::consts::PI".to_string(), Precedence::Atom),
+                (Language::Rust, "e") => ("std::f64::consts::E".to_string(), Precedence::Atom),
+                (Language::Python, "pi") => ("np.pi".to_string(), Precedence::Atom),
+                (Language::Python, "e") => ("np.e".to_string(), Precedence::Atom),
+                _ => self.number(evaluate_named_constant(name)?),
+            }),
+            Expression::Monomial(m) => {
+                let variable = Expression::Monomial(Monomial {
+                    factor: 1.into(),
+                    variable: m.variable,
+                    power: 1,
+                });
+                let power = if m.power == 1 {
+                    (self.name(m.variable), Precedence::Atom)
+                } else {
+                    self.power(&variable, &Expression::Constant(i64::from(m.power).into()))?
+                };
+                if m.factor == 1.into() {
+                    return Ok(power);
+                }
+                if m.factor == (-1).into() {
+                    return Ok(negate(power));
+                }
+                let (factor, _) = self.number(rational_to_f64(&m.factor));
+                let power = if power.1 < next(Precedence::Product) {
+                    format!("({})", power.0)
+                } else {
+                    power.0
+                };
+                Ok((format!("{factor} * {power}"), Precedence::Product))
+            }
+            Expression::BinaryOperation(operation) => {
+                let operands = [
+                    *operation.left_value.clone(),
+                    *operation.right_value.clone(),
+                ];
+                match operation.operation {
+                    BinaryOperationType::Addition => self.infix("+", Precedence::Sum, &operands),
+                    BinaryOperationType::Substraction => {
+                        self.infix("-", Precedence::Sum, &operands)
+                    }
+                    BinaryOperationType::Multiplication => self.product(&operands),
+                    BinaryOperationType::Division => {
+                        self.infix("/", Precedence::Product, &operands)
+                    }
+                    BinaryOperationType::Power => {
+                        self.power(&operation.left_value, &operation.right_value)
+                    }
+                }
+            }
+            Expression::Function {
+                function,
+                expression,
+            } => Ok(self.function(*function, self.print(expression)?.0)),
+            Expression::Sum(terms) if terms.is_empty() => Ok(self.number(0.)),
+            Expression::Sum(terms) => self.infix("+", Precedence::Sum, terms),
+            Expression::Product(factors) if factors.is_empty() => Ok(self.number(1.)),
+            Expression::Product(factors) => self.product(factors),
+        }
+    }
+
+    fn parameters(&self, variables: &[Variable]) -> String {
+        variables
+            .iter()
+            .map(|variable| match self {
+                Language::Rust => format!("{variable}: f64"),
+                Language::C => format!("double {variable}"),
+                Language::Python => variable.to_string(),
+                Language::Glsl => format!("float {variable}"),
+                Language::Wgsl => format!("{variable}: f32"),
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    fn binding(&self, variable: Variable, value: &str) -> String {
+        let name = self.name(variable);
+        match self {
+            Language::Rust => format!("    let {name} = {value};\n"),
+            Language::C => format!("    const double {name} = {value};\n"),
+            Language::Python => format!("    {name} = {value}\n"),
+            Language::Glsl => format!("    float {name} = {value};\n"),
+            Language::Wgsl => format!("    let {name} = {value};\n"),
+        }
+    }
+}
+
+/// `-code`, which rounds like `code` whatever the grouping of a product after the sign.
+fn negate((code, precedence): (String, Precedence)) -> (String, Precedence) {
+    if precedence < Precedence::Product || code.starts_with('-') {
+        (format!("-({code})"), Precedence::Product)
+    } else {
+        (format!("-{code}"), Precedence::Product)
+    }
+}
+
+fn next(precedence: Precedence) -> Precedence {
+    match precedence {
+        Precedence::Sum => Precedence::Product,
+        Precedence::Product => Precedence::Negation,
+        Precedence::Negation => Precedence::Power,
+        Precedence::Power | Precedence::Atom => Precedence::Atom,
+    }
+}
+
+/// Temporaries shared by the `expressions`, then the printed expressions.
+fn body(
+    language: Language,
+    variables: &[Variable],
+    expressions: &[Expression],
+) -> Result<(String, Vec<String>), EvaluationError> {
+    for expression in expressions {
+        if let Some(variable) = expression
+            .variables()
+            .into_iter()
+            .find(|variable| !variables.contains(variable))
+        {
+            return Err(EvaluationError::UnboundVariable(variable));
+        }
+    }
+    let cse = eliminate_common_subexpressions(expressions);
+    let mut code = String::new();
+    for (variable, value) in &cse.bindings {
+        code += &language.binding(*variable, &language.print(value)?.0);
+    }
+    let results = cse
+        .results
+        .iter()
+        .map(|result| Ok(language.print(result)?.0))
+        .collect::<Result<_, EvaluationError>>()?;
+    Ok((code, results))
+}
+
+/// Lints the generated Rust function would trigger through its parameters.
+fn rust_attributes(variables: &[Variable], expressions: &[Expression]) -> String {
+    let used: HashSet<Variable> = expressions
+        .iter()
+        .flat_map(|expression| expression.variables())
+        .collect();
+    let mut lints = vec![];
+    if variables.iter().any(|variable| variable.is_uppercase()) {
+        lints.push("non_snake_case");
+    }
+    if variables.iter().any(|variable| !used.contains(variable)) {
+        lints.push("unused_variables");
+    }
+    if lints.is_empty() {
+        String::new()
+    } else {
+        format!("#[allow({})]\n", lints.join(", "))
+    }
+}
+
+/// Source of a standalone function `name` of `variables` returning `expression`.
+pub fn generate(
+    language: Language,
+    name: &str,
+    variables: &[Variable],
+    expression: &Expression,
+) -> Result<String, EvaluationError> {
+    let parameters = language.parameters(variables);
+    let (bindings, results) = body(language, variables, std::slice::from_ref(expression))?;
+    let result = &results[0];
+    Ok(match language {
+        Language::Rust => {
+            let attributes = rust_attributes(variables, std::slice::from_ref(expression));
+            format!(
+                "{attributes}pub fn {name}({parameters}) -> f64 {{\n{bindings}    {result}\n}}\n"
+            )
+        }
+        Language::C => format!(
+            "#include <math.h>\n\ndouble {name}({parameters}) {{\n{bindings}    return {result};\n}}\n"
+        ),
+        Language::Python => format!(
+            "import numpy as np\n\n\ndef {name}({parameters}):\n{bindings}    return {result}\n"
+        ),
+        Language::Glsl => {
+            format!("float {name}({parameters}) {{\n{bindings}    return {result};\n}}\n")
+        }
+        Language::Wgsl => {
+            format!("fn {name}({parameters}) -> f32 {{\n{bindings}    return {result};\n}}\n")
+        }
+    })
+}
+
+/// Source of a standalone function `name` of `variables` returning the entries of `matrix`:
+/// a nested array in Rust, an array whose first two axes are the rows and columns in Python,
+/// and a row-major array otherwise.
+pub fn generate_matrix(
+    language: Language,
+    name: &str,
+    variables: &[Variable],
+    matrix: &SymbolicMatrix,
+) -> Result<String, EvaluationError> {
+    let parameters = language.parameters(variables);
+    let (bindings, results) = body(language, variables, matrix.entries())?;
+    let (rows, columns) = (matrix.rows(), matrix.columns());
+    let row = |index: usize| results[index * columns..(index + 1) * columns].join(", ");
+    Ok(match language {
+        Language::Rust => {
+            let entries: String = (0..rows)
+                .map(|index| format!("        [{}],\n", row(index)))
+                .collect();
+            let attributes = rust_attributes(variables, matrix.entries());
+            format!(
+                "{attributes}pub fn {name}({parameters}) -> [[f64; {columns}]; {rows}] {{\n{bindings}    [\n{entries}    ]\n}}\n"
+            )
+        }
+        Language::C => {
+            let assignments: String = results
+                .iter()
+                .enumerate()
+                .map(|(index, result)| format!("    out[{index}] = {result};\n"))
+                .collect();
+            let parameters = if parameters.is_empty() {
+                format!("double out[{}]", rows * columns)
+            } else {
+                format!("{parameters}, double out[{}]", rows * columns)
+            };
+            format!(
+                "#include <math.h>\n\nvoid {name}({parameters}) {{\n{bindings}{assignments}}}\n"
+            )
+        }
+        Language::Python => format!(
+            "import numpy as np\n\n\ndef {name}({parameters}):\n{bindings}    \
+             entries = np.broadcast_arrays({})\n    \
+             return np.stack(entries).reshape(({rows}, {columns}) + entries[0].shape)\n",
+            results.join(", ")
+        ),
+        Language::Glsl => format!(
+            "float[{size}] {name}({parameters}) {{\n{bindings}    return float[{size}]({});\n}}\n",
+            results.join(", "),
+            size = rows * columns
+        ),
+        Language::Wgsl => format!(
+            "fn {name}({parameters}) -> array<f32, {size}> {{\n{bindings}    \
+             return array<f32, {size}>({});\n}}\n",
+            results.join(", "),
+            size = rows * columns
+        ),
+    })
+}
+
+/// Shader functions of the surface coordinates for the position, unit normal, Gaussian
+/// curvature and mean curvature of `surface`, named with `name` as prefix. The parametric
+/// variables are uniforms: global `uniform float`s in GLSL, and in WGSL the fields of a
+/// uniform struct bound at group 0, binding 0.
+///
+/// # Panics
+///
+/// Panics if `language` is not a shading language, or unless the surface has two coordinates
+/// and is embedded in three dimensions.
+pub fn generate_surface_shader(
+    language: Language,
+    name: &str,
+    surface: &Surface,
+) -> Result<String, EvaluationError> {
+    assert!(
+        language.is_shading_language(),
+        "{language:?} is not a shading language"
+    );
+    let coordinates = surface.coordinates();
+    let mut uniforms: Vec<Variable> = surface.parametric_variables.iter().copied().collect();
+    uniforms.sort_unstable();
+    let uniform_struct = format!("{}Parameters", pascal_case(name));
+    let mut code = match language {
+        Language::Glsl => uniforms
+            .iter()
+            .map(|uniform| format!("uniform float {uniform};\n"))
+            .collect(),
+        _ => {
+            let fields: String = uniforms
+                .iter()
+                .map(|uniform| format!("    {uniform}: f32,\n"))
+                .collect();
+            format!(
+                "struct {uniform_struct} {{\n{fields}}}\n\n\
+                 @group(0) @binding(0) var<uniform> {name}_parameters: {uniform_struct};\n"
+            )
+        }
+    };
+    let functions = [
+        ("position", surface.embedding.clone()),
+        ("normal", surface.unit_normal()),
+        ("gaussian_curvature", vec![surface.gaussian_curvature()]),
+        ("mean_curvature", vec![surface.mean_curvature()]),
+    ];
+    for (quantity, expressions) in functions {
+        let variables: Vec<Variable> = coordinates.iter().chain(&uniforms).copied().collect();
+        let (bindings, results) = body(language, &variables, &expressions)?;
+        let parameters = language.parameters(&coordinates);
+        let (scalar, vector) = match language {
+            Language::Glsl => ("float".to_string(), format!("vec{}", results.len())),
+            _ => ("f32".to_string(), format!("vec{}<f32>", results.len())),
+        };
+        let (return_type, result) = match results.as_slice() {
+            [result] => (scalar, result.clone()),
+            _ => (vector.clone(), format!("{vector}({})", results.join(", "))),
+        };
+        code += "\n";
+        code += &match language {
+            Language::Glsl => format!(
+                "{return_type} {name}_{quantity}({parameters}) {{\n{bindings}    return {result};\n}}\n"
+            ),
+            _ => {
+                let used: HashSet<Variable> = expressions
+                    .iter()
+                    .flat_map(|expression| expression.variables())
+                    .collect();
+                let loads: String = uniforms
+                    .iter()
+                    .filter(|uniform| used.contains(uniform))
+                    .map(|uniform| format!("    let {uniform} = {name}_parameters.{uniform};\n"))
+                    .collect();
+                format!(
+                    "fn {name}_{quantity}({parameters}) -> {return_type} {{\n\
+                     {loads}{bindings}    return {result};\n}}\n"
+                )
+            }
+        };
+    }
+    Ok(code)
+}
+
+fn pascal_case(name: &str) -> String {
+    name.split('_')
+        .map(|word| {
+            let mut characters = word.chars();
+            characters
+                .next()
+                .map(|first| first.to_uppercase().chain(characters).collect::<String>())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::expression::BinaryOperation;
+
+    /// sin(x y)^2 + (x - y) / 2 + sqrt(sin(x y))
+    fn expression() -> Expression {
+        let sine = Expression::Function {
+            function: Function::Sine,
+            expression: Box::new(Expression::Product(vec![
+                Expression::monomial(1, 'x', 1),
+                Expression::monomial(1, 'y', 1),
+            ])),
+        };
+        Expression::Sum(vec![
+            Expression::BinaryOperation(BinaryOperation {
+                operation: BinaryOperationType::Power,
+                left_value: Box::new(sine.clone()),
+                right_value: Box::new(Expression::Constant(2.into())),
+            }),
+            Expression::BinaryOperation(BinaryOperation {
+                operation: BinaryOperationType::Division,
+                left_value: Box::new(Expression::BinaryOperation(BinaryOperation {
+                    operation: BinaryOperationType::Substraction,
+                    left_value: Box::new(Expression::monomial(1, 'x', 1)),
+                    right_value: Box::new(Expression::monomial(1, 'y', 1)),
+                })),
+                right_value: Box::new(Expression::Constant(2.into())),
+            }),
+            Expression::BinaryOperation(BinaryOperation {
+                operation: BinaryOperationType::Power,
+                left_value: Box::new(sine),
+                right_value: Box::new(Expression::Constant(Rational::new(1, 2))),
+            }),
+        ])
+    }
+
+    #[test]
+    fn test_generate() {
+        assert_eq!(
+            generate(Language::Rust, "f", &['x', 'y'], &expression()).unwrap(),
+            "pub fn f(x: f64, y: f64) -> f64 {\n    \
+                 let t0 = f64::sin(x * y);\n    \
+                 f64::powi(t0, 2) + (x - y) / 2.0 + f64::sqrt(t0)\n\
+             }\n"
+        );
+        assert_eq!(
+            generate(Language::C, "f", &['x', 'y'], &expression()).unwrap(),
+            "#include <math.h>\n\n\
+             double f(double x, double y) {\n    \
+                 const double t0 = sin(x * y);\n    \
+                 return pow(t0, 2.0) + (x - y) / 2.0 + sqrt(t0);\n\
+             }\n"
+        );
+        assert_eq!(
+            generate(Language::Python, "f", &['x', 'y'], &expression()).unwrap(),
+            "import numpy as np\n\n\n\
+             def f(x, y):\n    \
+                 t0 = np.sin(x * y)\n    \
+                 return t0**2.0 + (x - y) / 2.0 + np.sqrt(t0)\n"
+        );
+    }
+
+    #[test]
+    fn test_generate_matrix() {
+        let matrix = SymbolicMatrix::from_rows(vec![
+            vec![
+                Expression::monomial(1, 'x', 2),
+                Expression::Constant((-1).into()),
+            ],
+            vec![
+                Expression::NamedConstant("pi".to_string()),
+                Expression::monomial(1, 'x', 1),
+            ],
+        ]);
+        assert_eq!(
+            generate_matrix(Language::C, "m", &['x'], &matrix).unwrap(),
+            "#include <math.h>\n\n\
+             void m(double x, double out[4]) {\n    \
+                 out[0] = pow(x, 2.0);\n    \
+                 out[1] = -1.0;\n    \
+                 out[2] = 3.141592653589793;\n    \
+                 out[3] = x;\n\
+             }\n"
+        );
+        assert_eq!(
+            generate_matrix(Language::Python, "m", &['x'], &matrix).unwrap(),
+            "import numpy as np\n\n\n\
+             def m(x):\n    \
+                 entries = np.broadcast_arrays(x**2.0, -1.0, np.pi, x)\n    \
+                 return np.stack(entries).reshape((2, 2) + entries[0].shape)\n"
+        );
+    }
+
+    #[test]
+    fn test_generate_shading_languages() {
+        // x^2 / y^-2, with powers multiplied out
+        let expression = Expression::BinaryOperation(BinaryOperation {
+            operation: BinaryOperationType::Division,
+            left_value: Box::new(Expression::monomial(1, 'x', 2)),
+            right_value: Box::new(Expression::BinaryOperation(BinaryOperation {
+                operation: BinaryOperationType::Power,
+                left_value: Box::new(Expression::monomial(1, 'y', 1)),
+                right_value: Box::new(Expression::Constant((-2).into())),
+            })),
+        });
+        assert_eq!(
+            generate(Language::Glsl, "f", &['x', 'y'], &expression).unwrap(),
+            "float f(float x, float y) {\n    return x * x / (1.0 / (y * y));\n}\n"
+        );
+        assert_eq!(
+            generate(Language::Wgsl, "f", &['x', 'y'], &expression).unwrap(),
+            "fn f(x: f32, y: f32) -> f32 {\n    return x * x / (1.0 / (y * y));\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_unbound_variable() {
+        assert_eq!(
+            generate(Language::Rust, "f", &['x'], &expression()),
+            Err(EvaluationError::UnboundVariable('y'))
+        );
+    }
+}
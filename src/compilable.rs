@@ -0,0 +1,428 @@
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+use crate::evaluable::{evaluate_named_constant, rational_to_f64, EvaluationError};
+use crate::expression::{BinaryOperationType, Expression, Function, Variable};
+
+/// One instruction of a compiled expression. Every instruction writes a new register, numbered
+/// by its position, and reads registers written before it.
+#[derive(Debug, Clone, Copy)]
+pub enum Instruction {
+    Constant(f64),
+    /// Loads the argument at the given index.
+    Argument(usize),
+    Add(usize, usize),
+    Subtract(usize, usize),
+    Multiply(usize, usize),
+    Divide(usize, usize),
+    IntegerPower(usize, i32),
+    Power(usize, usize),
+    Function(Function, usize),
+}
+
+impl Instruction {
+    fn operands(&self) -> Vec<usize> {
+        match *self {
+            Instruction::Constant(_) | Instruction::Argument(_) => vec![],
+            Instruction::Add(left, right)
+            | Instruction::Subtract(left, right)
+            | Instruction::Multiply(left, right)
+            | Instruction::Divide(left, right)
+            | Instruction::Power(left, right) => vec![left, right],
+            Instruction::IntegerPower(operand, _) | Instruction::Function(_, operand) => {
+                vec![operand]
+            }
+        }
+    }
+
+    fn map_operands(self, f: impl Fn(usize) -> usize) -> Instruction {
+        match self {
+            Instruction::Constant(_) | Instruction::Argument(_) => self,
+            Instruction::Add(left, right) => Instruction::Add(f(left), f(right)),
+            Instruction::Subtract(left, right) => Instruction::Subtract(f(left), f(right)),
+            Instruction::Multiply(left, right) => Instruction::Multiply(f(left), f(right)),
+            Instruction::Divide(left, right) => Instruction::Divide(f(left), f(right)),
+            Instruction::Power(left, right) => Instruction::Power(f(left), f(right)),
+            Instruction::IntegerPower(operand, exponent) => {
+                Instruction::IntegerPower(f(operand), exponent)
+            }
+            Instruction::Function(function, operand) => Instruction::Function(function, f(operand)),
+        }
+    }
+
+    /// Result of the instruction given a way to read its operand registers.
+    #[inline]
+    fn execute(&self, arguments: &[f64], register: impl Fn(usize) -> f64) -> f64 {
+        match *self {
+            Instruction::Constant(value) => value,
+            Instruction::Argument(index) => arguments[index],
+            Instruction::Add(left, right) => register(left) + register(right),
+            Instruction::Subtract(left, right) => register(left) - register(right),
+            Instruction::Multiply(left, right) => register(left) * register(right),
+            Instruction::Divide(left, right) => register(left) / register(right),
+            Instruction::IntegerPower(operand, exponent) => register(operand).powi(exponent),
+            Instruction::Power(left, right) => register(left).powf(register(right)),
+            Instruction::Function(function, operand) => {
+                let value = register(operand);
+                match function {
+                    Function::Sine => value.sin(),
+                    Function::Cosine => value.cos(),
+                    Function::Exponential => value.exp(),
+                    Function::Logarithm => value.ln(),
+                    Function::AbsoluteValue => value.abs(),
+                }
+            }
+        }
+    }
+}
+
+// Constants compare by bit pattern so that identical instructions, NaN included, are shared.
+impl PartialEq for Instruction {
+    fn eq(&self, other: &Instruction) -> bool {
+        match (self, other) {
+            (Instruction::Constant(left), Instruction::Constant(right)) => {
+                left.to_bits() == right.to_bits()
+            }
+            (Instruction::Argument(left), Instruction::Argument(right)) => left == right,
+            (Instruction::Function(f, left), Instruction::Function(g, right)) => {
+                f == g && left == right
+            }
+            (Instruction::IntegerPower(left, n), Instruction::IntegerPower(right, m)) => {
+                left == right && n == m
+            }
+            _ => {
+                std::mem::discriminant(self) == std::mem::discriminant(other)
+                    && self.operands() == other.operands()
+            }
+        }
+    }
+}
+
+impl Eq for Instruction {}
+
+impl Hash for Instruction {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            Instruction::Constant(value) => value.to_bits().hash(state),
+            Instruction::Argument(index) => index.hash(state),
+            Instruction::IntegerPower(operand, exponent) => (operand, exponent).hash(state),
+            Instruction::Function(function, operand) => (function, operand).hash(state),
+            _ => self.operands().hash(state),
+        }
+    }
+}
+
+/// Straight-line register code for an expression, evaluated with arguments given in the order
+/// of the variables it was compiled for.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompiledExpression {
+    variables: Vec<Variable>,
+    instructions: Vec<Instruction>,
+}
+
+impl CompiledExpression {
+    pub fn variables(&self) -> &[Variable] {
+        &self.variables
+    }
+
+    pub fn instructions(&self) -> &[Instruction] {
+        &self.instructions
+    }
+
+    /// Panics if the number of arguments differs from the number of variables.
+    pub fn evaluate(&self, arguments: &[f64]) -> f64 {
+        let mut registers = Vec::with_capacity(self.instructions.len());
+        self.evaluate_with(arguments, &mut registers)
+    }
+
+    /// Same as `evaluate`, reusing `registers` to avoid an allocation per call.
+    pub fn evaluate_with(&self, arguments: &[f64], registers: &mut Vec<f64>) -> f64 {
+        assert_eq!(
+            arguments.len(),
+            self.variables.len(),
+            "expected one argument per variable"
+        );
+        registers.clear();
+        for instruction in &self.instructions {
+            let value = instruction.execute(arguments, |register| registers[register]);
+            registers.push(value);
+        }
+        *registers
+            .last()
+            .expect("compiled expressions are never empty")
+    }
+
+    /// The compiled expression as a closure over argument slices.
+    pub fn as_fn(&self) -> impl Fn(&[f64]) -> f64 + '_ {
+        move |arguments| self.evaluate(arguments)
+    }
+}
+
+/// Compilation of an expression to `CompiledExpression`, with constant folding and
+/// common-subexpression elimination.
+pub trait Compilable {
+    fn compile(&self, variables: &[Variable]) -> Result<CompiledExpression, EvaluationError>;
+}
+
+impl Compilable for Expression {
+    fn compile(&self, variables: &[Variable]) -> Result<CompiledExpression, EvaluationError> {
+        let mut compiler = Compiler {
+            variables,
+            instructions: vec![],
+            registers: HashMap::new(),
+        };
+        let output = compiler.compile(self)?;
+        Ok(CompiledExpression {
+            variables: variables.to_vec(),
+            instructions: compiler.finish(output),
+        })
+    }
+}
+
+struct Compiler<'a> {
+    variables: &'a [Variable],
+    instructions: Vec<Instruction>,
+    /// Register already holding the result of an instruction.
+    registers: HashMap<Instruction, usize>,
+}
+
+impl Compiler<'_> {
+    fn compile(&mut self, expression: &Expression) -> Result<usize, EvaluationError> {
+        Ok(match expression {
+            Expression::Constant(value) => self.emit(Instruction::Constant(rational_to_f64(value))),
+            Expression::NamedConstant(name) => {
+                self.emit(Instruction::Constant(evaluate_named_constant(name)?))
+            }
+            Expression::Monomial(m) => {
+                let mut register = self.variable(m.variable)?;
+                if m.power != 1 {
+                    register = self.emit(Instruction::IntegerPower(register, i32::from(m.power)));
+                }
+                if m.factor != 1.into() {
+                    let factor = self.emit(Instruction::Constant(rational_to_f64(&m.factor)));
+                    register = self.emit(Instruction::Multiply(factor, register));
+                }
+                register
+            }
+            Expression::BinaryOperation(operation) => {
+                let left = self.compile(&operation.left_value)?;
+                if let (BinaryOperationType::Power, Expression::Constant(exponent)) =
+                    (operation.operation, &*operation.right_value)
+                {
+                    if exponent.is_integer() {
+                        if let Ok(exponent) = i32::try_from(*exponent.numer()) {
+                            return Ok(self.emit(Instruction::IntegerPower(left, exponent)));
+                        }
+                    }
+                }
+                let right = self.compile(&operation.right_value)?;
+                self.emit(match operation.operation {
+                    BinaryOperationType::Addition => Instruction::Add(left, right),
+                    BinaryOperationType::Substraction => Instruction::Subtract(left, right),
+                    BinaryOperationType::Multiplication => Instruction::Multiply(left, right),
+                    BinaryOperationType::Division => Instruction::Divide(left, right),
+                    BinaryOperationType::Power => Instruction::Power(left, right),
+                })
+            }
+            Expression::Function {
+                function,
+                expression,
+            } => {
+                let operand = self.compile(expression)?;
+                self.emit(Instruction::Function(*function, operand))
+            }
+            Expression::Sum(terms) => self.fold(terms, 0., Instruction::Add)?,
+            Expression::Product(factors) => self.fold(factors, 1., Instruction::Multiply)?,
+        })
+    }
+
+    fn variable(&mut self, variable: Variable) -> Result<usize, EvaluationError> {
+        let index = self
+            .variables
+            .iter()
+            .position(|candidate| *candidate == variable)
+            .ok_or(EvaluationError::UnboundVariable(variable))?;
+        Ok(self.emit(Instruction::Argument(index)))
+    }
+
+    fn fold(
+        &mut self,
+        operands: &[Expression],
+        neutral: f64,
+        instruction: fn(usize, usize) -> Instruction,
+    ) -> Result<usize, EvaluationError> {
+        let mut registers = operands
+            .iter()
+            .map(|operand| self.compile(operand))
+            .collect::<Result<Vec<_>, _>>()?;
+        // keep operands in a canonical order so that reordered sums and products are shared
+        registers.sort_unstable();
+        let mut registers = registers.into_iter();
+        let Some(first) = registers.next() else {
+            return Ok(self.emit(Instruction::Constant(neutral)));
+        };
+        Ok(registers.fold(first, |accumulator, register| {
+            self.emit(instruction(accumulator, register))
+        }))
+    }
+
+    /// Register holding the result of `instruction`, reusing an identical earlier instruction
+    /// and folding instructions whose operands are all constants.
+    fn emit(&mut self, instruction: Instruction) -> usize {
+        let instruction = match instruction {
+            Instruction::Add(left, right) if left > right => Instruction::Add(right, left),
+            Instruction::Multiply(left, right) if left > right => {
+                Instruction::Multiply(right, left)
+            }
+            _ => instruction,
+        };
+        let operands = instruction.operands();
+        let instruction = if !operands.is_empty()
+            && operands
+                .iter()
+                .all(|operand| matches!(self.instructions[*operand], Instruction::Constant(_)))
+        {
+            Instruction::Constant(instruction.execute(&[], |register| {
+                match self.instructions[register] {
+                    Instruction::Constant(value) => value,
+                    _ => unreachable!("operands are constants"),
+                }
+            }))
+        } else {
+            instruction
+        };
+        if let Some(register) = self.registers.get(&instruction) {
+            return *register;
+        }
+        self.instructions.push(instruction);
+        self.registers
+            .insert(instruction, self.instructions.len() - 1);
+        self.instructions.len() - 1
+    }
+
+    /// Instructions needed for `output`, renumbered, with `output` last.
+    fn finish(self, output: usize) -> Vec<Instruction> {
+        let mut live = vec![false; output + 1];
+        live[output] = true;
+        for register in (0..=output).rev() {
+            if live[register] {
+                for operand in self.instructions[register].operands() {
+                    live[operand] = true;
+                }
+            }
+        }
+        let mut renumbered = vec![0; output + 1];
+        let mut instructions = vec![];
+        for register in 0..=output {
+            if live[register] {
+                renumbered[register] = instructions.len();
+                instructions.push(self.instructions[register].map_operands(|r| renumbered[r]));
+            }
+        }
+        instructions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::evaluable::Evaluable;
+    use crate::expression::Rational;
+
+    fn sine(expression: Expression) -> Expression {
+        Expression::function(Function::Sine, expression)
+    }
+
+    #[test]
+    fn test_compiled_matches_tree_walking() {
+        // sin(x y)^2 / (2 + x^3) - exp(y)^(x) + pi
+        let expr = Expression::Sum(vec![
+            Expression::binary(
+                BinaryOperationType::Division,
+                Expression::binary(
+                    BinaryOperationType::Power,
+                    sine(Expression::Product(vec![
+                        Expression::monomial(1, 'x', 1),
+                        Expression::monomial(1, 'y', 1),
+                    ])),
+                    Expression::Constant(2.into()),
+                ),
+                Expression::Sum(vec![
+                    Expression::Constant(2.into()),
+                    Expression::monomial(1, 'x', 3),
+                ]),
+            ),
+            Expression::binary(
+                BinaryOperationType::Substraction,
+                Expression::NamedConstant("pi".to_string()),
+                Expression::binary(
+                    BinaryOperationType::Power,
+                    Expression::Function {
+                        function: Function::Exponential,
+                        expression: Box::new(Expression::monomial(1, 'y', 1)),
+                    },
+                    Expression::monomial(1, 'x', 1),
+                ),
+            ),
+        ]);
+        let compiled = expr.compile(&['x', 'y']).unwrap();
+        for (x, y) in [(0.5, 1.5), (-1.2, 0.3), (2., -2.)] {
+            let expected = expr.evaluate(&HashMap::from([('x', x), ('y', y)])).unwrap();
+            assert!((compiled.evaluate(&[x, y]) - expected).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_constant_folding() {
+        // (1/2 + 3) * pi is a single constant
+        let expr = Expression::Product(vec![
+            Expression::Sum(vec![
+                Expression::Constant(Rational::new(1, 2)),
+                Expression::Constant(3.into()),
+            ]),
+            Expression::NamedConstant("pi".to_string()),
+        ]);
+        let compiled = expr.compile(&[]).unwrap();
+        assert_eq!(
+            compiled.instructions(),
+            &[Instruction::Constant(3.5 * std::f64::consts::PI)]
+        );
+    }
+
+    #[test]
+    fn test_common_subexpressions_are_shared() {
+        // sin(x y) + sin(y x): one product, one sine, one addition after the two arguments
+        let expr = Expression::Sum(vec![
+            sine(Expression::Product(vec![
+                Expression::monomial(1, 'x', 1),
+                Expression::monomial(1, 'y', 1),
+            ])),
+            sine(Expression::Product(vec![
+                Expression::monomial(1, 'y', 1),
+                Expression::monomial(1, 'x', 1),
+            ])),
+        ]);
+        let compiled = expr.compile(&['x', 'y']).unwrap();
+        assert_eq!(
+            compiled.instructions(),
+            &[
+                Instruction::Argument(0),
+                Instruction::Argument(1),
+                Instruction::Multiply(0, 1),
+                Instruction::Function(Function::Sine, 2),
+                Instruction::Add(3, 3),
+            ]
+        );
+        assert_eq!((compiled.as_fn())(&[1., 2.]), 2. * 2f64.sin());
+    }
+
+    #[test]
+    fn test_unbound_variable() {
+        assert_eq!(
+            Expression::monomial(1, 'z', 2).compile(&['x']),
+            Err(EvaluationError::UnboundVariable('z'))
+        );
+    }
+}
@@ -0,0 +1,535 @@
+use std::collections::HashMap;
+
+use num_traits::{CheckedMul, CheckedSub};
+
+use crate::expression::{
+    BinaryOperation, BinaryOperationType, Expression, Function, Monomial, Rational, Variable,
+};
+
+pub type NodeId = usize;
+
+/// Node of an `ExpressionDag`, whose children are other nodes of the same graph.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Node {
+    Constant(Rational),
+    NamedConstant(String),
+    Monomial(Monomial),
+    BinaryOperation(BinaryOperationType, NodeId, NodeId),
+    Function(Function, NodeId),
+    Sum(Vec<NodeId>),
+    Product(Vec<NodeId>),
+}
+
+impl Node {
+    pub fn children(&self) -> Vec<NodeId> {
+        match self {
+            Node::Constant(_) | Node::NamedConstant(_) | Node::Monomial(_) => vec![],
+            Node::BinaryOperation(_, left, right) => vec![*left, *right],
+            Node::Function(_, operand) => vec![*operand],
+            Node::Sum(operands) | Node::Product(operands) => operands.clone(),
+        }
+    }
+}
+
+/// Hash-consed expressions: structurally equal subexpressions are stored once and shared,
+/// so expressions with many repeated subtrees, such as iterated derivatives, stay small.
+/// Children are always interned before their parents, so ids are in topological order.
+#[derive(Debug, Clone, Default)]
+pub struct ExpressionDag {
+    nodes: Vec<Node>,
+    ids: HashMap<Node, NodeId>,
+    derivatives: HashMap<(NodeId, Variable), NodeId>,
+}
+
+impl ExpressionDag {
+    pub fn new() -> ExpressionDag {
+        ExpressionDag::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn node(&self, id: NodeId) -> &Node {
+        &self.nodes[id]
+    }
+
+    /// Id of `node`, adding it if no equal node exists yet.
+    pub fn add(&mut self, node: Node) -> NodeId {
+        if let Some(id) = self.ids.get(&node) {
+            return *id;
+        }
+        self.nodes.push(node.clone());
+        self.ids.insert(node, self.nodes.len() - 1);
+        self.nodes.len() - 1
+    }
+
+    pub fn intern(&mut self, expression: &Expression) -> NodeId {
+        let node = match expression {
+            Expression::Constant(value) => Node::Constant(*value),
+            Expression::NamedConstant(name) => Node::NamedConstant(name.clone()),
+            Expression::Monomial(m) => Node::Monomial(m.clone()),
+            Expression::BinaryOperation(operation) => Node::BinaryOperation(
+                operation.operation,
+                self.intern(&operation.left_value),
+                self.intern(&operation.right_value),
+            ),
+            Expression::Function {
+                function,
+                expression,
+            } => Node::Function(*function, self.intern(expression)),
+            Expression::Sum(terms) => Node::Sum(self.intern_all(terms)),
+            Expression::Product(factors) => Node::Product(self.intern_all(factors)),
+        };
+        self.add(node)
+    }
+
+    fn intern_all(&mut self, expressions: &[Expression]) -> Vec<NodeId> {
+        expressions
+            .iter()
+            .map(|expression| self.intern(expression))
+            .collect()
+    }
+
+    /// The expression tree rooted at `id`, with shared nodes copied at every use.
+    pub fn to_expression(&self, id: NodeId) -> Expression {
+        self.build(id, &|child| self.to_expression(child))
+    }
+
+    /// Expression for the node `id` whose children are built by `child`.
+    fn build(&self, id: NodeId, child: &dyn Fn(NodeId) -> Expression) -> Expression {
+        match &self.nodes[id] {
+            Node::Constant(value) => Expression::Constant(*value),
+            Node::NamedConstant(name) => Expression::NamedConstant(name.clone()),
+            Node::Monomial(m) => Expression::Monomial(m.clone()),
+            Node::BinaryOperation(operation, left, right) => {
+                Expression::BinaryOperation(BinaryOperation {
+                    operation: *operation,
+                    left_value: Box::new(child(*left)),
+                    right_value: Box::new(child(*right)),
+                })
+            }
+            Node::Function(function, operand) => Expression::Function {
+                function: *function,
+                expression: Box::new(child(*operand)),
+            },
+            Node::Sum(terms) => Expression::Sum(terms.iter().map(|id| child(*id)).collect()),
+            Node::Product(factors) => {
+                Expression::Product(factors.iter().map(|id| child(*id)).collect())
+            }
+        }
+    }
+
+    /// Derivative of the node `id`, memoized so that every shared subexpression is derived
+    /// once and the product rule reuses operands instead of copying them. Zero terms and
+    /// factors of one are dropped as the result is built.
+    pub fn derive(&mut self, id: NodeId, variable: Variable) -> NodeId {
+        if let Some(derivative) = self.derivatives.get(&(id, variable)) {
+            return *derivative;
+        }
+        let derivative = match self.nodes[id].clone() {
+            Node::Constant(_) | Node::NamedConstant(_) => self.constant(0),
+            Node::Monomial(m) if m.variable != variable => self.constant(0),
+            Node::Monomial(m) if m.power == 1 => self.add(Node::Constant(m.factor)),
+            Node::Monomial(m) => {
+                let power = Rational::from(i64::from(m.power));
+                let lowered = |factor| {
+                    Node::Monomial(Monomial {
+                        factor,
+                        variable,
+                        power: m.power - 1,
+                    })
+                };
+                match m.factor.checked_mul(&power) {
+                    Some(factor) => self.add(lowered(factor)),
+                    None => {
+                        let power = self.add(Node::Constant(power));
+                        let monomial = self.add(lowered(m.factor));
+                        self.product(vec![power, monomial])
+                    }
+                }
+            }
+            Node::BinaryOperation(operation, left, right) => {
+                let left_derivative = self.derive(left, variable);
+                let right_derivative = self.derive(right, variable);
+                match operation {
+                    BinaryOperationType::Addition => {
+                        self.sum(vec![left_derivative, right_derivative])
+                    }
+                    BinaryOperationType::Substraction => {
+                        let negated = self.negate(right_derivative);
+                        self.sum(vec![left_derivative, negated])
+                    }
+                    BinaryOperationType::Multiplication => {
+                        let terms = vec![
+                            self.product(vec![left_derivative, right]),
+                            self.product(vec![left, right_derivative]),
+                        ];
+                        self.sum(terms)
+                    }
+                    BinaryOperationType::Division => {
+                        let first = self.product(vec![left_derivative, right]);
+                        let second = self.product(vec![left, right_derivative]);
+                        let negated = self.negate(second);
+                        let numerator = self.sum(vec![first, negated]);
+                        let two = self.constant(2);
+                        let denominator = self.add(Node::BinaryOperation(
+                            BinaryOperationType::Power,
+                            right,
+                            two,
+                        ));
+                        self.divide(numerator, denominator)
+                    }
+                    BinaryOperationType::Power => match self.nodes[right].clone() {
+                        Node::Constant(exponent) => {
+                            let lowered = match exponent.checked_sub(&Rational::from(1)) {
+                                Some(lowered) => self.add(Node::Constant(lowered)),
+                                None => {
+                                    let exponent = self.add(Node::Constant(exponent));
+                                    let one = self.constant(1);
+                                    self.add(Node::BinaryOperation(
+                                        BinaryOperationType::Substraction,
+                                        exponent,
+                                        one,
+                                    ))
+                                }
+                            };
+                            let power = self.add(Node::BinaryOperation(
+                                BinaryOperationType::Power,
+                                left,
+                                lowered,
+                            ));
+                            let exponent = self.add(Node::Constant(exponent));
+                            self.product(vec![exponent, power, left_derivative])
+                        }
+                        _ => {
+                            // f^g (g' ln f + g f' / f)
+                            let logarithm = self.add(Node::Function(Function::Logarithm, left));
+                            let first = self.product(vec![right_derivative, logarithm]);
+                            let numerator = self.product(vec![right, left_derivative]);
+                            let second = self.divide(numerator, left);
+                            let factor = self.sum(vec![first, second]);
+                            self.product(vec![id, factor])
+                        }
+                    },
+                }
+            }
+            Node::Function(function, operand) => {
+                let operand_derivative = self.derive(operand, variable);
+                if self.is_zero(operand_derivative) {
+                    operand_derivative
+                } else {
+                    let outer = self.function_derivative(id, function, operand);
+                    self.product(vec![outer, operand_derivative])
+                }
+            }
+            Node::Sum(terms) => {
+                let derivatives = terms
+                    .iter()
+                    .map(|term| self.derive(*term, variable))
+                    .collect();
+                self.sum(derivatives)
+            }
+            Node::Product(factors) => {
+                let terms = (0..factors.len())
+                    .map(|index| {
+                        let mut product = factors.clone();
+                        product[index] = self.derive(factors[index], variable);
+                        self.product(product)
+                    })
+                    .collect();
+                self.sum(terms)
+            }
+        };
+        self.derivatives.insert((id, variable), derivative);
+        derivative
+    }
+
+    /// Derivative of `function` at `operand`, where `id` is the node `function(operand)`.
+    fn function_derivative(&mut self, id: NodeId, function: Function, operand: NodeId) -> NodeId {
+        match function {
+            Function::Sine => self.add(Node::Function(Function::Cosine, operand)),
+            Function::Cosine => {
+                let sine = self.add(Node::Function(Function::Sine, operand));
+                self.negate(sine)
+            }
+            Function::Exponential => id,
+            Function::Logarithm => {
+                let one = self.constant(1);
+                self.divide(one, operand)
+            }
+            // f / |f|, defined away from the zeros of f
+            Function::AbsoluteValue => self.divide(operand, id),
+        }
+    }
+
+    fn constant(&mut self, value: i64) -> NodeId {
+        self.add(Node::Constant(value.into()))
+    }
+
+    fn is_zero(&self, id: NodeId) -> bool {
+        self.nodes[id] == Node::Constant(0.into())
+    }
+
+    fn is_one(&self, id: NodeId) -> bool {
+        self.nodes[id] == Node::Constant(1.into())
+    }
+
+    fn sum(&mut self, terms: Vec<NodeId>) -> NodeId {
+        let terms: Vec<NodeId> = terms.into_iter().filter(|id| !self.is_zero(*id)).collect();
+        match terms.len() {
+            0 => self.constant(0),
+            1 => terms[0],
+            _ => self.add(Node::Sum(terms)),
+        }
+    }
+
+    fn product(&mut self, factors: Vec<NodeId>) -> NodeId {
+        if factors.iter().any(|id| self.is_zero(*id)) {
+            return self.constant(0);
+        }
+        let factors: Vec<NodeId> = factors.into_iter().filter(|id| !self.is_one(*id)).collect();
+        match factors.len() {
+            0 => self.constant(1),
+            1 => factors[0],
+            _ => self.add(Node::Product(factors)),
+        }
+    }
+
+    fn negate(&mut self, id: NodeId) -> NodeId {
+        let minus_one = self.constant(-1);
+        self.product(vec![minus_one, id])
+    }
+
+    fn divide(&mut self, numerator: NodeId, denominator: NodeId) -> NodeId {
+        if self.is_zero(numerator) {
+            return numerator;
+        }
+        self.add(Node::BinaryOperation(
+            BinaryOperationType::Division,
+            numerator,
+            denominator,
+        ))
+    }
+
+    /// Expression for `id` where the nodes bound to temporaries are replaced by them.
+    fn reference(&self, temporaries: &HashMap<NodeId, Variable>, id: NodeId) -> Expression {
+        match temporaries.get(&id) {
+            Some(variable) => Expression::Monomial(Monomial {
+                factor: 1.into(),
+                variable: *variable,
+                power: 1,
+            }),
+            None => self.build(id, &|child| self.reference(temporaries, child)),
+        }
+    }
+
+    /// Number of uses of every node by the nodes reachable from `roots`, roots counting as
+    /// one use each.
+    fn use_counts(&self, roots: &[NodeId]) -> Vec<usize> {
+        let mut counts = vec![0; self.nodes.len()];
+        let mut reachable = vec![false; self.nodes.len()];
+        for root in roots {
+            counts[*root] += 1;
+            reachable[*root] = true;
+        }
+        for id in (0..self.nodes.len()).rev() {
+            if reachable[id] {
+                for child in self.nodes[id].children() {
+                    counts[child] += 1;
+                    reachable[child] = true;
+                }
+            }
+        }
+        counts
+    }
+}
+
+/// Shared subexpressions of several expressions, bound once to temporaries.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommonSubexpressions {
+    /// Temporaries in evaluation order, each defined in terms of the previous ones.
+    pub bindings: Vec<(Variable, Expression)>,
+    /// The input expressions in terms of the temporaries.
+    pub results: Vec<Expression>,
+}
+
+/// Variable standing for the `index`-th temporary, taken from a Unicode private use area so
+/// that it cannot clash with the variables of an expression.
+pub fn temporary(index: usize) -> Variable {
+    u32::try_from(index)
+        .ok()
+        .filter(|index| *index < 0xFFFE)
+        .and_then(|index| char::from_u32(0xF0000 + index))
+        .expect("too many temporaries")
+}
+
+/// Index of the temporary `variable`, if it is one.
+pub fn temporary_index(variable: Variable) -> Option<usize> {
+    (variable as u32)
+        .checked_sub(0xF0000)
+        .filter(|index| *index < 0xFFFE)
+        .map(|index| index as usize)
+}
+
+/// Binds every compound subexpression used more than once across `expressions` to a
+/// temporary.
+pub fn eliminate_common_subexpressions(expressions: &[Expression]) -> CommonSubexpressions {
+    let mut dag = ExpressionDag::new();
+    let roots: Vec<NodeId> = expressions
+        .iter()
+        .map(|expression| dag.intern(expression))
+        .collect();
+    let counts = dag.use_counts(&roots);
+    let mut temporaries: HashMap<NodeId, Variable> = HashMap::new();
+    let mut bindings = vec![];
+    for (id, count) in counts.into_iter().enumerate() {
+        let compound = !dag.node(id).children().is_empty();
+        if compound && count > 1 {
+            let value = dag.build(id, &|child| dag.reference(&temporaries, child));
+            let variable = temporary(bindings.len());
+            bindings.push((variable, value));
+            temporaries.insert(id, variable);
+        }
+    }
+    CommonSubexpressions {
+        bindings,
+        results: roots
+            .iter()
+            .map(|root| dag.reference(&temporaries, *root))
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::derivable::Derivable;
+    use crate::evaluable::Evaluable;
+
+    #[test]
+    fn test_structurally_equal_subexpressions_are_shared() {
+        let sine = Expression::function(Function::Sine, Expression::monomial(1, 'x', 1));
+        let expression = Expression::Sum(vec![
+            sine.clone(),
+            Expression::Product(vec![sine.clone(), sine]),
+        ]);
+        let mut dag = ExpressionDag::new();
+        let root = dag.intern(&expression);
+        // x, sin(x), the product and the sum
+        assert_eq!(dag.len(), 4);
+        assert_eq!(dag.to_expression(root), expression);
+        assert_eq!(dag.intern(&expression), root);
+    }
+
+    #[test]
+    fn test_derivatives_share_subexpressions() {
+        // sin(x y) cos(x + y) exp(x) / y, derived three times in x and twice in y
+        let expression = Expression::BinaryOperation(BinaryOperation {
+            operation: BinaryOperationType::Division,
+            left_value: Box::new(Expression::Product(vec![
+                Expression::function(
+                    Function::Sine,
+                    Expression::Product(vec![
+                        Expression::monomial(1, 'x', 1),
+                        Expression::monomial(1, 'y', 1),
+                    ]),
+                ),
+                Expression::function(
+                    Function::Cosine,
+                    Expression::Sum(vec![
+                        Expression::monomial(1, 'x', 1),
+                        Expression::monomial(1, 'y', 1),
+                    ]),
+                ),
+                Expression::function(Function::Exponential, Expression::monomial(1, 'x', 1)),
+            ])),
+            right_value: Box::new(Expression::monomial(1, 'y', 1)),
+        });
+        let variables = ['x', 'x', 'x', 'y', 'y'];
+        let mut dag = ExpressionDag::new();
+        let derivative = variables
+            .iter()
+            .fold(dag.intern(&expression), |id, variable| {
+                dag.derive(id, *variable)
+            });
+        let tree = expression.derive_multi(&variables);
+        assert!(dag.len() * 20 < tree.size());
+
+        let values = HashMap::from([('x', 0.4), ('y', 1.3)]);
+        let shared = dag.to_expression(derivative).evaluate(&values).unwrap();
+        assert!((shared - tree.evaluate(&values).unwrap()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_derivative_overflow_is_kept_symbolic() {
+        let mut dag = ExpressionDag::new();
+        let cube = dag.intern(&Expression::monomial(i64::MAX, 'x', 3));
+        let derivative = dag.derive(cube, 'x');
+        assert_eq!(
+            dag.to_expression(derivative),
+            Expression::Product(vec![
+                Expression::Constant(3.into()),
+                Expression::monomial(i64::MAX, 'x', 2),
+            ])
+        );
+
+        let sine = Expression::function(Function::Sine, Expression::monomial(1, 'x', 1));
+        let power = dag.intern(&Expression::binary(
+            BinaryOperationType::Power,
+            sine.clone(),
+            Expression::Constant(i64::MIN.into()),
+        ));
+        let derivative = dag.derive(power, 'x');
+        assert_eq!(
+            dag.to_expression(derivative),
+            Expression::Product(vec![
+                Expression::Constant(i64::MIN.into()),
+                Expression::binary(
+                    BinaryOperationType::Power,
+                    sine,
+                    Expression::binary(
+                        BinaryOperationType::Substraction,
+                        Expression::Constant(i64::MIN.into()),
+                        Expression::Constant(1.into()),
+                    ),
+                ),
+                Expression::function(Function::Cosine, Expression::monomial(1, 'x', 1)),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_eliminate_common_subexpressions() {
+        // sin(x y) + sin(x y)^2, and exp(sin(x y))
+        let sine = Expression::function(
+            Function::Sine,
+            Expression::Product(vec![
+                Expression::monomial(1, 'x', 1),
+                Expression::monomial(1, 'y', 1),
+            ]),
+        );
+        let square = Expression::BinaryOperation(BinaryOperation {
+            operation: BinaryOperationType::Power,
+            left_value: Box::new(sine.clone()),
+            right_value: Box::new(Expression::Constant(2.into())),
+        });
+        let cse = eliminate_common_subexpressions(&[
+            Expression::Sum(vec![sine.clone(), square]),
+            Expression::function(Function::Exponential, sine.clone()),
+        ]);
+        let t0 = temporary(0);
+        assert_eq!(cse.bindings, vec![(t0, sine)]);
+        assert_eq!(
+            cse.results[1],
+            Expression::function(Function::Exponential, Expression::monomial(1, t0, 1))
+        );
+        assert_eq!(temporary_index(t0), Some(0));
+        assert_eq!(temporary_index('x'), None);
+    }
+}
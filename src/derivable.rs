@@ -1,16 +1,27 @@
+use std::collections::HashMap;
+
+use num_traits::{CheckedMul, CheckedSub};
+
 use crate::expression::{
-    BinaryOperation, BinaryOperationType, Expression, Function, Monomial, Variable,
+    BinaryOperation, BinaryOperationType, Expression, Function, Monomial, Rational, Variable,
 };
 use crate::simplifiable::*;
 
 pub trait Derivable {
     fn derive(&self, derivation_variable: Variable) -> Self;
+    /// `n`-th derivative with respect to `derivation_variable`.
+    fn derive_n(&self, derivation_variable: Variable, n: usize) -> Self;
+    /// Derives with respect to each of `derivation_variables` in turn. See
+    /// `PartialDerivatives` to reuse the results between calls.
+    fn derive_multi(&self, derivation_variables: &[Variable]) -> Self;
 }
 
 impl Derivable for Expression {
     fn derive(&self, derivation_variable: Variable) -> Expression {
         match self {
-            Expression::Constant(_) | Expression::NamedConstant(_) => Expression::Constant(0.),
+            Expression::Constant(_) | Expression::NamedConstant(_) => {
+                Expression::Constant(0.into())
+            }
             Expression::Monomial(m) => {
                 derive_monomial(derivation_variable, m).simplify_expression()
             }
@@ -21,9 +32,111 @@ impl Derivable for Expression {
                 function,
                 expression,
             } => derive_function(*function, expression, derivation_variable),
-            _ => unimplemented!(),
+            Expression::Sum(terms) => Expression::Sum(
+                terms
+                    .iter()
+                    .map(|term| term.derive(derivation_variable))
+                    .collect(),
+            )
+            .simplify_expression(),
+            Expression::Product(factors) => {
+                derive_product(derivation_variable, factors).simplify_expression()
+            }
+        }
+    }
+
+    fn derive_n(&self, derivation_variable: Variable, n: usize) -> Expression {
+        self.derive_multi(&vec![derivation_variable; n])
+    }
+
+    fn derive_multi(&self, derivation_variables: &[Variable]) -> Expression {
+        derivation_variables
+            .iter()
+            .fold(self.clone(), |derivative, variable| {
+                derivative.derive(*variable)
+            })
+    }
+}
+
+/// Memoized partial derivatives of an expression. Mixed partials are identified by the
+/// multiset of their variables, the order of derivation not mattering for the smooth
+/// expressions this crate builds, and each is derived from the highest-order known derivative
+/// whose variables it contains.
+#[derive(Debug, Clone)]
+pub struct PartialDerivatives {
+    expression: Expression,
+    derivatives: HashMap<Vec<Variable>, Expression>,
+}
+
+impl PartialDerivatives {
+    pub fn new(expression: Expression) -> PartialDerivatives {
+        PartialDerivatives {
+            expression,
+            derivatives: HashMap::new(),
+        }
+    }
+
+    pub fn expression(&self) -> &Expression {
+        &self.expression
+    }
+
+    /// Number of derivatives computed so far.
+    pub fn computed(&self) -> usize {
+        self.derivatives.len()
+    }
+
+    pub fn derivative(&mut self, derivation_variables: &[Variable]) -> &Expression {
+        let mut key = derivation_variables.to_vec();
+        key.sort();
+        if key.is_empty() {
+            return &self.expression;
+        }
+        let (mut known, remaining) = self
+            .derivatives
+            .keys()
+            .filter_map(|known| Some((known, multiset_difference(&key, known)?)))
+            .max_by_key(|(known, _)| (known.len(), *known))
+            .map(|(known, remaining)| (known.clone(), remaining))
+            .unwrap_or_else(|| (vec![], key.clone()));
+        for variable in remaining {
+            let derivative = if known.is_empty() {
+                self.expression.derive(variable)
+            } else {
+                self.derivatives[&known].derive(variable)
+            };
+            known.push(variable);
+            known.sort();
+            self.derivatives.insert(known.clone(), derivative);
+        }
+        &self.derivatives[&key]
+    }
+}
+
+/// The variables of `whole` left after removing those of `part`, both sorted, or `None` when
+/// `part` is not contained in `whole`.
+fn multiset_difference(whole: &[Variable], part: &[Variable]) -> Option<Vec<Variable>> {
+    let mut remaining = vec![];
+    let mut part = part.iter().peekable();
+    for variable in whole {
+        if part.peek() == Some(&variable) {
+            part.next();
+        } else {
+            remaining.push(*variable);
         }
     }
+    part.peek().is_none().then_some(remaining)
+}
+
+fn derive_product(derivation_variable: Variable, factors: &[Expression]) -> Expression {
+    Expression::Sum(
+        (0..factors.len())
+            .map(|index| {
+                let mut product = factors.to_vec();
+                product[index] = factors[index].derive(derivation_variable);
+                Expression::Product(product)
+            })
+            .collect(),
+    )
 }
 
 fn derive_monomial(derivation_variable: Variable, monomial: &Monomial) -> Expression {
@@ -31,14 +144,18 @@ fn derive_monomial(derivation_variable: Variable, monomial: &Monomial) -> Expres
         if monomial.power == 1 {
             Expression::Constant(monomial.factor)
         } else {
-            Expression::Monomial(Monomial {
-                factor: monomial.factor * (monomial.power as f64),
-                variable: monomial.variable,
-                power: monomial.power - 1,
-            })
+            let power = Rational::from(i64::from(monomial.power));
+            match monomial.factor.checked_mul(&power) {
+                Some(factor) => Expression::monomial(factor, monomial.variable, monomial.power - 1),
+                None => Expression::binary(
+                    BinaryOperationType::Multiplication,
+                    Expression::Constant(power),
+                    Expression::monomial(monomial.factor, monomial.variable, monomial.power - 1),
+                ),
+            }
         }
     } else {
-        Expression::Constant(0.)
+        Expression::Constant(0.into())
     }
 }
 
@@ -64,83 +181,222 @@ fn derive_operation(derivation_variable: Variable, operation: &BinaryOperation)
                 right_value: Box::new(*operation.left_value.clone()),
             })),
         }),
-        _ => unimplemented!(),
+        BinaryOperationType::Power => match &*operation.right_value {
+            Expression::Constant(exponent) => {
+                derive_power(derivation_variable, &operation.left_value, *exponent)
+            }
+            exponent => derive_general_power(derivation_variable, &operation.left_value, exponent),
+        },
+        // (f' g - f g') / g^2
+        BinaryOperationType::Division => Expression::BinaryOperation(BinaryOperation {
+            operation: BinaryOperationType::Division,
+            left_value: Box::new(Expression::BinaryOperation(BinaryOperation {
+                operation: BinaryOperationType::Substraction,
+                left_value: Box::new(Expression::Product(vec![
+                    operation.left_value.derive(derivation_variable),
+                    *operation.right_value.clone(),
+                ])),
+                right_value: Box::new(Expression::Product(vec![
+                    *operation.left_value.clone(),
+                    operation.right_value.derive(derivation_variable),
+                ])),
+            })),
+            right_value: Box::new(Expression::BinaryOperation(BinaryOperation {
+                operation: BinaryOperationType::Power,
+                left_value: Box::new(*operation.right_value.clone()),
+                right_value: Box::new(Expression::Constant(2.into())),
+            })),
+        }),
     }
 }
 
+/// f^g (g' ln f + g f' / f), defined where f is positive
+fn derive_general_power(
+    derivation_variable: Variable,
+    base: &Expression,
+    exponent: &Expression,
+) -> Expression {
+    Expression::Product(vec![
+        Expression::BinaryOperation(BinaryOperation {
+            operation: BinaryOperationType::Power,
+            left_value: Box::new(base.clone()),
+            right_value: Box::new(exponent.clone()),
+        }),
+        Expression::Sum(vec![
+            Expression::Product(vec![
+                exponent.derive(derivation_variable),
+                Expression::Function {
+                    function: Function::Logarithm,
+                    expression: Box::new(base.clone()),
+                },
+            ]),
+            Expression::BinaryOperation(BinaryOperation {
+                operation: BinaryOperationType::Division,
+                left_value: Box::new(Expression::Product(vec![
+                    exponent.clone(),
+                    base.derive(derivation_variable),
+                ])),
+                right_value: Box::new(base.clone()),
+            }),
+        ]),
+    ])
+}
+
+fn derive_power(
+    derivation_variable: Variable,
+    base: &Expression,
+    exponent: Rational,
+) -> Expression {
+    let one = Rational::from(1);
+    let lowered = match exponent.checked_sub(&one) {
+        Some(lowered) => Expression::Constant(lowered),
+        None => Expression::binary(
+            BinaryOperationType::Substraction,
+            Expression::Constant(exponent),
+            Expression::Constant(one),
+        ),
+    };
+    Expression::BinaryOperation(BinaryOperation {
+        operation: BinaryOperationType::Multiplication,
+        left_value: Box::new(Expression::BinaryOperation(BinaryOperation {
+            operation: BinaryOperationType::Multiplication,
+            left_value: Box::new(Expression::Constant(exponent)),
+            right_value: Box::new(Expression::BinaryOperation(BinaryOperation {
+                operation: BinaryOperationType::Power,
+                left_value: Box::new(base.clone()),
+                right_value: Box::new(lowered),
+            })),
+        })),
+        right_value: Box::new(base.derive(derivation_variable)),
+    })
+}
+
 fn derive_function(
     function: Function,
     expression: &Expression,
     derivation_variable: Variable,
 ) -> Expression {
     if !expression.variables().contains(&derivation_variable) {
-        return Expression::Constant(0.);
+        return Expression::Constant(0.into());
     }
-    match function {
-        Function::Cosine => {
-            unimplemented!()
-        }
+    let derivative = match function {
+        Function::Cosine => Expression::BinaryOperation(BinaryOperation {
+            operation: BinaryOperationType::Multiplication,
+            left_value: Box::new(Expression::Constant((-1).into())),
+            right_value: Box::new(Expression::Function {
+                function: Function::Sine,
+                expression: Box::new(expression.clone()),
+            }),
+        }),
         Function::Sine => Expression::Function {
             function: Function::Cosine,
             expression: Box::new(expression.clone()),
         },
-    }
+        Function::Exponential => Expression::Function {
+            function: Function::Exponential,
+            expression: Box::new(expression.clone()),
+        },
+        Function::Logarithm => Expression::BinaryOperation(BinaryOperation {
+            operation: BinaryOperationType::Power,
+            left_value: Box::new(expression.clone()),
+            right_value: Box::new(Expression::Constant((-1).into())),
+        }),
+        // f / |f|, defined away from the zeros of f
+        Function::AbsoluteValue => Expression::BinaryOperation(BinaryOperation {
+            operation: BinaryOperationType::Multiplication,
+            left_value: Box::new(expression.clone()),
+            right_value: Box::new(Expression::BinaryOperation(BinaryOperation {
+                operation: BinaryOperationType::Power,
+                left_value: Box::new(Expression::Function {
+                    function: Function::AbsoluteValue,
+                    expression: Box::new(expression.clone()),
+                }),
+                right_value: Box::new(Expression::Constant((-1).into())),
+            })),
+        }),
+    };
+    Expression::BinaryOperation(BinaryOperation {
+        operation: BinaryOperationType::Multiplication,
+        left_value: Box::new(derivative),
+        right_value: Box::new(expression.derive(derivation_variable)),
+    })
+    .simplify_expression()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    use crate::derivable::*;
+    use crate::canonical::Canonical;
 
     #[test]
     fn derive_constant() {
         assert_eq!(
-            Expression::Constant(5.).derive('x'),
-            Expression::Constant(0.)
+            Expression::Constant(5.into()).derive('x'),
+            Expression::Constant(0.into())
         );
 
         assert_eq!(
-            Expression::NamedConstant("pi").derive('x'),
-            Expression::Constant(0.)
+            Expression::NamedConstant("pi".to_string()).derive('x'),
+            Expression::Constant(0.into())
         );
     }
 
     #[test]
     fn derive_monomial() {
         let x_monomial = Expression::Monomial(Monomial {
-            factor: 5.,
+            factor: 5.into(),
             variable: 'x',
             power: 1,
         });
-        assert_eq!(x_monomial.derive('y'), Expression::Constant(0.));
-        assert_eq!(x_monomial.derive('x'), Expression::Constant(5.));
+        assert_eq!(x_monomial.derive('y'), Expression::Constant(0.into()));
+        assert_eq!(x_monomial.derive('x'), Expression::Constant(5.into()));
 
         let x_squared_monomial = Expression::Monomial(Monomial {
-            factor: 3.,
+            factor: 3.into(),
             variable: 'x',
             power: 2,
         });
-        assert_eq!(x_squared_monomial.derive('y'), Expression::Constant(0.));
+        assert_eq!(
+            x_squared_monomial.derive('y'),
+            Expression::Constant(0.into())
+        );
         assert_eq!(
             x_squared_monomial.derive('x'),
             Expression::Monomial(Monomial {
-                factor: 6.,
+                factor: 6.into(),
                 variable: 'x',
                 power: 1
             })
         );
 
         let x_3_monomial = Expression::Monomial(Monomial {
-            factor: 5.,
+            factor: 5.into(),
             variable: 'x',
             power: 3,
         });
-        assert_eq!(x_3_monomial.derive('y'), Expression::Constant(0.));
+        assert_eq!(x_3_monomial.derive('y'), Expression::Constant(0.into()));
         assert_eq!(
             x_3_monomial.derive('x'),
             Expression::Monomial(Monomial {
-                factor: 15.,
+                factor: 15.into(),
+                variable: 'x',
+                power: 2
+            })
+        );
+    }
+
+    #[test]
+    fn derive_monomial_with_rational_factor() {
+        let expr = Expression::Monomial(Monomial {
+            factor: Rational::new(1, 3),
+            variable: 'x',
+            power: 3,
+        });
+        assert_eq!(
+            expr.derive('x'),
+            Expression::Monomial(Monomial {
+                factor: 1.into(),
                 variable: 'x',
                 power: 2
             })
@@ -150,12 +406,12 @@ mod tests {
     #[test]
     fn derive_sum() {
         let left_monomial = Expression::Monomial(Monomial {
-            factor: 5.,
+            factor: 5.into(),
             variable: 'x',
             power: 1,
         });
         let right_monomial = Expression::Monomial(Monomial {
-            factor: 3.,
+            factor: 3.into(),
             variable: 'x',
             power: 2,
         });
@@ -165,14 +421,14 @@ mod tests {
             right_value: Box::new(right_monomial.clone()),
         });
 
-        assert_eq!(sum.derive('y'), Expression::Constant(0.));
+        assert_eq!(sum.derive('y'), Expression::Constant(0.into()));
         assert_eq!(
             sum.derive('x'),
             Expression::BinaryOperation(BinaryOperation {
                 operation: BinaryOperationType::Addition,
-                left_value: Box::new(Expression::Constant(5.)),
+                left_value: Box::new(Expression::Constant(5.into())),
                 right_value: Box::new(Expression::Monomial(Monomial {
-                    factor: 6.,
+                    factor: 6.into(),
                     variable: 'x',
                     power: 1
                 }))
@@ -183,12 +439,12 @@ mod tests {
     #[test]
     fn derive_product() {
         let left_monomial = Expression::Monomial(Monomial {
-            factor: 5.,
+            factor: 5.into(),
             variable: 'x',
             power: 1,
         });
         let right_monomial = Expression::Monomial(Monomial {
-            factor: 3.,
+            factor: 3.into(),
             variable: 'y',
             power: 2,
         });
@@ -201,7 +457,7 @@ mod tests {
         assert_eq!(
             product.derive('x'),
             Expression::Monomial(Monomial {
-                factor: 15.,
+                factor: 15.into(),
                 variable: 'y',
                 power: 2
             })
@@ -211,18 +467,128 @@ mod tests {
             Expression::BinaryOperation(BinaryOperation {
                 operation: BinaryOperationType::Multiplication,
                 left_value: Box::new(Expression::Monomial(Monomial {
-                    factor: 6.,
+                    factor: 6.into(),
                     variable: 'y',
                     power: 1
                 })),
                 right_value: Box::new(Expression::Monomial(Monomial {
-                    factor: 5.,
+                    factor: 5.into(),
                     variable: 'x',
                     power: 1
                 }))
             })
         );
-        assert_eq!(product.derive('z'), Expression::Constant(0.));
+        assert_eq!(product.derive('z'), Expression::Constant(0.into()));
+    }
+
+    #[test]
+    fn test_derive_monomial_overflow() {
+        let monomial = Expression::monomial(i64::MAX, 'x', 3);
+        assert_eq!(
+            monomial.derive('x'),
+            Expression::binary(
+                BinaryOperationType::Multiplication,
+                Expression::Constant(3.into()),
+                Expression::monomial(i64::MAX, 'x', 2),
+            )
+        );
+    }
+
+    #[test]
+    fn test_derive_power_overflow() {
+        let sine = Expression::function(Function::Sine, Expression::monomial(1, 'x', 1));
+        let power = Expression::binary(
+            BinaryOperationType::Power,
+            sine.clone(),
+            Expression::Constant(i64::MIN.into()),
+        );
+        assert_eq!(
+            power.derive('x'),
+            Expression::binary(
+                BinaryOperationType::Multiplication,
+                Expression::binary(
+                    BinaryOperationType::Multiplication,
+                    Expression::Constant(i64::MIN.into()),
+                    Expression::binary(
+                        BinaryOperationType::Power,
+                        sine.clone(),
+                        Expression::binary(
+                            BinaryOperationType::Substraction,
+                            Expression::Constant(i64::MIN.into()),
+                            Expression::Constant(1.into()),
+                        ),
+                    ),
+                ),
+                sine.derive('x'),
+            )
+        );
+    }
+
+    #[test]
+    fn test_derive_composed_functions() {
+        // cos(2x) -> -1 * sin(2x) * 2
+        let double_angle = Expression::Monomial(Monomial {
+            factor: 2.into(),
+            variable: 'x',
+            power: 1,
+        });
+        let expr = Expression::Function {
+            function: Function::Cosine,
+            expression: Box::new(double_angle.clone()),
+        };
+        assert_eq!(
+            expr.derive('x'),
+            Expression::BinaryOperation(BinaryOperation {
+                operation: BinaryOperationType::Multiplication,
+                left_value: Box::new(Expression::BinaryOperation(BinaryOperation {
+                    operation: BinaryOperationType::Multiplication,
+                    left_value: Box::new(Expression::Constant((-1).into())),
+                    right_value: Box::new(Expression::Function {
+                        function: Function::Sine,
+                        expression: Box::new(double_angle),
+                    }),
+                })),
+                right_value: Box::new(Expression::Constant(2.into())),
+            })
+        );
+    }
+
+    #[test]
+    fn derive_n_ary_product() {
+        // d/dx (x * y * x^2) = 3 x^2 y
+        let product = Expression::Product(vec![
+            Expression::Monomial(Monomial {
+                factor: 1.into(),
+                variable: 'x',
+                power: 1,
+            }),
+            Expression::Monomial(Monomial {
+                factor: 1.into(),
+                variable: 'y',
+                power: 1,
+            }),
+            Expression::Monomial(Monomial {
+                factor: 1.into(),
+                variable: 'x',
+                power: 2,
+            }),
+        ]);
+        assert_eq!(
+            product.derive('x').to_canonical_form(),
+            Expression::Product(vec![
+                Expression::Constant(3.into()),
+                Expression::Monomial(Monomial {
+                    factor: 1.into(),
+                    variable: 'x',
+                    power: 2,
+                }),
+                Expression::Monomial(Monomial {
+                    factor: 1.into(),
+                    variable: 'y',
+                    power: 1,
+                }),
+            ])
+        );
     }
 
     #[test]
@@ -230,22 +596,105 @@ mod tests {
         let expr = Expression::Function {
             function: Function::Sine,
             expression: Box::new(Expression::Monomial(Monomial {
-                factor: 1.0,
+                factor: 1.into(),
                 variable: 'x',
                 power: 1,
             })),
         };
-        assert_eq!(expr.derive('y'), Expression::Constant(0.));
+        assert_eq!(expr.derive('y'), Expression::Constant(0.into()));
         assert_eq!(
             expr.derive('x'),
             Expression::Function {
                 function: Function::Cosine,
                 expression: Box::new(Expression::Monomial(Monomial {
-                    factor: 1.0,
+                    factor: 1.into(),
                     variable: 'x',
                     power: 1,
                 }))
             }
         );
     }
+
+    #[test]
+    fn test_derive_n() {
+        // x^3 -> 3x^2 -> 6x -> 6 -> 0
+        let expr = Expression::Monomial(Monomial {
+            factor: 1.into(),
+            variable: 'x',
+            power: 3,
+        });
+        assert_eq!(
+            expr.derive_n('x', 2),
+            Expression::Monomial(Monomial {
+                factor: 6.into(),
+                variable: 'x',
+                power: 1,
+            })
+        );
+        assert_eq!(expr.derive_n('x', 3), Expression::Constant(6.into()));
+        assert_eq!(expr.derive_n('x', 4), Expression::Constant(0.into()));
+        assert_eq!(expr.derive_n('x', 0), expr);
+    }
+
+    #[test]
+    fn test_mixed_partials_are_symmetric() {
+        use crate::canonical::Canonical;
+
+        let monomial = |variable, power| {
+            Expression::Monomial(Monomial {
+                factor: 1.into(),
+                variable,
+                power,
+            })
+        };
+        // sin(x y) + x^3 y^2 exp(y)
+        let expr = Expression::Sum(vec![
+            Expression::Function {
+                function: Function::Sine,
+                expression: Box::new(Expression::Product(vec![
+                    monomial('x', 1),
+                    monomial('y', 1),
+                ])),
+            },
+            Expression::Product(vec![
+                monomial('x', 3),
+                monomial('y', 2),
+                Expression::Function {
+                    function: Function::Exponential,
+                    expression: Box::new(monomial('y', 1)),
+                },
+            ]),
+        ]);
+        for (first, second) in [(['x', 'y'], ['y', 'x']), (['x', 'x'], ['x', 'x'])] {
+            assert_eq!(
+                expr.derive_multi(&first).to_canonical_form(),
+                expr.derive_multi(&second).to_canonical_form()
+            );
+        }
+        assert_eq!(
+            expr.derive_multi(&['x', 'y', 'y']).to_canonical_form(),
+            expr.derive_multi(&['y', 'x', 'y']).to_canonical_form()
+        );
+
+        let mut derivatives = PartialDerivatives::new(expr.clone());
+        let mixed = derivatives.derivative(&['y', 'x']).clone();
+        assert_eq!(derivatives.computed(), 2);
+        assert_eq!(derivatives.derivative(&['x', 'y']), &mixed);
+        assert_eq!(derivatives.computed(), 2);
+        assert_eq!(
+            mixed.to_canonical_form(),
+            expr.derive_multi(&['y', 'x']).to_canonical_form()
+        );
+        // reuses d2/dxdy, which is not a prefix of the sorted variables, adds d3/dx2dy
+        let third = derivatives.derivative(&['y', 'x', 'x']).clone();
+        assert_eq!(derivatives.computed(), 3);
+        assert_eq!(
+            third.to_canonical_form(),
+            expr.derive_multi(&['x', 'x', 'y']).to_canonical_form()
+        );
+        // reuses d3/dx2dy
+        derivatives.derivative(&['x', 'y', 'x', 'y']);
+        assert_eq!(derivatives.computed(), 4);
+        assert_eq!(derivatives.derivative(&[]), &expr);
+    }
 }
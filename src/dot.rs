@@ -0,0 +1,240 @@
+use std::collections::HashMap;
+
+use crate::expression::{BinaryOperationType, Expression, Function};
+
+/// Options of the Graphviz export.
+#[derive(Debug, Clone, Default)]
+pub struct DotOptions {
+    /// Draw structurally equal subexpressions once, highlighting the ones used several times.
+    pub sharing: bool,
+    /// Earlier version of the expression whose differences are highlighted.
+    pub previous: Option<Expression>,
+}
+
+impl DotOptions {
+    pub fn with_sharing(self) -> Self {
+        DotOptions {
+            sharing: true,
+            ..self
+        }
+    }
+
+    pub fn with_changes_from(self, previous: &Expression) -> Self {
+        DotOptions {
+            previous: Some(previous.clone()),
+            ..self
+        }
+    }
+}
+
+fn label(expression: &Expression) -> String {
+    match expression {
+        Expression::Constant(value) => value.to_string(),
+        Expression::NamedConstant(name) => name.to_string(),
+        Expression::Monomial(m) => {
+            let factor = if m.factor == 1.into() {
+                String::new()
+            } else if m.factor == (-1).into() {
+                "-".to_string()
+            } else if m.factor.is_integer() {
+                m.factor.to_string()
+            } else {
+                format!("({}) ", m.factor)
+            };
+            let power = if m.power == 1 {
+                String::new()
+            } else {
+                format!("^{}", m.power)
+            };
+            format!("{factor}{}{power}", m.variable)
+        }
+        Expression::BinaryOperation(operation) => match operation.operation {
+            BinaryOperationType::Addition => "+",
+            BinaryOperationType::Substraction => "-",
+            BinaryOperationType::Multiplication => "*",
+            BinaryOperationType::Division => "/",
+            BinaryOperationType::Power => "^",
+        }
+        .to_string(),
+        Expression::Function { function, .. } => match function {
+            Function::Sine => "sin",
+            Function::Cosine => "cos",
+            Function::Exponential => "exp",
+            Function::Logarithm => "ln",
+            Function::AbsoluteValue => "abs",
+        }
+        .to_string(),
+        Expression::Sum(_) => "sum".to_string(),
+        Expression::Product(_) => "product".to_string(),
+    }
+}
+
+fn children(expression: &Expression) -> Vec<&Expression> {
+    match expression {
+        Expression::Constant(_) | Expression::NamedConstant(_) | Expression::Monomial(_) => {
+            vec![]
+        }
+        Expression::BinaryOperation(operation) => {
+            vec![&operation.left_value, &operation.right_value]
+        }
+        Expression::Function { expression, .. } => vec![expression],
+        Expression::Sum(operands) | Expression::Product(operands) => operands.iter().collect(),
+    }
+}
+
+#[derive(Default)]
+struct Graph<'a> {
+    sharing: bool,
+    /// Label, number of uses and whether it changed, of every node in order of creation.
+    nodes: Vec<(String, usize, bool)>,
+    edges: Vec<(usize, usize)>,
+    ids: HashMap<&'a Expression, usize>,
+}
+
+impl<'a> Graph<'a> {
+    /// Adds `expression` below its parent. `previous` is the subexpression at the same
+    /// position in the earlier version, if it has the same shape so far; `None` when there is
+    /// no earlier version to compare with.
+    fn add(&mut self, expression: &'a Expression, previous: Option<Option<&Expression>>) -> usize {
+        let changed = match previous {
+            Some(Some(previous)) => {
+                label(previous) != label(expression)
+                    || children(previous).len() != children(expression).len()
+            }
+            Some(None) => true,
+            None => false,
+        };
+        let existing = self
+            .sharing
+            .then(|| self.ids.get(expression).copied())
+            .flatten();
+        let id = existing.unwrap_or_else(|| {
+            self.nodes.push((label(expression), 0, false));
+            self.nodes.len() - 1
+        });
+        self.nodes[id].1 += 1;
+        self.nodes[id].2 |= changed;
+        if self.sharing {
+            self.ids.insert(expression, id);
+        }
+        let previous_children = match previous {
+            Some(Some(previous)) if !changed => children(previous).into_iter().map(Some).collect(),
+            _ => vec![None; children(expression).len()],
+        };
+        for (child, previous_child) in children(expression).into_iter().zip(previous_children) {
+            let previous_child = previous.map(|_| previous_child);
+            let child_id = self.add(child, previous_child);
+            if existing.is_none() {
+                self.edges.push((id, child_id));
+            }
+        }
+        id
+    }
+}
+
+/// Graphviz DOT source drawing `expression` as a tree, operands left to right.
+pub fn to_dot(expression: &Expression, options: &DotOptions) -> String {
+    let mut graph = Graph {
+        sharing: options.sharing,
+        ..Graph::default()
+    };
+    graph.add(expression, options.previous.as_ref().map(Some));
+    let mut dot = String::from("digraph expression {\n    ordering=out;\n    node [shape=box];\n");
+    for (id, (label, uses, changed)) in graph.nodes.iter().enumerate() {
+        let mut attributes = vec![format!(
+            "label=\"{}\"",
+            label.replace('\\', "\\\\").replace('"', "\\\"")
+        )];
+        if options.sharing && *uses > 1 {
+            attributes.push("style=filled, fillcolor=lightblue".to_string());
+        }
+        if *changed {
+            attributes.push("color=red, fontcolor=red, penwidth=2".to_string());
+        }
+        dot += &format!("    n{id} [{}];\n", attributes.join(", "));
+    }
+    for (parent, child) in &graph.edges {
+        dot += &format!("    n{parent} -> n{child};\n");
+    }
+    dot += "}\n";
+    dot
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::expression::BinaryOperation;
+
+    fn sine(expression: Expression) -> Expression {
+        Expression::function(Function::Sine, expression)
+    }
+
+    /// sin(x) * sin(x) + 3x^2
+    fn expression() -> Expression {
+        Expression::BinaryOperation(BinaryOperation {
+            operation: BinaryOperationType::Addition,
+            left_value: Box::new(Expression::Product(vec![
+                sine(Expression::monomial(1, 'x', 1)),
+                sine(Expression::monomial(1, 'x', 1)),
+            ])),
+            right_value: Box::new(Expression::monomial(3, 'x', 2)),
+        })
+    }
+
+    #[test]
+    fn test_tree() {
+        assert_eq!(
+            to_dot(&expression(), &DotOptions::default()),
+            "digraph expression {\n    ordering=out;\n    node [shape=box];\n    \
+             n0 [label=\"+\"];\n    \
+             n1 [label=\"product\"];\n    \
+             n2 [label=\"sin\"];\n    \
+             n3 [label=\"x\"];\n    \
+             n4 [label=\"sin\"];\n    \
+             n5 [label=\"x\"];\n    \
+             n6 [label=\"3x^2\"];\n    \
+             n2 -> n3;\n    n1 -> n2;\n    n4 -> n5;\n    n1 -> n4;\n    n0 -> n1;\n    n0 -> n6;\n\
+             }\n"
+        );
+    }
+
+    #[test]
+    fn test_sharing() {
+        let dot = to_dot(&expression(), &DotOptions::default().with_sharing());
+        assert!(dot.contains("n2 [label=\"sin\", style=filled, fillcolor=lightblue];"));
+        assert!(dot.contains("n3 [label=\"x\", style=filled, fillcolor=lightblue];"));
+        // the product points twice to the same sine
+        assert_eq!(dot.matches("n1 -> n2;").count(), 2);
+        assert!(!dot.contains("n5"));
+    }
+
+    #[test]
+    fn test_changes() {
+        // sin(x) * sin(x) + 3x^2 becomes sin(x) * cos(x) + 3x^2
+        let mut changed = expression();
+        if let Expression::BinaryOperation(operation) = &mut changed {
+            if let Expression::Product(factors) = operation.left_value.as_mut() {
+                factors[1] = Expression::Function {
+                    function: Function::Cosine,
+                    expression: Box::new(Expression::monomial(1, 'x', 1)),
+                };
+            }
+        }
+        let dot = to_dot(
+            &changed,
+            &DotOptions::default().with_changes_from(&expression()),
+        );
+        let highlighted: Vec<&str> = dot
+            .lines()
+            .filter(|line| line.contains("color=red"))
+            .collect();
+        assert_eq!(
+            highlighted,
+            vec![
+                "    n4 [label=\"cos\", color=red, fontcolor=red, penwidth=2];",
+                "    n5 [label=\"x\", color=red, fontcolor=red, penwidth=2];",
+            ]
+        );
+    }
+}
@@ -0,0 +1,850 @@
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap};
+
+use num_traits::{CheckedAdd, CheckedMul, Zero};
+
+use crate::expression::{
+    BinaryOperation, BinaryOperationType, Expression, Function, Monomial, Rational,
+};
+use crate::rewrite::{Bindings, Pattern, RewriteRule, RuleSet};
+use crate::simplifiable::{fold_constants, monomial_power};
+
+/// Index of an equivalence class in an `EGraph`.
+pub type Id = usize;
+
+/// Expression node whose operands are equivalence classes. Sums and products are stored as
+/// nested binary operations.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ENode {
+    Constant(Rational),
+    NamedConstant(String),
+    Monomial(Monomial),
+    BinaryOperation(BinaryOperationType, Id, Id),
+    Function(Function, Id),
+}
+
+impl ENode {
+    fn children(&self) -> Vec<Id> {
+        match self {
+            ENode::BinaryOperation(_, left, right) => vec![*left, *right],
+            ENode::Function(_, argument) => vec![*argument],
+            _ => vec![],
+        }
+    }
+}
+
+/// Equivalence classes of expressions, closed under congruence after each `rebuild`.
+#[derive(Debug, Default)]
+pub struct EGraph {
+    parents: Vec<Id>,
+    memo: HashMap<ENode, Id>,
+    classes: BTreeMap<Id, Vec<ENode>>,
+}
+
+impl EGraph {
+    pub fn new() -> EGraph {
+        EGraph::default()
+    }
+
+    pub fn find(&self, mut id: Id) -> Id {
+        while self.parents[id] != id {
+            id = self.parents[id];
+        }
+        id
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.memo.len()
+    }
+
+    pub fn class_count(&self) -> usize {
+        self.classes.len()
+    }
+
+    /// Nodes of the class of `id`, as of the last `rebuild`.
+    pub fn nodes(&self, id: Id) -> &[ENode] {
+        self.classes
+            .get(&self.find(id))
+            .map_or(&[], |nodes| nodes.as_slice())
+    }
+
+    fn canonicalize(&self, node: &ENode) -> ENode {
+        match node {
+            ENode::BinaryOperation(operation, left, right) => {
+                ENode::BinaryOperation(*operation, self.find(*left), self.find(*right))
+            }
+            ENode::Function(function, argument) => ENode::Function(*function, self.find(*argument)),
+            _ => node.clone(),
+        }
+    }
+
+    /// Adds `node` unless an equal node exists. Monomials are also added as `c * x^p` and
+    /// `x^p`, the forms patterns match them by.
+    pub fn add(&mut self, node: ENode) -> Id {
+        let node = self.canonicalize(&node);
+        if let Some(id) = self.memo.get(&node) {
+            return self.find(*id);
+        }
+        let id = self.parents.len();
+        self.parents.push(id);
+        self.memo.insert(node.clone(), id);
+        self.classes.insert(id, vec![node.clone()]);
+        if let ENode::Monomial(m) = node {
+            if m.factor != 1.into() {
+                let constant = self.add(ENode::Constant(m.factor));
+                let power = self.add(ENode::Monomial(Monomial {
+                    factor: 1.into(),
+                    variable: m.variable,
+                    power: m.power,
+                }));
+                let view = self.add(ENode::BinaryOperation(
+                    BinaryOperationType::Multiplication,
+                    constant,
+                    power,
+                ));
+                self.union(id, view);
+            } else if m.power >= 2 {
+                let variable = self.add(ENode::Monomial(Monomial {
+                    factor: 1.into(),
+                    variable: m.variable,
+                    power: 1,
+                }));
+                let exponent = self.add(ENode::Constant(i64::from(m.power).into()));
+                let view = self.add(ENode::BinaryOperation(
+                    BinaryOperationType::Power,
+                    variable,
+                    exponent,
+                ));
+                self.union(id, view);
+            }
+        }
+        self.find(id)
+    }
+
+    pub fn add_expression(&mut self, expression: &Expression) -> Id {
+        match expression {
+            Expression::Constant(value) => self.add(ENode::Constant(*value)),
+            Expression::NamedConstant(name) => self.add(ENode::NamedConstant(name.clone())),
+            Expression::Monomial(m) => self.add(ENode::Monomial(m.clone())),
+            Expression::BinaryOperation(operation) => {
+                let left = self.add_expression(&operation.left_value);
+                let right = self.add_expression(&operation.right_value);
+                self.add(ENode::BinaryOperation(operation.operation, left, right))
+            }
+            Expression::Function {
+                function,
+                expression,
+            } => {
+                let argument = self.add_expression(expression);
+                self.add(ENode::Function(*function, argument))
+            }
+            Expression::Sum(operands) => self.add_operands(BinaryOperationType::Addition, operands),
+            Expression::Product(operands) => {
+                self.add_operands(BinaryOperationType::Multiplication, operands)
+            }
+        }
+    }
+
+    fn add_operands(&mut self, operation: BinaryOperationType, operands: &[Expression]) -> Id {
+        let ids: Vec<Id> = operands
+            .iter()
+            .map(|operand| self.add_expression(operand))
+            .collect();
+        match ids
+            .into_iter()
+            .reduce(|left, right| self.add(ENode::BinaryOperation(operation, left, right)))
+        {
+            Some(id) => id,
+            None => self.add_expression(&operation.identity_element()),
+        }
+    }
+
+    /// Class of `expression` if the graph already represents it.
+    pub fn lookup(&self, expression: &Expression) -> Option<Id> {
+        let node = match expression {
+            Expression::Constant(value) => ENode::Constant(*value),
+            Expression::NamedConstant(name) => ENode::NamedConstant(name.clone()),
+            Expression::Monomial(m) => ENode::Monomial(m.clone()),
+            Expression::BinaryOperation(operation) => ENode::BinaryOperation(
+                operation.operation,
+                self.lookup(&operation.left_value)?,
+                self.lookup(&operation.right_value)?,
+            ),
+            Expression::Function {
+                function,
+                expression,
+            } => ENode::Function(*function, self.lookup(expression)?),
+            Expression::Sum(operands) => {
+                return self.lookup_operands(BinaryOperationType::Addition, operands)
+            }
+            Expression::Product(operands) => {
+                return self.lookup_operands(BinaryOperationType::Multiplication, operands)
+            }
+        };
+        self.memo
+            .get(&self.canonicalize(&node))
+            .map(|id| self.find(*id))
+    }
+
+    fn lookup_operands(
+        &self,
+        operation: BinaryOperationType,
+        operands: &[Expression],
+    ) -> Option<Id> {
+        let mut ids = operands.iter().map(|operand| self.lookup(operand));
+        let Some(first) = ids.next() else {
+            return self.lookup(&operation.identity_element());
+        };
+        ids.try_fold(first?, |left, right| {
+            let node = ENode::BinaryOperation(operation, left, right?);
+            self.memo.get(&node).map(|id| self.find(*id))
+        })
+    }
+
+    /// Merges two classes, returns whether they were distinct.
+    pub fn union(&mut self, left: Id, right: Id) -> bool {
+        let (left, right) = (self.find(left), self.find(right));
+        if left == right {
+            return false;
+        }
+        self.parents[right] = left;
+        let nodes = self.classes.remove(&right).unwrap_or_default();
+        self.classes.entry(left).or_default().extend(nodes);
+        true
+    }
+
+    /// Restores congruence: nodes with equivalent operands end up in the same class.
+    pub fn rebuild(&mut self) {
+        loop {
+            let mut memo: HashMap<ENode, Id> = HashMap::new();
+            let mut merges = vec![];
+            for (node, id) in &self.memo {
+                let id = self.find(*id);
+                match memo.entry(self.canonicalize(node)) {
+                    Entry::Occupied(entry) => {
+                        if self.find(*entry.get()) != id {
+                            merges.push((*entry.get(), id));
+                        }
+                    }
+                    Entry::Vacant(entry) => {
+                        entry.insert(id);
+                    }
+                }
+            }
+            self.memo = memo;
+            if merges.is_empty() {
+                break;
+            }
+            for (left, right) in merges {
+                self.union(left, right);
+            }
+        }
+        let mut classes: BTreeMap<Id, Vec<ENode>> = BTreeMap::new();
+        for (node, id) in &self.memo {
+            classes
+                .entry(self.find(*id))
+                .or_default()
+                .push(node.clone());
+        }
+        for nodes in classes.values_mut() {
+            nodes.sort();
+        }
+        self.classes = classes;
+    }
+
+    /// Every way the class of `id` matches `pattern`, extending `bindings`.
+    pub fn ematch(
+        &self,
+        pattern: &Pattern,
+        id: Id,
+        bindings: &HashMap<String, Id>,
+    ) -> Vec<HashMap<String, Id>> {
+        let id = self.find(id);
+        match pattern {
+            Pattern::Wildcard(name) => self.bind(name, id, bindings).into_iter().collect(),
+            Pattern::ConstantWildcard(name) => match self.constant(id) {
+                Some(_) => self.bind(name, id, bindings).into_iter().collect(),
+                None => vec![],
+            },
+            Pattern::Exact(expression) => match self.lookup(expression) {
+                Some(exact) if exact == id => vec![bindings.clone()],
+                _ => vec![],
+            },
+            Pattern::BinaryOperation {
+                operation,
+                left,
+                right,
+            } => self
+                .nodes(id)
+                .iter()
+                .flat_map(|node| match node {
+                    ENode::BinaryOperation(o, l, r) if o == operation => {
+                        if operation.is_commutative() && l != r {
+                            vec![(*l, *r), (*r, *l)]
+                        } else {
+                            vec![(*l, *r)]
+                        }
+                    }
+                    _ => vec![],
+                })
+                .flat_map(|(l, r)| {
+                    self.ematch(left, l, bindings)
+                        .into_iter()
+                        .flat_map(move |bindings| self.ematch(right, r, &bindings))
+                })
+                .collect(),
+            Pattern::Function { function, argument } => self
+                .nodes(id)
+                .iter()
+                .flat_map(|node| match node {
+                    ENode::Function(f, a) if f == function => self.ematch(argument, *a, bindings),
+                    _ => vec![],
+                })
+                .collect(),
+        }
+    }
+
+    fn bind(
+        &self,
+        name: &str,
+        id: Id,
+        bindings: &HashMap<String, Id>,
+    ) -> Option<HashMap<String, Id>> {
+        match bindings.get(name) {
+            Some(bound) if self.find(*bound) != id => None,
+            Some(_) => Some(bindings.clone()),
+            None => {
+                let mut bindings = bindings.clone();
+                bindings.insert(name.to_string(), id);
+                Some(bindings)
+            }
+        }
+    }
+
+    fn constant(&self, id: Id) -> Option<Rational> {
+        self.nodes(id).iter().find_map(|node| match node {
+            ENode::Constant(value) => Some(*value),
+            _ => None,
+        })
+    }
+
+    fn monomial(&self, id: Id) -> Option<Monomial> {
+        self.nodes(id).iter().find_map(|node| match node {
+            ENode::Monomial(m) => Some(m.clone()),
+            _ => None,
+        })
+    }
+
+    /// Adds the expression `pattern` describes, its wildcards standing for classes.
+    pub fn instantiate(&mut self, pattern: &Pattern, bindings: &HashMap<String, Id>) -> Option<Id> {
+        Some(match pattern {
+            Pattern::Wildcard(name) | Pattern::ConstantWildcard(name) => *bindings.get(name)?,
+            Pattern::Exact(expression) => self.add_expression(expression),
+            Pattern::BinaryOperation {
+                operation,
+                left,
+                right,
+            } => {
+                let left = self.instantiate(left, bindings)?;
+                let right = self.instantiate(right, bindings)?;
+                self.add(ENode::BinaryOperation(*operation, left, right))
+            }
+            Pattern::Function { function, argument } => {
+                let argument = self.instantiate(argument, bindings)?;
+                self.add(ENode::Function(*function, argument))
+            }
+        })
+    }
+
+    /// Adds the exact value of operations on constants and monomials to their class,
+    /// returns whether a class changed.
+    fn fold(&mut self) -> bool {
+        let mut folded = vec![];
+        for (id, nodes) in &self.classes {
+            for node in nodes {
+                let ENode::BinaryOperation(operation, left, right) = node else {
+                    continue;
+                };
+                let value = match (
+                    self.constant(*left),
+                    self.constant(*right),
+                    self.monomial(*left),
+                    self.monomial(*right),
+                ) {
+                    (Some(l), Some(r), _, _) => {
+                        fold_constants(*operation, &l, &r).map(ENode::Constant)
+                    }
+                    (Some(c), _, _, Some(m)) | (_, Some(c), Some(m), _)
+                        if *operation == BinaryOperationType::Multiplication =>
+                    {
+                        m.factor
+                            .checked_mul(&c)
+                            .map(|factor| ENode::Monomial(Monomial { factor, ..m }))
+                    }
+                    (_, Some(exponent), Some(m), _) if *operation == BinaryOperationType::Power => {
+                        monomial_power(&m, &exponent).map(ENode::Monomial)
+                    }
+                    (_, _, Some(l), Some(r))
+                        if *operation == BinaryOperationType::Addition
+                            && l.variable == r.variable
+                            && l.power == r.power =>
+                    {
+                        l.factor.checked_add(&r.factor).map(|factor| {
+                            if factor.is_zero() {
+                                ENode::Constant(factor)
+                            } else {
+                                ENode::Monomial(Monomial { factor, ..l })
+                            }
+                        })
+                    }
+                    _ => None,
+                };
+                if let Some(value) = value {
+                    folded.push((*id, value));
+                }
+            }
+        }
+        let mut changed = false;
+        for (id, value) in folded {
+            let value = self.add(value);
+            changed |= self.union(id, value);
+        }
+        changed
+    }
+
+    /// Cheapest node of every class under `cost_function`.
+    fn best_nodes(&self, cost_function: CostFunction) -> HashMap<Id, (f64, ENode)> {
+        let mut best: HashMap<Id, (f64, ENode)> = HashMap::new();
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (id, nodes) in &self.classes {
+                for node in nodes {
+                    let children: Option<Vec<f64>> = node
+                        .children()
+                        .iter()
+                        .map(|child| best.get(&self.find(*child)).map(|(cost, _)| *cost))
+                        .collect();
+                    let Some(children) = children else {
+                        continue;
+                    };
+                    let cost = cost_function.node_cost(node, &children);
+                    if best.get(id).is_none_or(|(best_cost, _)| cost < *best_cost) {
+                        best.insert(*id, (cost, node.clone()));
+                        changed = true;
+                    }
+                }
+            }
+        }
+        best
+    }
+
+    /// Cheapest expression of the class of `id` and its cost.
+    pub fn extract(&self, id: Id, cost_function: CostFunction) -> (Expression, f64) {
+        let best = self.best_nodes(cost_function);
+        let root = self.find(id);
+        (self.build(root, &best), best[&root].0)
+    }
+
+    fn build(&self, id: Id, best: &HashMap<Id, (f64, ENode)>) -> Expression {
+        match &best[&self.find(id)].1 {
+            ENode::Constant(value) => Expression::Constant(*value),
+            ENode::NamedConstant(name) => Expression::NamedConstant(name.clone()),
+            ENode::Monomial(m) => Expression::Monomial(m.clone()),
+            ENode::BinaryOperation(operation, left, right) => {
+                Expression::BinaryOperation(BinaryOperation {
+                    operation: *operation,
+                    left_value: Box::new(self.build(*left, best)),
+                    right_value: Box::new(self.build(*right, best)),
+                })
+            }
+            ENode::Function(function, argument) => Expression::Function {
+                function: *function,
+                expression: Box::new(self.build(*argument, best)),
+            },
+        }
+    }
+}
+
+/// What extraction minimizes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CostFunction {
+    NodeCount,
+    Depth,
+    /// Rough number of floating point operations to evaluate the expression.
+    EvaluationCost,
+}
+
+impl CostFunction {
+    pub fn node_cost(&self, node: &ENode, children: &[f64]) -> f64 {
+        match self {
+            CostFunction::NodeCount => 1. + children.iter().sum::<f64>(),
+            CostFunction::Depth => 1. + children.iter().copied().fold(0., f64::max),
+            CostFunction::EvaluationCost => {
+                let own = match node {
+                    ENode::Constant(_) | ENode::NamedConstant(_) => 0.,
+                    ENode::Monomial(m) => {
+                        let scaling = if m.factor == 1.into() { 0. } else { 1. };
+                        1. + f64::from(m.power.saturating_sub(1)) + scaling
+                    }
+                    ENode::BinaryOperation(operation, _, _) => match operation {
+                        BinaryOperationType::Addition
+                        | BinaryOperationType::Substraction
+                        | BinaryOperationType::Multiplication => 1.,
+                        BinaryOperationType::Division => 4.,
+                        BinaryOperationType::Power => 8.,
+                    },
+                    ENode::Function(..) => 16.,
+                };
+                own + children.iter().sum::<f64>()
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopReason {
+    /// No rule application changed the graph, the extracted expression is optimal for the
+    /// rules and the cost function.
+    Saturated,
+    IterationLimit,
+    NodeLimit,
+}
+
+/// Result of `EGraphSimplifier::run`.
+#[derive(Debug, Clone)]
+pub struct Saturation {
+    pub expression: Expression,
+    pub cost: f64,
+    pub iterations: usize,
+    pub node_count: usize,
+    pub stop_reason: StopReason,
+}
+
+/// Simplifier that applies every rule to every equivalent form at once, then extracts the
+/// cheapest form. Rules that undo each other only add nodes, so the run always ends.
+#[derive(Debug, Clone)]
+pub struct EGraphSimplifier {
+    pub rule_sets: Vec<RuleSet>,
+    pub iteration_limit: usize,
+    pub node_limit: usize,
+    pub cost_function: CostFunction,
+}
+
+impl EGraphSimplifier {
+    /// Uses `rule_sets` together with the identities of addition, multiplication and power.
+    pub fn new(rule_sets: Vec<RuleSet>) -> EGraphSimplifier {
+        EGraphSimplifier {
+            rule_sets,
+            iteration_limit: 30,
+            node_limit: 10_000,
+            cost_function: CostFunction::NodeCount,
+        }
+    }
+
+    pub fn with_iteration_limit(self, iteration_limit: usize) -> EGraphSimplifier {
+        EGraphSimplifier {
+            iteration_limit,
+            ..self
+        }
+    }
+
+    pub fn with_node_limit(self, node_limit: usize) -> EGraphSimplifier {
+        EGraphSimplifier { node_limit, ..self }
+    }
+
+    pub fn with_cost_function(self, cost_function: CostFunction) -> EGraphSimplifier {
+        EGraphSimplifier {
+            cost_function,
+            ..self
+        }
+    }
+
+    pub fn simplify(&self, expression: &Expression) -> Expression {
+        self.run(expression).expression
+    }
+
+    pub fn run(&self, expression: &Expression) -> Saturation {
+        let identities = identities();
+        let rules: Vec<&RewriteRule> = self
+            .rule_sets
+            .iter()
+            .chain([&identities])
+            .flat_map(|rule_set| &rule_set.rules)
+            .collect();
+
+        let mut egraph = EGraph::new();
+        let root = egraph.add_expression(expression);
+        egraph.rebuild();
+        let mut iterations = 0;
+        let stop_reason = loop {
+            if iterations == self.iteration_limit {
+                break StopReason::IterationLimit;
+            }
+            iterations += 1;
+            let node_count = egraph.node_count();
+            let representatives = egraph.best_nodes(CostFunction::NodeCount);
+
+            let mut matches = vec![];
+            for id in egraph.classes.keys() {
+                for rule in &rules {
+                    for bindings in egraph.ematch(&rule.pattern, *id, &HashMap::new()) {
+                        let accepted = rule.condition.as_ref().is_none_or(|condition| {
+                            condition(&egraph.expression_bindings(&bindings, &representatives))
+                        });
+                        if accepted {
+                            matches.push((*id, *rule, bindings));
+                        }
+                    }
+                }
+            }
+
+            let mut changed = false;
+            for (id, rule, bindings) in matches {
+                if let Some(rewritten) = egraph.instantiate(&rule.replacement, &bindings) {
+                    changed |= egraph.union(id, rewritten);
+                }
+                if egraph.node_count() > self.node_limit {
+                    break;
+                }
+            }
+            changed |= egraph.fold();
+            egraph.rebuild();
+            if egraph.node_count() > self.node_limit {
+                break StopReason::NodeLimit;
+            }
+            if !changed && egraph.node_count() == node_count {
+                break StopReason::Saturated;
+            }
+        };
+
+        let (expression, cost) = egraph.extract(root, self.cost_function);
+        Saturation {
+            expression,
+            cost,
+            iterations,
+            node_count: egraph.node_count(),
+            stop_reason,
+        }
+    }
+}
+
+impl EGraph {
+    fn expression_bindings(
+        &self,
+        bindings: &HashMap<String, Id>,
+        representatives: &HashMap<Id, (f64, ENode)>,
+    ) -> Bindings {
+        bindings
+            .iter()
+            .map(|(name, id)| {
+                let expression = match self.constant(*id) {
+                    Some(value) => Expression::Constant(value),
+                    None => self.build(*id, representatives),
+                };
+                (name.clone(), expression)
+            })
+            .collect()
+    }
+}
+
+/// Neutral and absorbing elements and associativity, which every e-graph run relies on.
+fn identities() -> RuleSet {
+    use BinaryOperationType::*;
+    let x = || Pattern::wildcard("x");
+    let y = || Pattern::wildcard("y");
+    let z = || Pattern::wildcard("z");
+    let operation = Pattern::operation;
+    let mut rules = vec![
+        RewriteRule::new(
+            "add-zero",
+            operation(Addition, x(), Pattern::integer(0)),
+            x(),
+        ),
+        RewriteRule::new(
+            "substract-zero",
+            operation(Substraction, x(), Pattern::integer(0)),
+            x(),
+        ),
+        RewriteRule::new(
+            "multiply-one",
+            operation(Multiplication, x(), Pattern::integer(1)),
+            x(),
+        ),
+        RewriteRule::new(
+            "multiply-zero",
+            operation(Multiplication, x(), Pattern::integer(0)),
+            Pattern::integer(0),
+        ),
+        RewriteRule::new(
+            "divide-one",
+            operation(Division, x(), Pattern::integer(1)),
+            x(),
+        ),
+        RewriteRule::new("power-one", operation(Power, x(), Pattern::integer(1)), x()),
+        RewriteRule::new(
+            "power-zero",
+            operation(Power, x(), Pattern::integer(0)),
+            Pattern::integer(1),
+        ),
+    ];
+    for (name, associative) in [("add", Addition), ("multiply", Multiplication)] {
+        rules.push(RewriteRule::new(
+            &format!("{name}-associativity"),
+            operation(associative, operation(associative, x(), y()), z()),
+            operation(associative, x(), operation(associative, y(), z())),
+        ));
+    }
+    RuleSet::new("identities", rules)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::canonical::Canonical;
+
+    fn variable(variable: char) -> Expression {
+        Expression::monomial(1, variable, 1)
+    }
+
+    fn square(expression: Expression) -> Expression {
+        Expression::binary(
+            BinaryOperationType::Power,
+            expression,
+            Expression::Constant(2.into()),
+        )
+    }
+
+    #[test]
+    fn test_congruence_closure() {
+        let mut egraph = EGraph::new();
+        let x = egraph.add_expression(&variable('x'));
+        let y = egraph.add_expression(&variable('y'));
+        let sin_x = egraph.add_expression(&Expression::function(Function::Sine, variable('x')));
+        let sin_y = egraph.add_expression(&Expression::function(Function::Sine, variable('y')));
+        assert_ne!(egraph.find(sin_x), egraph.find(sin_y));
+        egraph.union(x, y);
+        egraph.rebuild();
+        assert_eq!(egraph.find(sin_x), egraph.find(sin_y));
+    }
+
+    #[test]
+    fn test_simplify_pythagorean_identity_in_a_sum() {
+        // y + sin(x)^2 + cos(x)^2
+        let expr = Expression::Sum(vec![
+            variable('y'),
+            square(Expression::function(Function::Sine, variable('x'))),
+            square(Expression::function(Function::Cosine, variable('x'))),
+        ]);
+        let saturation = EGraphSimplifier::new(vec![RuleSet::trigonometric()]).run(&expr);
+        assert_eq!(saturation.cost, 3.);
+        assert_eq!(
+            saturation.expression.to_canonical_form(),
+            Expression::binary(
+                BinaryOperationType::Addition,
+                variable('y'),
+                Expression::Constant(1.into())
+            )
+            .to_canonical_form()
+        );
+    }
+
+    #[test]
+    fn test_rules_undoing_each_other_terminate() {
+        let swap = RewriteRule::new(
+            "swap",
+            Pattern::operation(
+                BinaryOperationType::Multiplication,
+                Pattern::wildcard("a"),
+                Pattern::function(Function::Exponential, Pattern::wildcard("b")),
+            ),
+            Pattern::operation(
+                BinaryOperationType::Multiplication,
+                Pattern::function(Function::Exponential, Pattern::wildcard("b")),
+                Pattern::wildcard("a"),
+            ),
+        );
+        let expr = Expression::binary(
+            BinaryOperationType::Multiplication,
+            variable('x'),
+            Expression::function(Function::Exponential, variable('y')),
+        );
+        let saturation = EGraphSimplifier::new(vec![RuleSet::new("swap", vec![swap])]).run(&expr);
+        assert_eq!(saturation.stop_reason, StopReason::Saturated);
+        assert_eq!(saturation.cost, 4.);
+    }
+
+    #[test]
+    fn test_limits() {
+        let expr = Expression::Sum(('a'..='h').map(variable).collect());
+        let saturation = EGraphSimplifier::new(vec![]).with_node_limit(50).run(&expr);
+        assert_eq!(saturation.stop_reason, StopReason::NodeLimit);
+        let saturation = EGraphSimplifier::new(vec![])
+            .with_iteration_limit(1)
+            .run(&expr);
+        assert_eq!(saturation.stop_reason, StopReason::IterationLimit);
+        assert_eq!(saturation.iterations, 1);
+    }
+
+    #[test]
+    fn test_cost_functions() {
+        // ((a + b) + c) + d
+        let expr = Expression::Sum(('a'..='d').map(variable).collect());
+        let saturation = EGraphSimplifier::new(vec![])
+            .with_cost_function(CostFunction::Depth)
+            .run(&expr);
+        assert_eq!(saturation.cost, 3.);
+
+        // (a + b)^2 -> (a + b) * (a + b)
+        let unfold = RewriteRule::new(
+            "unfold-square",
+            Pattern::operation(
+                BinaryOperationType::Power,
+                Pattern::wildcard("x"),
+                Pattern::integer(2),
+            ),
+            Pattern::operation(
+                BinaryOperationType::Multiplication,
+                Pattern::wildcard("x"),
+                Pattern::wildcard("x"),
+            ),
+        );
+        let sum = Expression::binary(BinaryOperationType::Addition, variable('a'), variable('b'));
+        let expr = square(sum.clone());
+        let simplifier = EGraphSimplifier::new(vec![RuleSet::new("unfold", vec![unfold])]);
+        assert_eq!(simplifier.simplify(&expr), expr);
+        assert_eq!(
+            simplifier
+                .with_cost_function(CostFunction::EvaluationCost)
+                .simplify(&expr),
+            Expression::binary(BinaryOperationType::Multiplication, sum.clone(), sum)
+        );
+    }
+
+    #[test]
+    fn test_constant_folding_overflow() {
+        let huge = Expression::Monomial(Monomial {
+            factor: i64::MAX.into(),
+            variable: 'x',
+            power: 1,
+        });
+        let expr = Expression::Sum(vec![
+            Expression::binary(
+                BinaryOperationType::Multiplication,
+                Expression::Constant(2.into()),
+                huge.clone(),
+            ),
+            huge,
+        ]);
+        // 3 * i64::MAX does not fit in a monomial factor, so the constant stays apart.
+        assert_eq!(
+            EGraphSimplifier::new(vec![RuleSet::polynomial()]).simplify(&expr),
+            Expression::binary(
+                BinaryOperationType::Multiplication,
+                Expression::Constant(i64::MAX.into()),
+                Expression::monomial(3, 'x', 1),
+            )
+        );
+    }
+}
@@ -0,0 +1,228 @@
+use std::collections::HashMap;
+
+use ndarray::{ArrayD, ArrayViewD, IxDyn};
+use num_traits::ToPrimitive;
+
+use crate::compilable::Compilable;
+use crate::expression::{BinaryOperationType, Expression, Function, Rational, Variable};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EvaluationError {
+    UnboundVariable(Variable),
+    UnknownNamedConstant(String),
+    /// Shapes of variable arrays that cannot be broadcast together.
+    IncompatibleShapes(Vec<usize>, Vec<usize>),
+}
+
+/// Numerical evaluation of an expression, the only place where exact constants become floats.
+pub trait Evaluable {
+    fn evaluate(&self, values: &HashMap<Variable, f64>) -> Result<f64, EvaluationError>;
+
+    /// Element-wise evaluation over arrays of values, broadcast together as in NumPy: shapes
+    /// are aligned on their last axis and axes of length 1 are repeated.
+    fn evaluate_array(
+        &self,
+        values: &HashMap<Variable, ArrayViewD<f64>>,
+    ) -> Result<ArrayD<f64>, EvaluationError>;
+}
+
+impl Evaluable for Expression {
+    fn evaluate(&self, values: &HashMap<Variable, f64>) -> Result<f64, EvaluationError> {
+        match self {
+            Expression::Constant(value) => Ok(rational_to_f64(value)),
+            Expression::NamedConstant(name) => evaluate_named_constant(name),
+            Expression::Monomial(m) => {
+                let value = evaluate_variable(m.variable, values)?;
+                Ok(rational_to_f64(&m.factor) * value.powi(m.power as i32))
+            }
+            Expression::BinaryOperation(operation) => {
+                let left = operation.left_value.evaluate(values)?;
+                let right = operation.right_value.evaluate(values)?;
+                Ok(match operation.operation {
+                    BinaryOperationType::Addition => left + right,
+                    BinaryOperationType::Substraction => left - right,
+                    BinaryOperationType::Multiplication => left * right,
+                    BinaryOperationType::Division => left / right,
+                    BinaryOperationType::Power => left.powf(right),
+                })
+            }
+            Expression::Function {
+                function,
+                expression,
+            } => {
+                let value = expression.evaluate(values)?;
+                Ok(match function {
+                    Function::Sine => value.sin(),
+                    Function::Cosine => value.cos(),
+                    Function::Exponential => value.exp(),
+                    Function::Logarithm => value.ln(),
+                    Function::AbsoluteValue => value.abs(),
+                })
+            }
+            Expression::Sum(terms) => terms.iter().map(|term| term.evaluate(values)).sum(),
+            Expression::Product(factors) => factors
+                .iter()
+                .map(|factor| factor.evaluate(values))
+                .product(),
+        }
+    }
+
+    fn evaluate_array(
+        &self,
+        values: &HashMap<Variable, ArrayViewD<f64>>,
+    ) -> Result<ArrayD<f64>, EvaluationError> {
+        let mut variables: Vec<Variable> = values.keys().copied().collect();
+        variables.sort_unstable();
+        let shape = variables.iter().try_fold(vec![], |shape, variable| {
+            broadcast_shape(&shape, values[variable].shape())
+        })?;
+        let compiled = self.compile(&variables)?;
+        let arrays: Vec<_> = variables
+            .iter()
+            .map(|variable| {
+                values[variable]
+                    .broadcast(IxDyn(&shape))
+                    .expect("shape is compatible with every array")
+            })
+            .collect();
+        let mut iterators: Vec<_> = arrays.iter().map(|array| array.iter()).collect();
+        let mut arguments = vec![0.; variables.len()];
+        let mut registers = vec![];
+        let size = shape.iter().product();
+        let mut results = Vec::with_capacity(size);
+        for _ in 0..size {
+            for (argument, iterator) in arguments.iter_mut().zip(&mut iterators) {
+                *argument = *iterator.next().expect("arrays have the broadcast size");
+            }
+            results.push(compiled.evaluate_with(&arguments, &mut registers));
+        }
+        Ok(ArrayD::from_shape_vec(shape, results).expect("one result per element"))
+    }
+}
+
+fn broadcast_shape(left: &[usize], right: &[usize]) -> Result<Vec<usize>, EvaluationError> {
+    let length = left.len().max(right.len());
+    let axis = |shape: &[usize], index: usize| {
+        (index + shape.len())
+            .checked_sub(length)
+            .map_or(1, |index| shape[index])
+    };
+    (0..length)
+        .map(|index| match (axis(left, index), axis(right, index)) {
+            (l, r) if l == r || r == 1 => Ok(l),
+            (1, r) => Ok(r),
+            _ => Err(EvaluationError::IncompatibleShapes(
+                left.to_vec(),
+                right.to_vec(),
+            )),
+        })
+        .collect()
+}
+
+pub fn rational_to_f64(value: &Rational) -> f64 {
+    value.to_f64().unwrap_or(f64::NAN)
+}
+
+pub(crate) fn evaluate_variable(
+    variable: Variable,
+    values: &HashMap<Variable, f64>,
+) -> Result<f64, EvaluationError> {
+    values
+        .get(&variable)
+        .copied()
+        .ok_or(EvaluationError::UnboundVariable(variable))
+}
+
+pub(crate) fn evaluate_named_constant(name: &str) -> Result<f64, EvaluationError> {
+    match name {
+        "pi" => Ok(std::f64::consts::PI),
+        "e" => Ok(std::f64::consts::E),
+        _ => Err(EvaluationError::UnknownNamedConstant(name.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ndarray::{arr1, arr2, Array};
+
+    use crate::expression::{BinaryOperation, Monomial};
+
+    #[test]
+    fn test_evaluate_exact_constants() {
+        assert_eq!(
+            Expression::Constant(Rational::new(1, 4)).evaluate(&HashMap::new()),
+            Ok(0.25)
+        );
+        assert_eq!(
+            Expression::NamedConstant("pi".to_string()).evaluate(&HashMap::new()),
+            Ok(std::f64::consts::PI)
+        );
+        assert_eq!(
+            Expression::NamedConstant("tau".to_string()).evaluate(&HashMap::new()),
+            Err(EvaluationError::UnknownNamedConstant("tau".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_evaluate_expression() {
+        // sin(x) + 3/2 y^2
+        let expr = Expression::BinaryOperation(BinaryOperation {
+            operation: BinaryOperationType::Addition,
+            left_value: Box::new(Expression::Function {
+                function: Function::Sine,
+                expression: Box::new(Expression::Monomial(Monomial {
+                    factor: 1.into(),
+                    variable: 'x',
+                    power: 1,
+                })),
+            }),
+            right_value: Box::new(Expression::Monomial(Monomial {
+                factor: Rational::new(3, 2),
+                variable: 'y',
+                power: 2,
+            })),
+        });
+        let values = HashMap::from([('x', std::f64::consts::FRAC_PI_2), ('y', 2.)]);
+        assert_eq!(expr.evaluate(&values), Ok(7.));
+        assert_eq!(
+            expr.evaluate(&HashMap::from([('x', 0.)])),
+            Err(EvaluationError::UnboundVariable('y'))
+        );
+    }
+
+    #[test]
+    fn test_evaluate_array_broadcasts() {
+        // u v + 1 over a meshgrid given as a column and a row
+        let expression = Expression::Sum(vec![
+            Expression::Product(vec![
+                Expression::Monomial(Monomial {
+                    factor: 1.into(),
+                    variable: 'u',
+                    power: 1,
+                }),
+                Expression::Monomial(Monomial {
+                    factor: 1.into(),
+                    variable: 'v',
+                    power: 1,
+                }),
+            ]),
+            Expression::Constant(1.into()),
+        ]);
+        let u = Array::linspace(0., 2., 3).into_shape((3, 1)).unwrap();
+        let v = arr1(&[1., 10.]);
+        let values = HashMap::from([('u', u.view().into_dyn()), ('v', v.view().into_dyn())]);
+        assert_eq!(
+            expression.evaluate_array(&values),
+            Ok(arr2(&[[1., 1.], [2., 11.], [3., 21.]]).into_dyn())
+        );
+
+        let w = arr1(&[1., 2., 3.]);
+        let values = HashMap::from([('u', v.view().into_dyn()), ('v', w.view().into_dyn())]);
+        assert_eq!(
+            expression.evaluate_array(&values),
+            Err(EvaluationError::IncompatibleShapes(vec![2], vec![3]))
+        );
+    }
+}
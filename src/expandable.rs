@@ -0,0 +1,857 @@
+use std::collections::BTreeMap;
+
+use num_traits::{CheckedAdd, CheckedMul, One, Signed, Zero};
+
+use crate::expression::{BinaryOperationType, Expression, Monomial, Rational, Variable};
+use crate::polynomial::{term_to_expression, Polynomial, PowerProduct};
+use crate::simplifiable::{fold_constants, Simplifiable};
+
+pub trait Expandable {
+    /// Distributes products over sums, expands natural powers and collects like terms.
+    fn expand(&self) -> Self;
+    /// Expands the expression and groups its terms by powers of `variable`.
+    fn collect(&self, variable: Variable) -> Self;
+    /// Combines a sum of fractions over a common denominator.
+    fn together(&self) -> Self;
+    /// Puts the expression over a common denominator and cancels the polynomial gcd.
+    fn cancel(&self) -> Self;
+}
+
+impl Expandable for Expression {
+    fn expand(&self) -> Expression {
+        match expand_terms(self) {
+            Some(terms) => terms_to_expression(&terms),
+            None => self.clone(),
+        }
+    }
+
+    fn collect(&self, variable: Variable) -> Expression {
+        let Some(terms) = expand_terms(self) else {
+            return self.clone();
+        };
+        let mut groups = BTreeMap::<u32, Vec<Term>>::new();
+        for term in terms {
+            let power = term.power_product.power_of(variable);
+            groups.entry(power).or_default().push(Term {
+                power_product: term.power_product.without(variable),
+                ..term
+            });
+        }
+        groups
+            .iter()
+            .rev()
+            .map(|(power, terms)| {
+                let coefficient = terms_to_expression(terms);
+                match (*power, coefficient) {
+                    (0, coefficient) => coefficient,
+                    (power, Expression::Constant(factor)) => {
+                        variable_power(factor, variable, power)
+                    }
+                    (power, coefficient) => Expression::binary(
+                        BinaryOperationType::Multiplication,
+                        coefficient,
+                        variable_power(Rational::one(), variable, power),
+                    ),
+                }
+            })
+            .reduce(|acc, term| Expression::binary(BinaryOperationType::Addition, acc, term))
+            .unwrap_or(Expression::Constant(0.into()))
+    }
+
+    fn together(&self) -> Expression {
+        let (numerator, denominator) = numerator_and_denominator(self);
+        fraction(
+            numerator.simplify_expression(),
+            denominator.simplify_expression(),
+        )
+    }
+
+    fn cancel(&self) -> Expression {
+        let (numerator, denominator) = numerator_and_denominator(self);
+        match (
+            Polynomial::try_from(&numerator),
+            Polynomial::try_from(&denominator),
+        ) {
+            (Ok(numerator), Ok(denominator)) if !denominator.is_zero() => {
+                cancel_polynomials(&numerator, &denominator).unwrap_or_else(|| self.together())
+            }
+            _ => self.together(),
+        }
+    }
+}
+
+/// `factor * variable^power`, kept as a power node when `power` does not fit in a monomial.
+fn variable_power(factor: Rational, variable: Variable, power: u32) -> Expression {
+    match u8::try_from(power) {
+        Ok(power) => Expression::Monomial(Monomial {
+            factor,
+            variable,
+            power,
+        }),
+        Err(_) => {
+            let power = Expression::binary(
+                BinaryOperationType::Power,
+                Expression::Monomial(Monomial {
+                    factor: Rational::one(),
+                    variable,
+                    power: 1,
+                }),
+                Expression::Constant((power as i64).into()),
+            );
+            if factor.is_one() {
+                power
+            } else {
+                Expression::binary(
+                    BinaryOperationType::Multiplication,
+                    Expression::Constant(factor),
+                    power,
+                )
+            }
+        }
+    }
+}
+
+/// `numerator / denominator` divided by their gcd, `None` when a coefficient overflows.
+fn cancel_polynomials(numerator: &Polynomial, denominator: &Polynomial) -> Option<Expression> {
+    let gcd = numerator.gcd(denominator)?;
+    let numerator = numerator.checked_div_rem(&gcd)?.0;
+    let denominator = denominator.checked_div_rem(&gcd)?.0;
+    Some(if denominator.is_constant() {
+        numerator
+            .checked_mul(&Polynomial::constant(denominator.constant_term().recip()))?
+            .to_expression()
+    } else {
+        fraction(numerator.to_expression(), denominator.to_expression())
+    })
+}
+
+/// Product of a rational coefficient, a power product of variables and of non-polynomial
+/// factors with their multiplicities, the factors of negative multiplicity forming the
+/// denominator.
+#[derive(Debug, Clone)]
+struct Term {
+    coefficient: Rational,
+    power_product: PowerProduct,
+    factors: Vec<(Expression, i32)>,
+}
+
+impl Term {
+    fn constant(coefficient: Rational) -> Term {
+        Term {
+            coefficient,
+            power_product: PowerProduct::one(),
+            factors: vec![],
+        }
+    }
+
+    fn numerator(expression: Expression) -> Term {
+        Term {
+            factors: vec![(expression, 1)],
+            ..Term::constant(Rational::one())
+        }
+    }
+
+    fn denominator(expression: Expression) -> Term {
+        Term {
+            factors: vec![(expression, -1)],
+            ..Term::constant(Rational::one())
+        }
+    }
+
+    /// `None` when the coefficients or the powers overflow.
+    fn multiply(&self, other: &Term) -> Option<Term> {
+        let mut factors = self.factors.clone();
+        for (factor, multiplicity) in &other.factors {
+            match factors.iter().position(|(f, _)| f == factor) {
+                Some(index) => {
+                    factors[index].1 = factors[index].1.checked_add(*multiplicity)?;
+                    if factors[index].1 == 0 {
+                        factors.remove(index);
+                    }
+                }
+                None => factors.push((factor.clone(), *multiplicity)),
+            }
+        }
+        Some(Term {
+            coefficient: self.coefficient.checked_mul(&other.coefficient)?,
+            power_product: self.power_product.checked_mul(&other.power_product)?,
+            factors,
+        })
+    }
+
+    /// `self^exponent` for a positive `exponent`, `None` when the coefficient or the powers
+    /// overflow.
+    fn pow(&self, exponent: u32) -> Option<Term> {
+        let multiplicity = i32::try_from(exponent).ok()?;
+        Some(Term {
+            coefficient: fold_constants(
+                BinaryOperationType::Power,
+                &self.coefficient,
+                &Rational::from(i64::from(exponent)),
+            )?,
+            power_product: self.power_product.checked_pow(exponent)?,
+            factors: self
+                .factors
+                .iter()
+                .map(|(factor, m)| Some((factor.clone(), (*m).checked_mul(multiplicity)?)))
+                .collect::<Option<_>>()?,
+        })
+    }
+
+    fn is_like(&self, other: &Term) -> bool {
+        self.power_product == other.power_product
+            && self.factors.len() == other.factors.len()
+            && self
+                .factors
+                .iter()
+                .all(|factor| other.factors.contains(factor))
+    }
+
+    fn to_expression(&self) -> Expression {
+        let power = |factor: &Expression, multiplicity: i64| {
+            if multiplicity == 1 {
+                factor.clone()
+            } else {
+                Expression::binary(
+                    BinaryOperationType::Power,
+                    factor.clone(),
+                    Expression::Constant(multiplicity.into()),
+                )
+            }
+        };
+        let numerators: Vec<Expression> = self
+            .factors
+            .iter()
+            .filter(|(_, multiplicity)| *multiplicity > 0)
+            .map(|(factor, multiplicity)| power(factor, i64::from(*multiplicity)))
+            .collect();
+        let denominators: Vec<Expression> = self
+            .factors
+            .iter()
+            .filter(|(_, multiplicity)| *multiplicity < 0)
+            .map(|(factor, multiplicity)| power(factor, -i64::from(*multiplicity)))
+            .collect();
+        let numerator = if numerators.is_empty()
+            || !self.power_product.is_one()
+            || !self.coefficient.is_one()
+        {
+            let polynomial_part = term_to_expression(self.coefficient, &self.power_product);
+            numerators.into_iter().fold(polynomial_part, |acc, factor| {
+                Expression::binary(BinaryOperationType::Multiplication, acc, factor)
+            })
+        } else {
+            product(numerators).unwrap()
+        };
+        match product(denominators) {
+            Some(denominator) => fraction(numerator, denominator),
+            None => numerator,
+        }
+    }
+}
+
+pub(crate) fn same_factors(left: &[Expression], right: &[Expression]) -> bool {
+    let mut remaining = right.to_vec();
+    left.len() == right.len()
+        && left
+            .iter()
+            .all(|factor| match remaining.iter().position(|r| r == factor) {
+                Some(index) => {
+                    remaining.remove(index);
+                    true
+                }
+                None => false,
+            })
+}
+
+/// Gathers repeated factors into powers, keeping the order of first appearance.
+pub(crate) fn group_factors(factors: &[Expression]) -> Vec<Expression> {
+    let mut grouped: Vec<(Expression, i64)> = vec![];
+    for factor in factors {
+        match grouped.iter_mut().find(|(f, _)| f == factor) {
+            Some((_, count)) => *count += 1,
+            None => grouped.push((factor.clone(), 1)),
+        }
+    }
+    grouped
+        .into_iter()
+        .map(|(factor, count)| {
+            if count == 1 {
+                factor
+            } else {
+                Expression::binary(
+                    BinaryOperationType::Power,
+                    factor,
+                    Expression::Constant(count.into()),
+                )
+            }
+        })
+        .collect()
+}
+
+fn product(factors: Vec<Expression>) -> Option<Expression> {
+    factors
+        .into_iter()
+        .reduce(|acc, factor| Expression::binary(BinaryOperationType::Multiplication, acc, factor))
+}
+
+// The term arithmetic below returns `None` as soon as a coefficient overflows, in which case
+// the expression is left unexpanded.
+
+fn add_term(terms: &mut Vec<Term>, term: Term) -> Option<()> {
+    if term.coefficient.is_zero() {
+        return Some(());
+    }
+    match terms.iter().position(|t| t.is_like(&term)) {
+        Some(index) => {
+            terms[index].coefficient = terms[index].coefficient.checked_add(&term.coefficient)?;
+            if terms[index].coefficient.is_zero() {
+                terms.remove(index);
+            }
+        }
+        None => terms.push(term),
+    }
+    Some(())
+}
+
+fn multiply_terms(left: &[Term], right: &[Term]) -> Option<Vec<Term>> {
+    let mut terms = vec![];
+    for l in left {
+        for r in right {
+            add_term(&mut terms, l.multiply(r)?)?;
+        }
+    }
+    Some(terms)
+}
+
+/// `terms^exponent` by repeated squaring, a single term being raised directly.
+fn power_terms(terms: &[Term], mut exponent: u32) -> Option<Vec<Term>> {
+    match terms {
+        _ if exponent == 0 => return Some(vec![Term::constant(Rational::one())]),
+        [term] => return Some(vec![term.pow(exponent)?]),
+        _ => {}
+    }
+    let (mut power, mut square) = (vec![Term::constant(Rational::one())], terms.to_vec());
+    loop {
+        if exponent % 2 == 1 {
+            power = multiply_terms(&power, &square)?;
+        }
+        exponent /= 2;
+        if exponent == 0 {
+            return Some(power);
+        }
+        square = multiply_terms(&square, &square)?;
+    }
+}
+
+fn scale_terms(terms: Vec<Term>, factor: Rational) -> Option<Vec<Term>> {
+    let mut scaled = vec![];
+    for term in terms {
+        let coefficient = term.coefficient.checked_mul(&factor)?;
+        if !coefficient.is_zero() {
+            scaled.push(Term {
+                coefficient,
+                ..term
+            });
+        }
+    }
+    Some(scaled)
+}
+
+fn natural_exponent(expression: &Expression) -> Option<u32> {
+    match expression {
+        Expression::Constant(value) if value.is_integer() && !value.is_negative() => {
+            u32::try_from(value.to_integer()).ok()
+        }
+        _ => None,
+    }
+}
+
+fn expand_terms(expression: &Expression) -> Option<Vec<Term>> {
+    Some(match expression {
+        Expression::Constant(value) => scale_terms(vec![Term::constant(Rational::one())], *value)?,
+        Expression::Monomial(m) => scale_terms(
+            vec![Term {
+                power_product: PowerProduct::variable(m.variable, m.power as u32),
+                ..Term::constant(Rational::one())
+            }],
+            m.factor,
+        )?,
+        Expression::NamedConstant(_) => vec![Term::numerator(expression.clone())],
+        Expression::Sum(operands) => {
+            let mut terms = vec![];
+            for operand in operands {
+                for term in expand_terms(operand)? {
+                    add_term(&mut terms, term)?;
+                }
+            }
+            terms
+        }
+        Expression::Product(operands) => operands
+            .iter()
+            .try_fold(vec![Term::constant(Rational::one())], |acc, factor| {
+                multiply_terms(&acc, &expand_terms(factor)?)
+            })?,
+        Expression::Function {
+            function,
+            expression,
+        } => vec![Term::numerator(Expression::Function {
+            function: *function,
+            expression: Box::new(expression.expand()),
+        })],
+        Expression::BinaryOperation(op) => {
+            let left = expand_terms(&op.left_value)?;
+            match op.operation {
+                BinaryOperationType::Addition | BinaryOperationType::Substraction => {
+                    let sign = if op.operation == BinaryOperationType::Addition {
+                        Rational::one()
+                    } else {
+                        -Rational::one()
+                    };
+                    let mut terms = left;
+                    for term in scale_terms(expand_terms(&op.right_value)?, sign)? {
+                        add_term(&mut terms, term)?;
+                    }
+                    terms
+                }
+                BinaryOperationType::Multiplication => {
+                    multiply_terms(&left, &expand_terms(&op.right_value)?)?
+                }
+                BinaryOperationType::Division => {
+                    let denominator = op.right_value.expand();
+                    match denominator {
+                        Expression::Constant(value) if !value.is_zero() => {
+                            scale_terms(left, value.recip())?
+                        }
+                        denominator => multiply_terms(&left, &[Term::denominator(denominator)])?,
+                    }
+                }
+                BinaryOperationType::Power => match natural_exponent(&op.right_value) {
+                    Some(exponent) => power_terms(&left, exponent)?,
+                    None => vec![Term::numerator(Expression::binary(
+                        BinaryOperationType::Power,
+                        terms_to_expression(&left),
+                        op.right_value.expand(),
+                    ))],
+                },
+            }
+        }
+    })
+}
+
+fn terms_to_expression(terms: &[Term]) -> Expression {
+    let mut sorted = terms.to_vec();
+    sorted.sort_by(|a, b| b.power_product.cmp(&a.power_product));
+    let mut terms = sorted.into_iter();
+    let first = match terms.next() {
+        Some(term) => term.to_expression(),
+        None => return Expression::Constant(0.into()),
+    };
+    terms.fold(first, |acc, term| {
+        if term.coefficient.is_negative() {
+            let term = Term {
+                coefficient: -term.coefficient,
+                ..term
+            };
+            Expression::binary(BinaryOperationType::Substraction, acc, term.to_expression())
+        } else {
+            Expression::binary(BinaryOperationType::Addition, acc, term.to_expression())
+        }
+    })
+}
+
+fn numerator_and_denominator(expression: &Expression) -> (Expression, Expression) {
+    let one = || Expression::Constant(1.into());
+    match expression {
+        Expression::BinaryOperation(op) => {
+            let (left_numerator, left_denominator) = numerator_and_denominator(&op.left_value);
+            let (right_numerator, right_denominator) = numerator_and_denominator(&op.right_value);
+            let multiply =
+                |left, right| Expression::binary(BinaryOperationType::Multiplication, left, right);
+            match op.operation {
+                BinaryOperationType::Addition | BinaryOperationType::Substraction => (
+                    Expression::binary(
+                        op.operation,
+                        multiply(left_numerator, right_denominator.clone()),
+                        multiply(right_numerator, left_denominator.clone()),
+                    ),
+                    multiply(left_denominator, right_denominator),
+                ),
+                BinaryOperationType::Multiplication => (
+                    multiply(left_numerator, right_numerator),
+                    multiply(left_denominator, right_denominator),
+                ),
+                BinaryOperationType::Division => (
+                    multiply(left_numerator, right_denominator),
+                    multiply(left_denominator, right_numerator),
+                ),
+                BinaryOperationType::Power => match &*op.right_value {
+                    Expression::Constant(exponent) if exponent.is_integer() => {
+                        let power = |base| {
+                            Expression::binary(
+                                BinaryOperationType::Power,
+                                base,
+                                Expression::Constant(exponent.abs()),
+                            )
+                        };
+                        if exponent.is_negative() {
+                            (power(left_denominator), power(left_numerator))
+                        } else {
+                            (power(left_numerator), power(left_denominator))
+                        }
+                    }
+                    _ => (expression.clone(), one()),
+                },
+            }
+        }
+        Expression::Function {
+            function,
+            expression,
+        } => (
+            Expression::Function {
+                function: *function,
+                expression: Box::new(expression.together()),
+            },
+            one(),
+        ),
+        Expression::Sum(operands) => {
+            numerator_and_denominator(&nest(BinaryOperationType::Addition, operands))
+        }
+        Expression::Product(operands) => {
+            numerator_and_denominator(&nest(BinaryOperationType::Multiplication, operands))
+        }
+        _ => (expression.clone(), one()),
+    }
+}
+
+fn nest(operation_type: BinaryOperationType, operands: &[Expression]) -> Expression {
+    operands
+        .iter()
+        .cloned()
+        .reduce(|acc, operand| Expression::binary(operation_type, acc, operand))
+        .unwrap_or_else(|| operation_type.identity_element())
+}
+
+fn fraction(numerator: Expression, denominator: Expression) -> Expression {
+    if denominator == Expression::Constant(1.into()) {
+        numerator
+    } else {
+        Expression::binary(BinaryOperationType::Division, numerator, denominator)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::expression::Function;
+
+    fn sine(expression: Expression) -> Expression {
+        Expression::function(Function::Sine, expression)
+    }
+
+    #[test]
+    fn test_expand_power_of_sum() {
+        // (x + y)^2
+        let expr = Expression::binary(
+            BinaryOperationType::Power,
+            Expression::binary(
+                BinaryOperationType::Addition,
+                Expression::monomial(1, 'x', 1),
+                Expression::monomial(1, 'y', 1),
+            ),
+            Expression::Constant(2.into()),
+        );
+        assert_eq!(
+            expr.expand(),
+            Expression::binary(
+                BinaryOperationType::Addition,
+                Expression::binary(
+                    BinaryOperationType::Addition,
+                    Expression::monomial(1, 'x', 2),
+                    Expression::binary(
+                        BinaryOperationType::Multiplication,
+                        Expression::monomial(2, 'x', 1),
+                        Expression::monomial(1, 'y', 1)
+                    )
+                ),
+                Expression::monomial(1, 'y', 2)
+            )
+        );
+    }
+
+    #[test]
+    fn test_expand_with_functions() {
+        // sin(x) * (x - sin(x)) + sin(x)^2
+        let expr = Expression::binary(
+            BinaryOperationType::Addition,
+            Expression::binary(
+                BinaryOperationType::Multiplication,
+                sine(Expression::monomial(1, 'x', 1)),
+                Expression::binary(
+                    BinaryOperationType::Substraction,
+                    Expression::monomial(1, 'x', 1),
+                    sine(Expression::monomial(1, 'x', 1)),
+                ),
+            ),
+            Expression::binary(
+                BinaryOperationType::Power,
+                sine(Expression::monomial(1, 'x', 1)),
+                Expression::Constant(2.into()),
+            ),
+        );
+        assert_eq!(
+            expr.expand(),
+            Expression::binary(
+                BinaryOperationType::Multiplication,
+                Expression::monomial(1, 'x', 1),
+                sine(Expression::monomial(1, 'x', 1))
+            )
+        );
+    }
+
+    #[test]
+    fn test_collect() {
+        // x^2*a + x*b + c*x^2
+        let expr = Expression::binary(
+            BinaryOperationType::Addition,
+            Expression::binary(
+                BinaryOperationType::Addition,
+                Expression::binary(
+                    BinaryOperationType::Multiplication,
+                    Expression::monomial(1, 'x', 2),
+                    Expression::monomial(1, 'a', 1),
+                ),
+                Expression::binary(
+                    BinaryOperationType::Multiplication,
+                    Expression::monomial(1, 'x', 1),
+                    Expression::monomial(1, 'b', 1),
+                ),
+            ),
+            Expression::binary(
+                BinaryOperationType::Multiplication,
+                Expression::monomial(1, 'c', 1),
+                Expression::monomial(1, 'x', 2),
+            ),
+        );
+        assert_eq!(
+            expr.collect('x'),
+            Expression::binary(
+                BinaryOperationType::Addition,
+                Expression::binary(
+                    BinaryOperationType::Multiplication,
+                    Expression::binary(
+                        BinaryOperationType::Addition,
+                        Expression::monomial(1, 'a', 1),
+                        Expression::monomial(1, 'c', 1)
+                    ),
+                    Expression::monomial(1, 'x', 2)
+                ),
+                Expression::binary(
+                    BinaryOperationType::Multiplication,
+                    Expression::monomial(1, 'b', 1),
+                    Expression::monomial(1, 'x', 1)
+                )
+            )
+        );
+    }
+
+    #[test]
+    fn test_collect_powers_beyond_monomials() {
+        // x^200 * x^100 + 2 x^3
+        let expr = Expression::binary(
+            BinaryOperationType::Addition,
+            Expression::binary(
+                BinaryOperationType::Multiplication,
+                Expression::monomial(1, 'x', 200),
+                Expression::monomial(1, 'x', 100),
+            ),
+            Expression::monomial(2, 'x', 3),
+        );
+        assert_eq!(
+            expr.collect('x'),
+            Expression::binary(
+                BinaryOperationType::Addition,
+                Expression::binary(
+                    BinaryOperationType::Power,
+                    Expression::monomial(1, 'x', 1),
+                    Expression::Constant(300.into())
+                ),
+                Expression::monomial(2, 'x', 3)
+            )
+        );
+    }
+
+    #[test]
+    fn test_together() {
+        // 1/x + 1/y = (y + x) / (x*y)
+        let expr = Expression::binary(
+            BinaryOperationType::Addition,
+            Expression::binary(
+                BinaryOperationType::Division,
+                Expression::Constant(1.into()),
+                Expression::monomial(1, 'x', 1),
+            ),
+            Expression::binary(
+                BinaryOperationType::Division,
+                Expression::Constant(1.into()),
+                Expression::monomial(1, 'y', 1),
+            ),
+        );
+        assert_eq!(
+            expr.together(),
+            Expression::binary(
+                BinaryOperationType::Division,
+                Expression::binary(
+                    BinaryOperationType::Addition,
+                    Expression::monomial(1, 'y', 1),
+                    Expression::monomial(1, 'x', 1)
+                ),
+                Expression::binary(
+                    BinaryOperationType::Multiplication,
+                    Expression::monomial(1, 'x', 1),
+                    Expression::monomial(1, 'y', 1)
+                )
+            )
+        );
+    }
+
+    #[test]
+    fn test_cancel() {
+        // (x^2 - 1) / (x - 1) = x + 1
+        let expr = Expression::binary(
+            BinaryOperationType::Division,
+            Expression::binary(
+                BinaryOperationType::Substraction,
+                Expression::monomial(1, 'x', 2),
+                Expression::Constant(1.into()),
+            ),
+            Expression::binary(
+                BinaryOperationType::Substraction,
+                Expression::monomial(1, 'x', 1),
+                Expression::Constant(1.into()),
+            ),
+        );
+        assert_eq!(
+            expr.cancel(),
+            Expression::binary(
+                BinaryOperationType::Addition,
+                Expression::monomial(1, 'x', 1),
+                Expression::Constant(1.into())
+            )
+        );
+
+        // x/(x*y) + 1/y = 2/y
+        let expr = Expression::binary(
+            BinaryOperationType::Addition,
+            Expression::binary(
+                BinaryOperationType::Division,
+                Expression::monomial(1, 'x', 1),
+                Expression::binary(
+                    BinaryOperationType::Multiplication,
+                    Expression::monomial(1, 'x', 1),
+                    Expression::monomial(1, 'y', 1),
+                ),
+            ),
+            Expression::binary(
+                BinaryOperationType::Division,
+                Expression::Constant(1.into()),
+                Expression::monomial(1, 'y', 1),
+            ),
+        );
+        assert_eq!(
+            expr.cancel(),
+            Expression::binary(
+                BinaryOperationType::Division,
+                Expression::Constant(2.into()),
+                Expression::monomial(1, 'y', 1)
+            )
+        );
+    }
+
+    #[test]
+    fn test_coefficient_overflow_is_left_unexpanded() {
+        // (x + 1)^70 has coefficients beyond i64::MAX
+        let power = Expression::binary(
+            BinaryOperationType::Power,
+            Expression::binary(
+                BinaryOperationType::Addition,
+                Expression::monomial(1, 'x', 1),
+                Expression::Constant(1.into()),
+            ),
+            Expression::Constant(70.into()),
+        );
+        assert_eq!(power.expand(), power);
+        assert_eq!(power.collect('x'), power);
+
+        let huge = Expression::Constant(i64::MAX.into());
+        let fraction = Expression::binary(
+            BinaryOperationType::Division,
+            Expression::binary(
+                BinaryOperationType::Multiplication,
+                huge.clone(),
+                power.clone(),
+            ),
+            Expression::binary(
+                BinaryOperationType::Addition,
+                Expression::monomial(1, 'x', 1),
+                huge,
+            ),
+        );
+        // The gcd overflows, so the fraction is only put over a common denominator.
+        assert_eq!(fraction.cancel(), fraction.together());
+        assert_eq!(fraction.cancel(), fraction);
+    }
+
+    #[test]
+    fn test_large_natural_powers() {
+        let power = |base: Expression, exponent: i64| {
+            Expression::binary(
+                BinaryOperationType::Power,
+                base,
+                Expression::Constant(exponent.into()),
+            )
+        };
+        let x = Expression::monomial(1, 'x', 1);
+        // Single terms are raised directly instead of being multiplied `exponent` times.
+        assert_eq!(
+            power(x.clone(), 1_000_000_000).expand(),
+            power(x.clone(), 1_000_000_000)
+        );
+        assert_eq!(
+            power(sine(x.clone()), 40000).expand(),
+            power(sine(x.clone()), 40000)
+        );
+        assert_eq!(
+            power(
+                Expression::binary(
+                    BinaryOperationType::Multiplication,
+                    x.clone(),
+                    sine(x.clone())
+                ),
+                300
+            )
+            .expand(),
+            Expression::binary(
+                BinaryOperationType::Multiplication,
+                power(x.clone(), 300),
+                power(sine(x.clone()), 300)
+            )
+        );
+        // (x + 1)^40 is expanded by repeated squaring, with C(40, 20) x^20 as middle term.
+        let expanded = power(
+            Expression::binary(
+                BinaryOperationType::Addition,
+                x,
+                Expression::Constant(1.into()),
+            ),
+            40,
+        )
+        .expand();
+        let polynomial = Polynomial::try_from(&expanded).unwrap();
+        assert_eq!(polynomial.terms().count(), 41);
+        assert_eq!(
+            polynomial.coefficient(&PowerProduct::variable('x', 20)),
+            137846528820.into()
+        );
+    }
+}
@@ -1,61 +1,192 @@
 use std::collections::HashSet;
 
+use num_rational::Ratio;
+
 pub type Variable = char;
+pub type Rational = Ratio<i64>;
 
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum BinaryOperationType {
     Addition,
     Substraction,
     Multiplication,
     Division,
+    Power,
 }
 
 impl BinaryOperationType {
     pub fn identity_element(&self) -> Expression {
         match self {
             BinaryOperationType::Addition | BinaryOperationType::Substraction => {
-                Expression::Constant(0.)
-            }
-            BinaryOperationType::Multiplication | BinaryOperationType::Division => {
-                Expression::Constant(1.)
+                Expression::Constant(0.into())
             }
+            BinaryOperationType::Multiplication
+            | BinaryOperationType::Division
+            | BinaryOperationType::Power => Expression::Constant(1.into()),
         }
     }
+
+    /// Whether the identity element is also neutral on the left-hand side.
+    pub fn is_commutative(&self) -> bool {
+        matches!(
+            self,
+            BinaryOperationType::Addition | BinaryOperationType::Multiplication
+        )
+    }
 }
 
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Function {
     Sine,
     Cosine,
+    Exponential,
+    Logarithm,
+    AbsoluteValue,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+impl Function {
+    pub fn is_trigonometric(&self) -> bool {
+        matches!(self, Function::Sine | Function::Cosine)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BinaryOperation {
     pub operation: BinaryOperationType,
     pub left_value: Box<Expression>,
     pub right_value: Box<Expression>,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Monomial {
-    pub factor: f64,
+    pub factor: Rational,
     pub variable: Variable,
     pub power: u8,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+/// Structural equality and ordering, see `Canonical` to compare expressions mathematically.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Expression {
-    Constant(f64),
-    NamedConstant(&'static str),
+    Constant(Rational),
+    NamedConstant(String),
     Monomial(Monomial),
     BinaryOperation(BinaryOperation),
     Function {
         function: Function,
         expression: Box<Expression>,
     },
+    Sum(Vec<Expression>),
+    Product(Vec<Expression>),
 }
 
 impl Expression {
+    /// `left operation right`.
+    pub fn binary(
+        operation: BinaryOperationType,
+        left: Expression,
+        right: Expression,
+    ) -> Expression {
+        Expression::BinaryOperation(BinaryOperation {
+            operation,
+            left_value: Box::new(left),
+            right_value: Box::new(right),
+        })
+    }
+
+    /// `function(expression)`.
+    pub fn function(function: Function, expression: Expression) -> Expression {
+        Expression::Function {
+            function,
+            expression: Box::new(expression),
+        }
+    }
+
+    /// `factor * variable^power`.
+    pub fn monomial(factor: impl Into<Rational>, variable: Variable, power: u8) -> Expression {
+        Expression::Monomial(Monomial {
+            factor: factor.into(),
+            variable,
+            power,
+        })
+    }
+
+    /// Returns a copy of the expression where `f` was applied to each direct subexpression.
+    pub fn map_children<F: Fn(&Expression) -> Expression>(&self, f: F) -> Expression {
+        match self {
+            Expression::Constant(_) | Expression::NamedConstant(_) | Expression::Monomial(_) => {
+                self.clone()
+            }
+            Expression::BinaryOperation(operation) => {
+                Expression::BinaryOperation(BinaryOperation {
+                    operation: operation.operation,
+                    left_value: Box::new(f(&operation.left_value)),
+                    right_value: Box::new(f(&operation.right_value)),
+                })
+            }
+            Expression::Function {
+                function,
+                expression,
+            } => Expression::Function {
+                function: *function,
+                expression: Box::new(f(expression)),
+            },
+            Expression::Sum(terms) => Expression::Sum(terms.iter().map(f).collect()),
+            Expression::Product(factors) => Expression::Product(factors.iter().map(f).collect()),
+        }
+    }
+
+    /// Copy of the expression where `variable` is replaced by `value`.
+    pub fn substitute(&self, variable: Variable, value: &Expression) -> Expression {
+        match self {
+            Expression::Monomial(m) if m.variable == variable => {
+                let power = if m.power == 1 {
+                    value.clone()
+                } else {
+                    Expression::BinaryOperation(BinaryOperation {
+                        operation: BinaryOperationType::Power,
+                        left_value: Box::new(value.clone()),
+                        right_value: Box::new(Expression::Constant(i64::from(m.power).into())),
+                    })
+                };
+                if m.factor == 1.into() {
+                    power
+                } else {
+                    Expression::Product(vec![Expression::Constant(m.factor), power])
+                }
+            }
+            _ => self.map_children(|child| child.substitute(variable, value)),
+        }
+    }
+
+    /// Copy of the expression where every occurrence of the subexpression `from` is replaced
+    /// by `to`.
+    pub fn replace(&self, from: &Expression, to: &Expression) -> Expression {
+        if self == from {
+            to.clone()
+        } else {
+            self.map_children(|child| child.replace(from, to))
+        }
+    }
+
+    /// Number of nodes in the expression tree, counting repeated subtrees every time.
+    pub fn size(&self) -> usize {
+        1 + match self {
+            Expression::Constant(_) | Expression::NamedConstant(_) | Expression::Monomial(_) => 0,
+            Expression::BinaryOperation(operation) => {
+                operation.left_value.size() + operation.right_value.size()
+            }
+            Expression::Function { expression, .. } => expression.size(),
+            Expression::Sum(operands) | Expression::Product(operands) => {
+                operands.iter().map(Expression::size).sum()
+            }
+        }
+    }
+
     pub fn variables(&self) -> HashSet<Variable> {
         match &self {
             Expression::Constant(_) | Expression::NamedConstant(_) => HashSet::<Variable>::new(),
@@ -66,6 +197,10 @@ impl Expression {
                 left_vars.extend(operation.right_value.variables());
                 left_vars
             }
+            Expression::Sum(operands) | Expression::Product(operands) => operands
+                .iter()
+                .flat_map(|operand| operand.variables())
+                .collect(),
         }
     }
 }
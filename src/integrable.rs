@@ -0,0 +1,925 @@
+use std::collections::HashMap;
+
+use num_traits::{CheckedDiv, CheckedMul, One};
+
+use crate::assumptions::{Assumptions, Interval};
+use crate::canonical::Canonical;
+use crate::dag::temporary;
+use crate::derivable::Derivable;
+use crate::evaluable::Evaluable;
+use crate::expandable::Expandable;
+use crate::expression::{
+    BinaryOperation, BinaryOperationType, Expression, Function, Monomial, Rational, Variable,
+};
+use crate::simplifiable::Simplifiable;
+use crate::trigonometric::Trigonometric;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum IntegrationError {
+    /// No rule finds an antiderivative of `integrand` with respect to `variable`.
+    CannotIntegrate {
+        integrand: Expression,
+        variable: Variable,
+    },
+    /// `integrand` or its antiderivative is not defined everywhere between the bounds, so
+    /// the difference of the antiderivative at the bounds is not the integral.
+    Improper {
+        integrand: Expression,
+        variable: Variable,
+    },
+}
+
+/// Antiderivatives of polynomials, powers of sines and cosines, exponentials and the
+/// integrands reducible to them by parts or by substitution.
+pub trait Integrable {
+    /// An antiderivative with respect to `variable`, without constant of integration.
+    fn integrate(&self, variable: Variable) -> Result<Expression, IntegrationError>;
+
+    /// Integral from `lower` to `upper`, the difference of the antiderivative at the bounds.
+    /// Fails with `Improper` unless every logarithm argument, denominator and base of a
+    /// negative power in the integrand and the antiderivative is provably nonzero between
+    /// the bounds.
+    fn integrate_between(
+        &self,
+        variable: Variable,
+        lower: &Expression,
+        upper: &Expression,
+    ) -> Result<Expression, IntegrationError>;
+
+    /// `integrate_between` where the signs of the other variables follow from `assumptions`.
+    fn integrate_between_with(
+        &self,
+        variable: Variable,
+        lower: &Expression,
+        upper: &Expression,
+        assumptions: &Assumptions,
+    ) -> Result<Expression, IntegrationError>;
+}
+
+impl Integrable for Expression {
+    fn integrate(&self, variable: Variable) -> Result<Expression, IntegrationError> {
+        antiderivative(&self.simplify_expression(), variable)
+            .map(|result| result.simplify_expression())
+            .ok_or_else(|| IntegrationError::CannotIntegrate {
+                integrand: self.clone(),
+                variable,
+            })
+    }
+
+    fn integrate_between(
+        &self,
+        variable: Variable,
+        lower: &Expression,
+        upper: &Expression,
+    ) -> Result<Expression, IntegrationError> {
+        self.integrate_between_with(variable, lower, upper, &Assumptions::new())
+    }
+
+    fn integrate_between_with(
+        &self,
+        variable: Variable,
+        lower: &Expression,
+        upper: &Expression,
+        assumptions: &Assumptions,
+    ) -> Result<Expression, IntegrationError> {
+        let primitive = self.integrate(variable)?;
+        let mut assumptions = assumptions.clone();
+        if let (Some(lower), Some(upper)) = (enclosure(lower), enclosure(upper)) {
+            let interval = Interval::closed(lower.0.min(upper.0), lower.1.max(upper.1));
+            assumptions = assumptions.in_interval(variable, interval);
+        }
+        if !defined(self, variable, &assumptions) || !defined(&primitive, variable, &assumptions) {
+            return Err(IntegrationError::Improper {
+                integrand: self.clone(),
+                variable,
+            });
+        }
+        Ok(Expression::binary(
+            BinaryOperationType::Substraction,
+            primitive.substitute(variable, upper),
+            primitive.substitute(variable, lower),
+        )
+        .simplify_expression()
+        .simplify_trigonometry())
+    }
+}
+
+/// Rationals enclosing the value of the constant `bound`.
+fn enclosure(bound: &Expression) -> Option<(Rational, Rational)> {
+    if let Expression::Constant(value) = bound.simplify_expression() {
+        return Some((value, value));
+    }
+    // rounded outwards to millionths
+    let value = bound.evaluate(&HashMap::new()).ok()? * 1e6;
+    if value.is_nan() || value.abs() >= 1e15 {
+        return None;
+    }
+    Some((
+        Rational::new(value.floor() as i64, 1_000_000),
+        Rational::new(value.ceil() as i64, 1_000_000),
+    ))
+}
+
+/// Whether the logarithm arguments, denominators and bases of negative powers of
+/// `expression` that depend on `variable` are provably nonzero, and the arguments of bare
+/// logarithms positive.
+fn defined(expression: &Expression, variable: Variable, assumptions: &Assumptions) -> bool {
+    let mut pending = vec![expression];
+    while let Some(expression) = pending.pop() {
+        let defined = match expression {
+            Expression::Function {
+                function: Function::Logarithm,
+                expression: argument,
+            } => match argument.as_ref() {
+                Expression::Function {
+                    function: Function::AbsoluteValue,
+                    expression: argument,
+                } => nonzero(assumptions, argument, variable),
+                argument => {
+                    !depends_on(argument, variable) || assumptions.sign(argument).is_positive()
+                }
+            },
+            Expression::BinaryOperation(BinaryOperation {
+                operation: BinaryOperationType::Division,
+                right_value: denominator,
+                ..
+            }) => nonzero(assumptions, denominator, variable),
+            Expression::BinaryOperation(BinaryOperation {
+                operation: BinaryOperationType::Power,
+                left_value: base,
+                right_value: exponent,
+            }) => match exponent.as_ref() {
+                Expression::Constant(exponent) if *exponent < 0.into() => {
+                    nonzero(assumptions, base, variable)
+                }
+                _ => true,
+            },
+            _ => true,
+        };
+        if !defined {
+            return false;
+        }
+        match expression {
+            Expression::BinaryOperation(operation) => {
+                pending.extend([&*operation.left_value, &*operation.right_value])
+            }
+            Expression::Function { expression, .. } => pending.push(expression),
+            Expression::Sum(operands) | Expression::Product(operands) => pending.extend(operands),
+            _ => {}
+        }
+    }
+    true
+}
+
+fn nonzero(assumptions: &Assumptions, expression: &Expression, variable: Variable) -> bool {
+    !depends_on(expression, variable) || assumptions.sign(expression).is_nonzero()
+}
+
+fn constant(value: impl Into<Rational>) -> Expression {
+    Expression::Constant(value.into())
+}
+
+fn power(base: Expression, exponent: i64) -> Expression {
+    match exponent {
+        1 => base,
+        _ => Expression::binary(BinaryOperationType::Power, base, constant(exponent)),
+    }
+}
+
+fn divide(numerator: Expression, denominator: Expression) -> Expression {
+    Expression::binary(BinaryOperationType::Division, numerator, denominator)
+}
+
+fn depends_on(expression: &Expression, variable: Variable) -> bool {
+    expression.variables().contains(&variable)
+}
+
+fn is_zero(expression: &Expression) -> bool {
+    expression.to_canonical_form() == Expression::Constant(0.into())
+}
+
+fn same(left: &Expression, right: &Expression) -> bool {
+    left.to_canonical_form() == right.to_canonical_form()
+}
+
+/// The slope `a` of an argument `a x + b`.
+fn slope(argument: &Expression, variable: Variable) -> Option<Expression> {
+    let slope = argument.derive(variable).simplify_expression();
+    (!depends_on(&slope, variable) && !is_zero(&slope)).then_some(slope)
+}
+
+/// `value` when it is an integer constant.
+fn integer(value: &Expression) -> Option<i64> {
+    match value {
+        Expression::Constant(value) if value.is_integer() => Some(*value.numer()),
+        _ => None,
+    }
+}
+
+/// `value` when it is a positive integer constant.
+fn positive_integer(value: &Expression) -> Option<i64> {
+    integer(value).filter(|value| *value > 0)
+}
+
+/// Gathers the integer powers of equal factors, dropping the ones that cancel out.
+fn cancel_factors(factors: Vec<Expression>) -> Vec<Expression> {
+    let mut powers: Vec<(Expression, i64)> = vec![];
+    for factor in factors {
+        let (base, exponent) = match &factor {
+            Expression::BinaryOperation(BinaryOperation {
+                operation: BinaryOperationType::Power,
+                left_value,
+                right_value,
+            }) => match integer(right_value) {
+                Some(exponent) => (*left_value.clone(), exponent),
+                None => (factor, 1),
+            },
+            _ => (factor, 1),
+        };
+        match powers.iter_mut().find(|(other, _)| same(other, &base)) {
+            Some((_, total)) => *total += exponent,
+            None => powers.push((base, exponent)),
+        }
+    }
+    powers
+        .into_iter()
+        .filter(|(_, exponent)| *exponent != 0)
+        .map(|(base, exponent)| power(base, exponent))
+        .collect()
+}
+
+/// Factors of a product, nested products and quotients being flattened.
+fn factors(expression: &Expression) -> Vec<Expression> {
+    match expression {
+        Expression::Product(operands) => operands.iter().flat_map(factors).collect(),
+        Expression::BinaryOperation(BinaryOperation {
+            operation: BinaryOperationType::Multiplication,
+            left_value,
+            right_value,
+        }) => [factors(left_value), factors(right_value)].concat(),
+        Expression::BinaryOperation(BinaryOperation {
+            operation: BinaryOperationType::Division,
+            left_value,
+            right_value,
+        }) => [
+            factors(left_value),
+            factors(&power(*right_value.clone(), -1)),
+        ]
+        .concat(),
+        Expression::BinaryOperation(BinaryOperation {
+            operation: BinaryOperationType::Power,
+            left_value,
+            right_value,
+        }) => match (factors(left_value).as_slice(), integer(right_value)) {
+            (split @ [_, _, ..], Some(exponent)) => split
+                .iter()
+                .map(|factor| power(factor.clone(), exponent))
+                .collect(),
+            _ => vec![expression.clone()],
+        },
+        Expression::Monomial(m) if m.factor != 1.into() => vec![
+            Expression::Constant(m.factor),
+            Expression::Monomial(Monomial {
+                factor: 1.into(),
+                ..*m
+            }),
+        ],
+        _ => vec![expression.clone()],
+    }
+}
+
+fn product(factors: Vec<Expression>) -> Expression {
+    match factors.len() {
+        0 => constant(1),
+        1 => factors.into_iter().next().unwrap(),
+        _ => Expression::Product(factors),
+    }
+}
+
+fn antiderivative(expression: &Expression, variable: Variable) -> Option<Expression> {
+    if !depends_on(expression, variable) {
+        return Some(Expression::binary(
+            BinaryOperationType::Multiplication,
+            expression.clone(),
+            Expression::Monomial(Monomial {
+                factor: 1.into(),
+                variable,
+                power: 1,
+            }),
+        ));
+    }
+    let result = match expression {
+        Expression::Sum(terms) => terms
+            .iter()
+            .map(|term| antiderivative(term, variable))
+            .collect::<Option<Vec<_>>>()
+            .map(Expression::Sum),
+        Expression::BinaryOperation(BinaryOperation {
+            operation:
+                operation @ (BinaryOperationType::Addition | BinaryOperationType::Substraction),
+            left_value,
+            right_value,
+        }) => antiderivative(left_value, variable).and_then(|left| {
+            antiderivative(right_value, variable)
+                .map(|right| Expression::binary(*operation, left, right))
+        }),
+        Expression::Monomial(m) => Some(if m.power == 0 {
+            antiderivative(&Expression::Constant(m.factor), variable)?
+        } else {
+            Expression::Monomial(Monomial {
+                factor: m
+                    .factor
+                    .checked_div(&Rational::from(i64::from(m.power) + 1))?,
+                power: m.power.checked_add(1)?,
+                ..*m
+            })
+        }),
+        _ => antiderivative_of_product(factors(expression), variable),
+    };
+    result
+        .or_else(|| by_substitution(expression, variable))
+        .or_else(|| {
+            let expanded = expression.expand();
+            (expanded != *expression)
+                .then(|| antiderivative(&expanded, variable))
+                .flatten()
+        })
+}
+
+fn antiderivative_of_product(factors: Vec<Expression>, variable: Variable) -> Option<Expression> {
+    let (constants, factors): (Vec<_>, Vec<_>) = cancel_factors(factors)
+        .into_iter()
+        .partition(|factor| !depends_on(factor, variable));
+    let result = match factors.as_slice() {
+        [factor] => single_factor(factor, variable),
+        _ => None,
+    }
+    .or_else(|| sines_and_cosines(&factors, variable))
+    .or_else(|| by_parts(&factors, variable))
+    .or_else(|| polynomial(&factors, variable))?;
+    Some(product([constants, vec![result]].concat()))
+}
+
+/// Powers and elementary functions of a linear argument.
+fn single_factor(factor: &Expression, variable: Variable) -> Option<Expression> {
+    match factor {
+        Expression::BinaryOperation(BinaryOperation {
+            operation: BinaryOperationType::Power,
+            left_value: base,
+            right_value: exponent,
+        }) if !depends_on(exponent, variable) => {
+            if let Some(result) = sines_and_cosines(std::slice::from_ref(factor), variable) {
+                return Some(result);
+            }
+            let a = slope(base, variable)?;
+            Some(if same(exponent, &constant(-1)) {
+                divide(
+                    Expression::function(
+                        Function::Logarithm,
+                        Expression::function(Function::AbsoluteValue, *base.clone()),
+                    ),
+                    a,
+                )
+            } else {
+                let exponent = Expression::binary(
+                    BinaryOperationType::Addition,
+                    *exponent.clone(),
+                    constant(1),
+                )
+                .simplify_expression();
+                divide(
+                    Expression::binary(BinaryOperationType::Power, *base.clone(), exponent.clone()),
+                    Expression::binary(BinaryOperationType::Multiplication, exponent, a),
+                )
+            })
+        }
+        Expression::BinaryOperation(BinaryOperation {
+            operation: BinaryOperationType::Power,
+            left_value: base,
+            right_value: exponent,
+        }) if !depends_on(base, variable) => {
+            let a = slope(exponent, variable)?;
+            Some(divide(
+                factor.clone(),
+                Expression::binary(
+                    BinaryOperationType::Multiplication,
+                    a,
+                    Expression::function(Function::Logarithm, *base.clone()),
+                ),
+            ))
+        }
+        Expression::Function {
+            function: f,
+            expression: argument,
+        } => {
+            let a = slope(argument, variable)?;
+            let u = *argument.clone();
+            let primitive = match f {
+                Function::Sine => Expression::Product(vec![
+                    constant(-1),
+                    Expression::function(Function::Cosine, u),
+                ]),
+                Function::Cosine => Expression::function(Function::Sine, u),
+                Function::Exponential => factor.clone(),
+                Function::Logarithm => Expression::binary(
+                    BinaryOperationType::Substraction,
+                    Expression::binary(
+                        BinaryOperationType::Multiplication,
+                        u.clone(),
+                        Expression::function(Function::Logarithm, u.clone()),
+                    ),
+                    u,
+                ),
+                Function::AbsoluteValue => return None,
+            };
+            Some(divide(primitive, a))
+        }
+        _ => None,
+    }
+}
+
+/// `sin(u)^m cos(u)^n` for a linear `u`, by the reduction formulas.
+fn sines_and_cosines(factors: &[Expression], variable: Variable) -> Option<Expression> {
+    let mut argument: Option<&Expression> = None;
+    let (mut sines, mut cosines) = (0, 0);
+    for factor in factors {
+        let (base, exponent) = match factor {
+            Expression::BinaryOperation(BinaryOperation {
+                operation: BinaryOperationType::Power,
+                left_value,
+                right_value,
+            }) => (left_value.as_ref(), positive_integer(right_value)?),
+            _ => (factor, 1),
+        };
+        let Expression::Function {
+            function: f @ (Function::Sine | Function::Cosine),
+            expression,
+        } = base
+        else {
+            return None;
+        };
+        if argument.is_some_and(|argument| !same(argument, expression)) {
+            return None;
+        }
+        argument = Some(expression);
+        match f {
+            Function::Sine => sines = exponent.checked_add(sines)?,
+            _ => cosines = exponent.checked_add(cosines)?,
+        }
+    }
+    let argument = argument?;
+    let a = slope(argument, variable)?;
+    reduce(sines, cosines, argument, &a, variable)
+}
+
+/// `∫ sin(u)^sines cos(u)^cosines dx` for `u = a x + b`, the reduction formulas being
+/// unrolled into a flat sum. `None` when a coefficient overflows.
+fn reduce(
+    mut sines: i64,
+    mut cosines: i64,
+    argument: &Expression,
+    a: &Expression,
+    variable: Variable,
+) -> Option<Expression> {
+    let sine = |exponent| {
+        power(
+            Expression::function(Function::Sine, argument.clone()),
+            exponent,
+        )
+    };
+    let cosine = |exponent| {
+        power(
+            Expression::function(Function::Cosine, argument.clone()),
+            exponent,
+        )
+    };
+    let mut terms = vec![];
+    // coefficient of the integral still to reduce
+    let mut coefficient = Rational::one();
+    loop {
+        let total = sines + cosines;
+        // the term, and the ratio and exponents of the integral it leaves
+        let (factor, factors, remaining) = match (sines, cosines) {
+            (0, 0) => (
+                Rational::one(),
+                vec![Expression::monomial(1, variable, 1)],
+                None,
+            ),
+            (0, 1) => (Rational::one(), vec![sine(1), power(a.clone(), -1)], None),
+            (1, _) => (
+                Rational::new(-1, cosines + 1),
+                vec![cosine(cosines + 1), power(a.clone(), -1)],
+                None,
+            ),
+            (0, _) => (
+                Rational::new(1, cosines),
+                vec![cosine(cosines - 1), sine(1), power(a.clone(), -1)],
+                Some((Rational::new(cosines - 1, cosines), 0, cosines - 2)),
+            ),
+            _ => (
+                Rational::new(-1, total),
+                vec![sine(sines - 1), cosine(cosines + 1), power(a.clone(), -1)],
+                Some((Rational::new(sines - 1, total), sines - 2, cosines)),
+            ),
+        };
+        terms.push(product(
+            [vec![constant(coefficient.checked_mul(&factor)?)], factors].concat(),
+        ));
+        let Some((ratio, next_sines, next_cosines)) = remaining else {
+            break;
+        };
+        coefficient = coefficient.checked_mul(&ratio)?;
+        (sines, cosines) = (next_sines, next_cosines);
+    }
+    Some(match terms.len() {
+        1 => terms.remove(0),
+        _ => Expression::Sum(terms),
+    })
+}
+
+fn is_polynomial(expression: &Expression, variable: Variable) -> bool {
+    match expression {
+        _ if !depends_on(expression, variable) => true,
+        Expression::Monomial(_) => true,
+        Expression::BinaryOperation(BinaryOperation {
+            operation: BinaryOperationType::Power,
+            left_value,
+            right_value,
+        }) => positive_integer(right_value).is_some() && is_polynomial(left_value, variable),
+        Expression::BinaryOperation(BinaryOperation {
+            operation:
+                BinaryOperationType::Addition
+                | BinaryOperationType::Substraction
+                | BinaryOperationType::Multiplication,
+            left_value,
+            right_value,
+        }) => is_polynomial(left_value, variable) && is_polynomial(right_value, variable),
+        Expression::Sum(operands) | Expression::Product(operands) => operands
+            .iter()
+            .all(|operand| is_polynomial(operand, variable)),
+        _ => false,
+    }
+}
+
+/// A polynomial times the exponential, sine or cosine of a linear argument, integrated by
+/// parts until the polynomial vanishes.
+fn by_parts(factors: &[Expression], variable: Variable) -> Option<Expression> {
+    let position = factors.iter().position(|factor| {
+        matches!(
+            factor,
+            Expression::Function {
+                function: Function::Exponential | Function::Sine | Function::Cosine,
+                expression,
+            } if slope(expression, variable).is_some()
+        )
+    })?;
+    let polynomial: Vec<Expression> = factors
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| *index != position)
+        .map(|(_, factor)| factor.clone())
+        .collect();
+    if polynomial.is_empty()
+        || !polynomial
+            .iter()
+            .all(|factor| is_polynomial(factor, variable))
+    {
+        return None;
+    }
+    let polynomial = product(polynomial);
+    let primitive = single_factor(&factors[position], variable)?;
+    let derivative = polynomial.derive(variable).simplify_expression();
+    Some(Expression::binary(
+        BinaryOperationType::Substraction,
+        Expression::binary(
+            BinaryOperationType::Multiplication,
+            polynomial,
+            primitive.clone(),
+        ),
+        antiderivative(
+            &Expression::binary(BinaryOperationType::Multiplication, derivative, primitive),
+            variable,
+        )?,
+    ))
+}
+
+/// Products of powers of the variable itself.
+fn polynomial(factors: &[Expression], variable: Variable) -> Option<Expression> {
+    let power = factors.iter().try_fold(0u8, |power, factor| match factor {
+        Expression::Monomial(m) if m.factor == 1.into() => power.checked_add(m.power),
+        _ => None,
+    })?;
+    antiderivative(
+        &Expression::Monomial(Monomial {
+            factor: 1.into(),
+            variable,
+            power,
+        }),
+        variable,
+    )
+}
+
+/// Function arguments, power bases and denominators depending on `variable`, outermost first.
+fn inner_expressions(expression: &Expression, variable: Variable) -> Vec<Expression> {
+    let inner: Vec<&Expression> = match expression {
+        Expression::Function { expression, .. } => vec![expression],
+        Expression::BinaryOperation(BinaryOperation {
+            operation: BinaryOperationType::Power,
+            left_value,
+            ..
+        }) => vec![left_value],
+        Expression::BinaryOperation(BinaryOperation {
+            operation: BinaryOperationType::Division,
+            right_value,
+            ..
+        }) => vec![right_value],
+        _ => vec![],
+    };
+    let candidates = inner
+        .into_iter()
+        .filter(|inner| {
+            depends_on(inner, variable) && !matches!(inner, Expression::Monomial(m) if m.power == 1)
+        })
+        .cloned();
+    let children: Vec<&Expression> = match expression {
+        Expression::BinaryOperation(operation) => {
+            vec![&operation.left_value, &operation.right_value]
+        }
+        Expression::Function { expression, .. } => vec![expression],
+        Expression::Sum(operands) | Expression::Product(operands) => operands.iter().collect(),
+        _ => vec![],
+    };
+    candidates
+        .chain(
+            children
+                .iter()
+                .flat_map(|child| inner_expressions(child, variable)),
+        )
+        .collect()
+}
+
+/// `∫ f(g(x)) g'(x) dx = ∫ f(w) dw` for an inner expression `g`.
+fn by_substitution(expression: &Expression, variable: Variable) -> Option<Expression> {
+    let used = expression.variables();
+    let fresh = (0..)
+        .map(temporary)
+        .find(|candidate| !used.contains(candidate) && *candidate != variable)?;
+    let w = Expression::Monomial(Monomial {
+        factor: 1.into(),
+        variable: fresh,
+        power: 1,
+    });
+    inner_expressions(expression, variable)
+        .into_iter()
+        .find_map(|inner| {
+            let derivative = inner.derive(variable).simplify_expression();
+            let (slope, derivative): (Vec<_>, Vec<_>) = factors(&derivative)
+                .into_iter()
+                .partition(|factor| !depends_on(factor, variable));
+            let mut remaining = factors(expression);
+            for factor in derivative {
+                let position = remaining.iter().position(|other| same(other, &factor))?;
+                remaining.remove(position);
+            }
+            let integrand = product(remaining).replace(&inner, &w);
+            if depends_on(&integrand, variable) {
+                return None;
+            }
+            let primitive = antiderivative(&integrand.simplify_expression(), fresh)?;
+            Some(divide(primitive.substitute(fresh, &inner), product(slope)))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::HashMap;
+
+    use crate::evaluable::Evaluable;
+
+    fn x() -> Expression {
+        Expression::Monomial(Monomial {
+            factor: 1.into(),
+            variable: 'x',
+            power: 1,
+        })
+    }
+
+    /// Checks numerically that the derivative of the antiderivative is the integrand.
+    fn assert_antiderivative(integrand: &Expression) {
+        let primitive = integrand.integrate('x').unwrap();
+        let derivative = primitive.derive('x');
+        for value in [0.3, 0.7, 1.1, 2.5] {
+            let values = HashMap::from([('x', value)]);
+            let expected = integrand.evaluate(&values).unwrap();
+            let actual = derivative.evaluate(&values).unwrap();
+            assert!(
+                (expected - actual).abs() < 1e-9,
+                "{integrand:?}: {expected} != {actual} at {value}, primitive {primitive:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_polynomials() {
+        assert_eq!(
+            Expression::monomial(3, 'x', 2).integrate('x'),
+            Ok(Expression::monomial(1, 'x', 3)),
+        );
+        assert_eq!(
+            Expression::Constant(2.into()).integrate('x'),
+            Ok(Expression::monomial(2, 'x', 1)),
+        );
+        // (x + 1)^3 - 4x + 1/x
+        assert_antiderivative(&Expression::Sum(vec![
+            power(
+                Expression::binary(BinaryOperationType::Addition, x(), constant(1)),
+                3,
+            ),
+            Expression::monomial(-4, 'x', 1),
+            power(x(), -1),
+        ]));
+        // x^2 * (2x + 3)
+        assert_antiderivative(&Expression::Product(vec![
+            Expression::monomial(1, 'x', 2),
+            Expression::binary(
+                BinaryOperationType::Addition,
+                Expression::monomial(2, 'x', 1),
+                constant(3),
+            ),
+        ]));
+    }
+
+    #[test]
+    fn test_exponentials_and_powers_of_sines_and_cosines() {
+        let u = Expression::binary(
+            BinaryOperationType::Addition,
+            Expression::monomial(2, 'x', 1),
+            constant(1),
+        );
+        assert_antiderivative(&Expression::function(Function::Exponential, u.clone()));
+        assert_antiderivative(&Expression::function(Function::Logarithm, x()));
+        assert_antiderivative(&power(Expression::function(Function::Sine, u.clone()), 2));
+        assert_antiderivative(&power(Expression::function(Function::Cosine, x()), 5));
+        // sin(2x + 1)^3 cos(2x + 1)^2
+        assert_antiderivative(&Expression::Product(vec![
+            power(Expression::function(Function::Sine, u.clone()), 3),
+            power(Expression::function(Function::Cosine, u), 2),
+        ]));
+        // 2^x
+        assert_antiderivative(&Expression::binary(
+            BinaryOperationType::Power,
+            constant(2),
+            x(),
+        ));
+    }
+
+    #[test]
+    fn test_by_parts_and_substitution() {
+        // x^2 e^x
+        assert_antiderivative(&Expression::Product(vec![
+            Expression::monomial(1, 'x', 2),
+            Expression::function(Function::Exponential, x()),
+        ]));
+        // x sin(3x)
+        assert_antiderivative(&Expression::Product(vec![
+            x(),
+            Expression::function(Function::Sine, Expression::monomial(3, 'x', 1)),
+        ]));
+        // x / (x^2 + 1)
+        assert_antiderivative(&divide(
+            x(),
+            Expression::binary(
+                BinaryOperationType::Addition,
+                Expression::monomial(1, 'x', 2),
+                constant(1),
+            ),
+        ));
+        // cos(x) e^sin(x)
+        assert_antiderivative(&Expression::Product(vec![
+            Expression::function(Function::Cosine, x()),
+            Expression::function(
+                Function::Exponential,
+                Expression::function(Function::Sine, x()),
+            ),
+        ]));
+        // x sqrt(x^2 + 1)
+        assert_antiderivative(&Expression::Product(vec![
+            x(),
+            Expression::binary(
+                BinaryOperationType::Power,
+                Expression::binary(
+                    BinaryOperationType::Addition,
+                    Expression::monomial(1, 'x', 2),
+                    constant(1),
+                ),
+                constant(Rational::new(1, 2)),
+            ),
+        ]));
+    }
+
+    #[test]
+    fn test_cannot_integrate() {
+        let integrand =
+            Expression::function(Function::Exponential, Expression::monomial(1, 'x', 2));
+        assert_eq!(
+            integrand.integrate('x'),
+            Err(IntegrationError::CannotIntegrate {
+                integrand: integrand.clone(),
+                variable: 'x',
+            })
+        );
+    }
+
+    #[test]
+    fn test_coefficient_overflow() {
+        let integrand = Expression::monomial(Rational::new(1, i64::MAX), 'x', 1);
+        assert_eq!(
+            integrand.integrate('x'),
+            Err(IntegrationError::CannotIntegrate {
+                integrand: integrand.clone(),
+                variable: 'x',
+            })
+        );
+    }
+
+    #[test]
+    fn test_high_powers_of_sines() {
+        let sine = Expression::function(Function::Sine, x());
+        let start = std::time::Instant::now();
+        assert_antiderivative(&power(sine.clone(), 40));
+        // the reduction coefficients of sin(x)^400 overflow long before the end
+        assert!(power(sine, 400).integrate('x').is_err());
+        assert!(start.elapsed() < std::time::Duration::from_secs(2));
+    }
+
+    #[test]
+    fn test_integrate_between() {
+        let pi = Expression::NamedConstant("pi".to_string());
+        let integral = Expression::function(Function::Sine, x())
+            .integrate_between('x', &constant(0), &pi)
+            .unwrap();
+        assert!((integral.evaluate(&HashMap::new()).unwrap() - 2.0).abs() < 1e-12);
+        let integral = Expression::monomial(1, 'x', 2)
+            .integrate_between('x', &constant(1), &constant(2))
+            .unwrap();
+        assert!(same(&integral, &constant(Rational::new(7, 3))));
+        // area of the sphere of radius r, with area element r^2 sin(u)
+        let area_element = Expression::Product(vec![
+            Expression::Monomial(Monomial {
+                factor: 1.into(),
+                variable: 'r',
+                power: 2,
+            }),
+            Expression::function(Function::Sine, x()),
+        ]);
+        let area = area_element
+            .integrate_between('x', &constant(0), &pi)
+            .unwrap()
+            .integrate_between(
+                'y',
+                &constant(0),
+                &Expression::binary(BinaryOperationType::Multiplication, constant(2), pi),
+            )
+            .unwrap();
+        let values = HashMap::from([('r', 1.5)]);
+        let expected = 4. * std::f64::consts::PI * 1.5 * 1.5;
+        assert!((area.evaluate(&values).unwrap() - expected).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_improper_integrals() {
+        let reciprocal = power(x(), -1);
+        assert_eq!(
+            reciprocal.integrate_between('x', &constant(-1), &constant(1)),
+            Err(IntegrationError::Improper {
+                integrand: reciprocal.clone(),
+                variable: 'x',
+            })
+        );
+        let integral = reciprocal
+            .integrate_between('x', &constant(1), &constant(2))
+            .unwrap();
+        let expected = std::f64::consts::LN_2;
+        assert!((integral.evaluate(&HashMap::new()).unwrap() - expected).abs() < 1e-12);
+        // 1 / x^2 has the antiderivative -1 / x, which jumps at 0 as well
+        let inverse_square = power(x(), -2);
+        assert!(matches!(
+            inverse_square.integrate_between('x', &constant(2), &constant(-1)),
+            Err(IntegrationError::Improper { .. })
+        ));
+        // 1 / (x - 4) between 0 and pi
+        let shifted = divide(
+            constant(1),
+            Expression::binary(BinaryOperationType::Substraction, x(), constant(4)),
+        );
+        let pi = Expression::NamedConstant("pi".to_string());
+        assert!(shifted.integrate_between('x', &constant(0), &pi).is_ok());
+        let beyond_four = Expression::binary(BinaryOperationType::Addition, pi, constant(1));
+        assert!(matches!(
+            shifted.integrate_between('x', &constant(0), &beyond_four),
+            Err(IntegrationError::Improper { .. })
+        ));
+    }
+}
@@ -1,5 +1,23 @@
+pub mod assumptions;
+pub mod autodiff;
+pub mod calculus;
+pub mod canonical;
+pub mod codegen;
+pub mod compilable;
+pub mod dag;
 pub mod derivable;
+pub mod dot;
+pub mod egraph;
+pub mod evaluable;
+pub mod expandable;
 pub mod expression;
+pub mod integrable;
+pub mod matrix;
 pub mod parser;
+pub mod polynomial;
+pub mod quadrature;
+pub mod rewrite;
 pub mod simplifiable;
 pub mod surface;
+pub mod surfaces;
+pub mod trigonometric;
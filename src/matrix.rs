@@ -0,0 +1,473 @@
+use std::collections::HashMap;
+use std::ops::{Add, Index, Mul};
+
+use ndarray::Array2;
+
+use crate::canonical::Canonical;
+use crate::evaluable::{Evaluable, EvaluationError};
+use crate::expandable::Expandable;
+use crate::expression::{BinaryOperationType, Expression, Variable};
+use crate::simplifiable::{Simplifiable, SimplificationOptions};
+
+/// Matrix of expressions, stored row by row.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SymbolicMatrix {
+    rows: usize,
+    columns: usize,
+    entries: Vec<Expression>,
+}
+
+impl SymbolicMatrix {
+    /// # Panics
+    ///
+    /// Panics if there are not `rows * columns` entries.
+    pub fn new(rows: usize, columns: usize, entries: Vec<Expression>) -> SymbolicMatrix {
+        assert_eq!(entries.len(), rows * columns, "wrong number of entries");
+        SymbolicMatrix {
+            rows,
+            columns,
+            entries,
+        }
+    }
+
+    /// # Panics
+    ///
+    /// Panics if the rows do not all have the same length.
+    pub fn from_rows(rows: Vec<Vec<Expression>>) -> SymbolicMatrix {
+        let columns = rows.first().map_or(0, Vec::len);
+        assert!(
+            rows.iter().all(|row| row.len() == columns),
+            "rows of different lengths"
+        );
+        SymbolicMatrix {
+            rows: rows.len(),
+            columns,
+            entries: rows.into_iter().flatten().collect(),
+        }
+    }
+
+    pub fn from_fn<F: FnMut(usize, usize) -> Expression>(
+        rows: usize,
+        columns: usize,
+        mut f: F,
+    ) -> SymbolicMatrix {
+        SymbolicMatrix {
+            rows,
+            columns,
+            entries: (0..rows)
+                .flat_map(|row| (0..columns).map(move |column| (row, column)))
+                .map(|(row, column)| f(row, column))
+                .collect(),
+        }
+    }
+
+    pub fn identity(size: usize) -> SymbolicMatrix {
+        SymbolicMatrix::from_fn(size, size, |row, column| {
+            Expression::Constant(i64::from(row == column).into())
+        })
+    }
+
+    pub fn column_vector(entries: Vec<Expression>) -> SymbolicMatrix {
+        SymbolicMatrix::new(entries.len(), 1, entries)
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    pub fn is_square(&self) -> bool {
+        self.rows == self.columns
+    }
+
+    pub fn get(&self, row: usize, column: usize) -> Option<&Expression> {
+        if row < self.rows && column < self.columns {
+            self.entries.get(row * self.columns + column)
+        } else {
+            None
+        }
+    }
+
+    pub fn row(&self, row: usize) -> &[Expression] {
+        &self.entries[row * self.columns..(row + 1) * self.columns]
+    }
+
+    pub fn entries(&self) -> &[Expression] {
+        &self.entries
+    }
+
+    pub fn map<F: Fn(&Expression) -> Expression>(&self, f: F) -> SymbolicMatrix {
+        SymbolicMatrix {
+            rows: self.rows,
+            columns: self.columns,
+            entries: self.entries.iter().map(f).collect(),
+        }
+    }
+
+    pub fn transpose(&self) -> SymbolicMatrix {
+        SymbolicMatrix::from_fn(self.columns, self.rows, |row, column| {
+            self[(column, row)].clone()
+        })
+    }
+
+    /// # Panics
+    ///
+    /// Panics if the matrix is not square, as do the other square matrix operations.
+    pub fn trace(&self) -> Expression {
+        assert!(self.is_square(), "trace of a non-square matrix");
+        (0..self.rows)
+            .map(|i| self[(i, i)].clone())
+            .reduce(|trace, entry| Expression::binary(BinaryOperationType::Addition, trace, entry))
+            .unwrap_or(Expression::Constant(0.into()))
+            .simplify_expression()
+    }
+
+    /// The matrix without `row` and `column`.
+    pub fn minor(&self, row: usize, column: usize) -> SymbolicMatrix {
+        SymbolicMatrix::from_fn(self.rows - 1, self.columns - 1, |i, j| {
+            self[(i + usize::from(i >= row), j + usize::from(j >= column))].clone()
+        })
+    }
+
+    /// Cofactor expansion up to 3x3, fraction-free (Bareiss) elimination for larger matrices.
+    pub fn determinant(&self) -> Expression {
+        assert!(self.is_square(), "determinant of a non-square matrix");
+        if self.rows <= 3 {
+            self.cofactor_determinant()
+        } else {
+            self.bareiss_determinant()
+        }
+    }
+
+    fn cofactor_determinant(&self) -> Expression {
+        if self.rows == 0 {
+            return Expression::Constant(1.into());
+        }
+        if self.rows == 1 {
+            return self[(0, 0)].clone();
+        }
+        (0..self.columns)
+            .map(|column| {
+                Expression::binary(
+                    BinaryOperationType::Multiplication,
+                    self[(0, column)].clone(),
+                    self.minor(0, column).cofactor_determinant(),
+                )
+            })
+            .enumerate()
+            .reduce(|(_, determinant), (column, term)| {
+                let operation_type = if column % 2 == 0 {
+                    BinaryOperationType::Addition
+                } else {
+                    BinaryOperationType::Substraction
+                };
+                (
+                    column,
+                    Expression::binary(operation_type, determinant, term),
+                )
+            })
+            .map(|(_, determinant)| determinant.simplify_expression())
+            .expect("matrix is not empty")
+    }
+
+    /// Each elimination step divides exactly by the previous pivot, which keeps the entries
+    /// polynomial in the entries of the matrix.
+    fn bareiss_determinant(&self) -> Expression {
+        let size = self.rows;
+        let mut rows: Vec<Vec<Expression>> = (0..size).map(|row| self.row(row).to_vec()).collect();
+        let mut negated = false;
+        let mut previous_pivot = Expression::Constant(1.into());
+        for k in 0..size - 1 {
+            if is_zero(&rows[k][k]) {
+                let Some(pivot_row) = (k + 1..size).find(|row| !is_zero(&rows[*row][k])) else {
+                    return Expression::Constant(0.into());
+                };
+                rows.swap(k, pivot_row);
+                negated = !negated;
+            }
+            for i in k + 1..size {
+                for j in k + 1..size {
+                    let cross = Expression::binary(
+                        BinaryOperationType::Substraction,
+                        Expression::binary(
+                            BinaryOperationType::Multiplication,
+                            rows[k][k].clone(),
+                            rows[i][j].clone(),
+                        ),
+                        Expression::binary(
+                            BinaryOperationType::Multiplication,
+                            rows[i][k].clone(),
+                            rows[k][j].clone(),
+                        ),
+                    );
+                    rows[i][j] = Expression::binary(
+                        BinaryOperationType::Division,
+                        cross,
+                        previous_pivot.clone(),
+                    )
+                    .cancel()
+                    .simplify_expression();
+                }
+            }
+            previous_pivot = rows[k][k].clone();
+        }
+        let determinant = rows[size - 1][size - 1].clone();
+        if negated {
+            Expression::binary(
+                BinaryOperationType::Multiplication,
+                Expression::Constant((-1).into()),
+                determinant,
+            )
+            .simplify_expression()
+        } else {
+            determinant
+        }
+    }
+
+    /// Transposed matrix of cofactors, `A adj(A) = det(A) I`.
+    pub fn adjugate(&self) -> SymbolicMatrix {
+        assert!(self.is_square(), "adjugate of a non-square matrix");
+        if self.rows == 1 {
+            return SymbolicMatrix::identity(1);
+        }
+        SymbolicMatrix::from_fn(self.rows, self.columns, |row, column| {
+            let minor = self.minor(column, row).determinant();
+            if (row + column) % 2 == 0 {
+                minor
+            } else {
+                Expression::binary(
+                    BinaryOperationType::Multiplication,
+                    Expression::Constant((-1).into()),
+                    minor,
+                )
+                .simplify_expression()
+            }
+        })
+    }
+
+    /// `adj(A) / det(A)`, `None` when the determinant simplifies to zero.
+    pub fn inverse(&self) -> Option<SymbolicMatrix> {
+        let determinant = self.determinant();
+        if is_zero(&determinant) {
+            return None;
+        }
+        Some(self.adjugate().map(|entry| {
+            Expression::binary(
+                BinaryOperationType::Division,
+                entry.clone(),
+                determinant.clone(),
+            )
+            .cancel()
+            .simplify_expression()
+        }))
+    }
+
+    pub fn simplify(&self) -> SymbolicMatrix {
+        self.map(Simplifiable::simplify_expression)
+    }
+
+    pub fn simplify_with(&self, options: &SimplificationOptions) -> SymbolicMatrix {
+        self.map(|entry| entry.simplify_with(options).expression)
+    }
+
+    pub fn evaluate(
+        &self,
+        values: &HashMap<Variable, f64>,
+    ) -> Result<Array2<f64>, EvaluationError> {
+        let entries = self
+            .entries
+            .iter()
+            .map(|entry| entry.evaluate(values))
+            .collect::<Result<Vec<f64>, EvaluationError>>()?;
+        Ok(Array2::from_shape_vec((self.rows, self.columns), entries)
+            .expect("entries match the shape"))
+    }
+}
+
+/// # Panics
+///
+/// Panics if the matrices do not have the same shape.
+impl Add for &SymbolicMatrix {
+    type Output = SymbolicMatrix;
+
+    fn add(self, other: &SymbolicMatrix) -> SymbolicMatrix {
+        assert_eq!(
+            (self.rows, self.columns),
+            (other.rows, other.columns),
+            "adding matrices of different shapes"
+        );
+        SymbolicMatrix {
+            rows: self.rows,
+            columns: self.columns,
+            entries: self
+                .entries
+                .iter()
+                .zip(&other.entries)
+                .map(|(left, right)| {
+                    Expression::binary(BinaryOperationType::Addition, left.clone(), right.clone())
+                        .simplify_expression()
+                })
+                .collect(),
+        }
+    }
+}
+
+/// # Panics
+///
+/// Panics if the columns of `self` do not match the rows of `other`.
+impl Mul for &SymbolicMatrix {
+    type Output = SymbolicMatrix;
+
+    fn mul(self, other: &SymbolicMatrix) -> SymbolicMatrix {
+        assert_eq!(
+            self.columns, other.rows,
+            "multiplying incompatible matrices"
+        );
+        SymbolicMatrix::from_fn(self.rows, other.columns, |row, column| {
+            (0..self.columns)
+                .map(|k| {
+                    Expression::binary(
+                        BinaryOperationType::Multiplication,
+                        self[(row, k)].clone(),
+                        other[(k, column)].clone(),
+                    )
+                })
+                .reduce(|sum, term| Expression::binary(BinaryOperationType::Addition, sum, term))
+                .unwrap_or(Expression::Constant(0.into()))
+                .simplify_expression()
+        })
+    }
+}
+
+impl Index<(usize, usize)> for SymbolicMatrix {
+    type Output = Expression;
+
+    fn index(&self, (row, column): (usize, usize)) -> &Expression {
+        self.get(row, column).expect("index out of bounds")
+    }
+}
+
+fn is_zero(expression: &Expression) -> bool {
+    expression.to_canonical_form() == Expression::Constant(0.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ndarray::array;
+
+    use crate::expression::{Function, Rational};
+
+    fn constant(value: i64) -> Expression {
+        Expression::Constant(value.into())
+    }
+
+    fn numeric(rows: Vec<Vec<i64>>) -> SymbolicMatrix {
+        SymbolicMatrix::from_rows(
+            rows.into_iter()
+                .map(|row| row.into_iter().map(constant).collect())
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let a = numeric(vec![vec![1, 2], vec![3, 4]]);
+        let b = SymbolicMatrix::from_rows(vec![
+            vec![Expression::monomial(1, 'x', 1), constant(0)],
+            vec![constant(1), Expression::monomial(2, 'y', 1)],
+        ]);
+        let values = HashMap::from([('x', 2.), ('y', 3.)]);
+        assert_eq!((&a + &b).evaluate(&values), Ok(array![[3., 2.], [4., 10.]]));
+        assert_eq!(
+            (&a * &b).evaluate(&values),
+            Ok(array![[4., 12.], [10., 24.]])
+        );
+        assert_eq!(a.transpose(), numeric(vec![vec![1, 3], vec![2, 4]]));
+        assert_eq!(b.trace().evaluate(&values), Ok(8.));
+        assert_eq!(&a * &SymbolicMatrix::identity(2), a);
+    }
+
+    #[test]
+    fn test_determinant_by_elimination_matches_cofactors() {
+        let a = numeric(vec![
+            vec![0, 2, 1, 3],
+            vec![1, 0, 2, 1],
+            vec![2, 1, 0, 4],
+            vec![1, 3, 1, 0],
+        ]);
+        assert_eq!(a.determinant(), a.cofactor_determinant());
+        assert_eq!(a.determinant(), constant(-52));
+
+        // x on the diagonal, 1 elsewhere: (x - 1)^3 (x + 3)
+        let b = SymbolicMatrix::from_fn(4, 4, |row, column| {
+            if row == column {
+                Expression::monomial(1, 'x', 1)
+            } else {
+                constant(1)
+            }
+        });
+        assert_eq!(
+            b.determinant().to_canonical_form(),
+            b.cofactor_determinant().to_canonical_form()
+        );
+        let values = HashMap::from([('x', 3.)]);
+        assert_eq!(b.determinant().evaluate(&values), Ok(48.));
+
+        let singular = numeric(vec![
+            vec![1, 2, 3, 4],
+            vec![2, 4, 6, 8],
+            vec![0, 1, 0, 1],
+            vec![1, 0, 1, 0],
+        ]);
+        assert_eq!(singular.determinant(), constant(0));
+        assert_eq!(singular.inverse(), None);
+    }
+
+    #[test]
+    fn test_inverse() {
+        // rotation by t
+        let cos = Expression::Function {
+            function: Function::Cosine,
+            expression: Box::new(Expression::monomial(1, 't', 1)),
+        };
+        let sin = Expression::Function {
+            function: Function::Sine,
+            expression: Box::new(Expression::monomial(1, 't', 1)),
+        };
+        let rotation = SymbolicMatrix::from_rows(vec![
+            vec![
+                cos.clone(),
+                Expression::binary(
+                    BinaryOperationType::Multiplication,
+                    constant(-1),
+                    sin.clone(),
+                ),
+            ],
+            vec![sin, cos],
+        ]);
+        let inverse = rotation.inverse().unwrap();
+        let values = HashMap::from([('t', 0.3)]);
+        let product = (&rotation * &inverse).evaluate(&values).unwrap();
+        for ((row, column), value) in product.indexed_iter() {
+            assert!((value - f64::from(u8::from(row == column))).abs() < 1e-12);
+        }
+
+        let a = numeric(vec![vec![2, 1], vec![1, 1]]);
+        assert_eq!(
+            a.adjugate(),
+            SymbolicMatrix::from_rows(vec![
+                vec![constant(1), constant(-1)],
+                vec![constant(-1), constant(2)],
+            ])
+        );
+        let half = numeric(vec![vec![2, 0], vec![0, 4]]).inverse().unwrap();
+        assert_eq!(half[(0, 0)], Expression::Constant(Rational::new(1, 2)));
+        assert_eq!(half[(1, 1)], Expression::Constant(Rational::new(1, 4)));
+    }
+}
@@ -0,0 +1,787 @@
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::ops::{Add, Mul, Neg, Sub};
+
+use num_traits::{CheckedAdd, CheckedDiv, CheckedMul, CheckedSub, One, Signed, Zero};
+
+use crate::expression::{
+    BinaryOperation, BinaryOperationType, Expression, Monomial, Rational, Variable,
+};
+
+/// Product of variables raised to positive powers, e.g. `x*y^2`.
+///
+/// Power products are ordered lexicographically, the variable with the smallest
+/// character being the most significant one, which makes it a monomial order.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct PowerProduct(BTreeMap<Variable, u32>);
+
+impl PowerProduct {
+    pub fn one() -> PowerProduct {
+        PowerProduct(BTreeMap::new())
+    }
+
+    pub fn variable(variable: Variable, power: u32) -> PowerProduct {
+        let mut powers = BTreeMap::new();
+        if power > 0 {
+            powers.insert(variable, power);
+        }
+        PowerProduct(powers)
+    }
+
+    pub fn is_one(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn power_of(&self, variable: Variable) -> u32 {
+        self.0.get(&variable).copied().unwrap_or(0)
+    }
+
+    pub fn degree(&self) -> u32 {
+        self.0.values().sum()
+    }
+
+    pub fn powers(&self) -> impl Iterator<Item = (Variable, u32)> + '_ {
+        self.0.iter().map(|(variable, power)| (*variable, *power))
+    }
+
+    pub fn divides(&self, other: &PowerProduct) -> bool {
+        self.powers()
+            .all(|(variable, power)| other.power_of(variable) >= power)
+    }
+
+    /// Returns the power product with `variable` removed.
+    pub fn without(&self, variable: Variable) -> PowerProduct {
+        let mut powers = self.0.clone();
+        powers.remove(&variable);
+        PowerProduct(powers)
+    }
+
+    /// `self * other`, or `None` when a power overflows.
+    pub fn checked_mul(&self, other: &PowerProduct) -> Option<PowerProduct> {
+        let mut powers = self.0.clone();
+        for (variable, power) in other.powers() {
+            let sum = powers.entry(variable).or_insert(0);
+            *sum = sum.checked_add(power)?;
+        }
+        Some(PowerProduct(powers))
+    }
+
+    /// `self^exponent`, or `None` when a power overflows.
+    pub fn checked_pow(&self, exponent: u32) -> Option<PowerProduct> {
+        if exponent == 0 {
+            return Some(PowerProduct::one());
+        }
+        self.powers()
+            .map(|(variable, power)| Some((variable, power.checked_mul(exponent)?)))
+            .collect::<Option<_>>()
+            .map(PowerProduct)
+    }
+
+    /// Returns `self / other`, or `None` if `other` does not divide `self`.
+    pub fn checked_div(&self, other: &PowerProduct) -> Option<PowerProduct> {
+        if !other.divides(self) {
+            return None;
+        }
+        let mut powers = self.0.clone();
+        for (variable, power) in other.powers() {
+            let remaining = powers[&variable] - power;
+            if remaining == 0 {
+                powers.remove(&variable);
+            } else {
+                powers.insert(variable, remaining);
+            }
+        }
+        Some(PowerProduct(powers))
+    }
+}
+
+impl Mul for &PowerProduct {
+    type Output = PowerProduct;
+
+    // Multiplying power products adds up the powers of each variable.
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn mul(self, other: &PowerProduct) -> PowerProduct {
+        let mut powers = self.0.clone();
+        for (variable, power) in other.powers() {
+            *powers.entry(variable).or_insert(0) += power;
+        }
+        PowerProduct(powers)
+    }
+}
+
+impl Ord for PowerProduct {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let variables: BTreeSet<Variable> = self.0.keys().chain(other.0.keys()).copied().collect();
+        variables
+            .into_iter()
+            .map(|variable| self.power_of(variable).cmp(&other.power_of(variable)))
+            .find(|ordering| *ordering != Ordering::Equal)
+            .unwrap_or(Ordering::Equal)
+    }
+}
+
+impl PartialOrd for PowerProduct {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PolynomialConversionError {
+    NonPolynomialSubexpression(Expression),
+    DivisionByZero,
+    /// A coefficient does not fit in a [`Rational`].
+    Overflow,
+}
+
+/// Sparse multivariate polynomial with exact rational coefficients.
+///
+/// Terms with a zero coefficient are never stored, so like terms are always collected. The
+/// arithmetic operators panic when a coefficient overflows, as integer arithmetic does; the
+/// `checked_*` methods and the algorithms built on them return `None` instead.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Polynomial {
+    terms: BTreeMap<PowerProduct, Rational>,
+}
+
+impl Polynomial {
+    pub fn zero() -> Polynomial {
+        Polynomial::default()
+    }
+
+    pub fn constant(value: Rational) -> Polynomial {
+        Polynomial::term(value, PowerProduct::one())
+    }
+
+    pub fn variable(variable: Variable) -> Polynomial {
+        Polynomial::term(Rational::one(), PowerProduct::variable(variable, 1))
+    }
+
+    pub fn term(coefficient: Rational, power_product: PowerProduct) -> Polynomial {
+        let mut polynomial = Polynomial::zero();
+        if !coefficient.is_zero() {
+            polynomial.terms.insert(power_product, coefficient);
+        }
+        polynomial
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    pub fn is_constant(&self) -> bool {
+        self.terms.keys().all(PowerProduct::is_one)
+    }
+
+    /// Returns the constant term, which is the value of a constant polynomial.
+    pub fn constant_term(&self) -> Rational {
+        self.coefficient(&PowerProduct::one())
+    }
+
+    pub fn coefficient(&self, power_product: &PowerProduct) -> Rational {
+        self.terms
+            .get(power_product)
+            .copied()
+            .unwrap_or_else(Rational::zero)
+    }
+
+    /// Iterates over the terms, from the smallest to the largest power product.
+    pub fn terms(&self) -> impl DoubleEndedIterator<Item = (&PowerProduct, &Rational)> {
+        self.terms.iter()
+    }
+
+    /// Returns the term with the largest power product, if the polynomial is not zero.
+    pub fn leading_term(&self) -> Option<(&PowerProduct, &Rational)> {
+        self.terms.iter().next_back()
+    }
+
+    pub fn variables(&self) -> HashSet<Variable> {
+        self.terms
+            .keys()
+            .flat_map(|power_product| power_product.powers().map(|(variable, _)| variable))
+            .collect()
+    }
+
+    /// Total degree of the polynomial, `None` for the zero polynomial.
+    pub fn degree(&self) -> Option<u32> {
+        self.terms.keys().map(PowerProduct::degree).max()
+    }
+
+    pub fn degree_in(&self, variable: Variable) -> Option<u32> {
+        self.terms
+            .keys()
+            .map(|power_product| power_product.power_of(variable))
+            .max()
+    }
+
+    pub fn checked_add(&self, other: &Polynomial) -> Option<Polynomial> {
+        let mut sum = self.clone();
+        for (power_product, coefficient) in other.terms() {
+            sum.add_term(*coefficient, power_product.clone())?;
+        }
+        Some(sum)
+    }
+
+    pub fn checked_sub(&self, other: &Polynomial) -> Option<Polynomial> {
+        let mut difference = self.clone();
+        for (power_product, coefficient) in other.terms() {
+            difference.add_term(
+                Rational::zero().checked_sub(coefficient)?,
+                power_product.clone(),
+            )?;
+        }
+        Some(difference)
+    }
+
+    pub fn checked_neg(&self) -> Option<Polynomial> {
+        Polynomial::zero().checked_sub(self)
+    }
+
+    pub fn checked_mul(&self, other: &Polynomial) -> Option<Polynomial> {
+        let mut product = Polynomial::zero();
+        for (left_power_product, left_coefficient) in self.terms() {
+            for (right_power_product, right_coefficient) in other.terms() {
+                product.add_term(
+                    left_coefficient.checked_mul(right_coefficient)?,
+                    left_power_product * right_power_product,
+                )?;
+            }
+        }
+        Some(product)
+    }
+
+    /// `self^exponent` by repeated squaring, `None` when a coefficient overflows.
+    pub fn checked_pow(&self, exponent: u32) -> Option<Polynomial> {
+        let mut power = Polynomial::constant(Rational::one());
+        let mut square = self.clone();
+        let mut exponent = exponent;
+        while exponent > 0 {
+            if exponent % 2 == 1 {
+                power = power.checked_mul(&square)?;
+            }
+            exponent /= 2;
+            if exponent > 0 {
+                square = square.checked_mul(&square)?;
+            }
+        }
+        Some(power)
+    }
+
+    /// # Panics
+    ///
+    /// Panics if a coefficient overflows, see [`Polynomial::checked_pow`].
+    pub fn pow(&self, exponent: u32) -> Polynomial {
+        self.checked_pow(exponent)
+            .expect("polynomial coefficient overflow")
+    }
+
+    /// `checked_div_rem`, panicking when a coefficient overflows.
+    pub fn div_rem(&self, divisor: &Polynomial) -> (Polynomial, Polynomial) {
+        self.checked_div_rem(divisor)
+            .expect("polynomial coefficient overflow")
+    }
+
+    /// Divides `self` by `divisor`, returning the quotient and the remainder, or `None` when a
+    /// coefficient overflows.
+    ///
+    /// No term of the remainder is divisible by the leading power product of `divisor`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `divisor` is the zero polynomial.
+    pub fn checked_div_rem(&self, divisor: &Polynomial) -> Option<(Polynomial, Polynomial)> {
+        let (divisor_power_product, divisor_coefficient) = divisor
+            .leading_term()
+            .expect("division of a polynomial by zero");
+        let mut quotient = Polynomial::zero();
+        let mut remainder = Polynomial::zero();
+        let mut dividend = self.clone();
+        while let Some((power_product, coefficient)) = dividend.leading_term() {
+            let coefficient = *coefficient;
+            match power_product.checked_div(divisor_power_product) {
+                Some(factor) => {
+                    let term =
+                        Polynomial::term(coefficient.checked_div(divisor_coefficient)?, factor);
+                    dividend = dividend.checked_sub(&term.checked_mul(divisor)?)?;
+                    quotient = quotient.checked_add(&term)?;
+                }
+                None => {
+                    let term = Polynomial::term(coefficient, power_product.clone());
+                    dividend = dividend.checked_sub(&term)?;
+                    remainder = remainder.checked_add(&term)?;
+                }
+            }
+        }
+        Some((quotient, remainder))
+    }
+
+    /// Greatest common divisor, normalized so that its leading coefficient is one, or `None`
+    /// when a coefficient overflows.
+    ///
+    /// Multivariate polynomials are handled recursively, as univariate polynomials in their
+    /// most significant variable whose coefficients are polynomials in the other variables.
+    pub fn gcd(&self, other: &Polynomial) -> Option<Polynomial> {
+        if self.is_zero() {
+            return other.monic();
+        }
+        if other.is_zero() {
+            return self.monic();
+        }
+        let variable = match self.variables().union(&other.variables()).min() {
+            Some(variable) => *variable,
+            None => return Some(Polynomial::constant(Rational::one())),
+        };
+        let content = self
+            .content_in(variable)?
+            .gcd(&other.content_in(variable)?)?;
+        let (mut a, mut b) = (
+            self.primitive_part_in(variable)?,
+            other.primitive_part_in(variable)?,
+        );
+        if a.degree_in(variable) < b.degree_in(variable) {
+            std::mem::swap(&mut a, &mut b);
+        }
+        let primitive_gcd = loop {
+            if b.is_zero() {
+                break a;
+            }
+            if b.degree_in(variable) == Some(0) {
+                break Polynomial::constant(Rational::one());
+            }
+            let remainder = a.pseudo_remainder(&b, variable)?;
+            a = b;
+            b = remainder.primitive_part_in(variable)?;
+        };
+        content
+            .checked_mul(&primitive_gcd.primitive_part_in(variable)?)?
+            .monic()
+    }
+
+    /// Divides by the leading coefficient, leaving the zero polynomial unchanged.
+    pub fn monic(&self) -> Option<Polynomial> {
+        match self.leading_term() {
+            Some((_, coefficient)) => self.checked_mul(&Polynomial::constant(coefficient.recip())),
+            None => Some(Polynomial::zero()),
+        }
+    }
+
+    /// Coefficient of `variable^power`, as a polynomial in the other variables.
+    pub fn coefficient_in(&self, variable: Variable, power: u32) -> Polynomial {
+        let mut coefficient = Polynomial::zero();
+        for (power_product, value) in self.terms() {
+            if power_product.power_of(variable) == power {
+                // distinct power products with the same power of `variable` stay distinct
+                coefficient
+                    .terms
+                    .insert(power_product.without(variable), *value);
+            }
+        }
+        coefficient
+    }
+
+    /// Greatest common divisor of the coefficients of `self` seen as a polynomial in `variable`.
+    pub fn content_in(&self, variable: Variable) -> Option<Polynomial> {
+        let powers: BTreeSet<u32> = self
+            .terms
+            .keys()
+            .map(|power_product| power_product.power_of(variable))
+            .collect();
+        powers
+            .into_iter()
+            .try_fold(Polynomial::zero(), |content, power| {
+                content.gcd(&self.coefficient_in(variable, power))
+            })
+    }
+
+    pub fn primitive_part_in(&self, variable: Variable) -> Option<Polynomial> {
+        if self.is_zero() {
+            return Some(Polynomial::zero());
+        }
+        Some(self.checked_div_rem(&self.content_in(variable)?)?.0)
+    }
+
+    /// Remainder of the division of `lc^(deg(self) - deg(divisor) + 1) * self` by `divisor`,
+    /// where `lc` is the leading coefficient of `divisor` in `variable`.
+    fn pseudo_remainder(&self, divisor: &Polynomial, variable: Variable) -> Option<Polynomial> {
+        let divisor_degree = divisor.degree_in(variable).unwrap_or(0);
+        let divisor_leading_coefficient = divisor.coefficient_in(variable, divisor_degree);
+        let mut remainder = self.clone();
+        while let Some(degree) = remainder.degree_in(variable) {
+            if degree < divisor_degree {
+                break;
+            }
+            let shift = Polynomial::term(
+                Rational::one(),
+                PowerProduct::variable(variable, degree - divisor_degree),
+            );
+            let leading_coefficient = remainder.coefficient_in(variable, degree);
+            remainder = divisor_leading_coefficient
+                .checked_mul(&remainder)?
+                .checked_sub(
+                    &leading_coefficient
+                        .checked_mul(&shift)?
+                        .checked_mul(divisor)?,
+                )?;
+        }
+        Some(remainder)
+    }
+
+    /// Returns the value of a constant polynomial if it is a natural number.
+    fn to_natural_number(&self) -> Option<u32> {
+        let value = self.constant_term();
+        if self.is_constant() && value.is_integer() && !value.is_negative() {
+            u32::try_from(value.to_integer()).ok()
+        } else {
+            None
+        }
+    }
+
+    pub fn to_expression(&self) -> Expression {
+        let mut terms = self.terms.iter().rev();
+        let first = match terms.next() {
+            Some((power_product, coefficient)) => term_to_expression(*coefficient, power_product),
+            None => return Expression::Constant(0.into()),
+        };
+        terms.fold(first, |acc, (power_product, coefficient)| {
+            let (operation, coefficient) = if coefficient.is_negative() {
+                (BinaryOperationType::Substraction, -coefficient)
+            } else {
+                (BinaryOperationType::Addition, *coefficient)
+            };
+            Expression::BinaryOperation(BinaryOperation {
+                operation,
+                left_value: Box::new(acc),
+                right_value: Box::new(term_to_expression(coefficient, power_product)),
+            })
+        })
+    }
+
+    /// `None` when the coefficient of `power_product` overflows.
+    fn add_term(&mut self, coefficient: Rational, power_product: PowerProduct) -> Option<()> {
+        if coefficient.is_zero() {
+            return Some(());
+        }
+        let sum = self.coefficient(&power_product).checked_add(&coefficient)?;
+        if sum.is_zero() {
+            self.terms.remove(&power_product);
+        } else {
+            self.terms.insert(power_product, sum);
+        }
+        Some(())
+    }
+}
+
+pub(crate) fn term_to_expression(
+    coefficient: Rational,
+    power_product: &PowerProduct,
+) -> Expression {
+    let mut factors = power_product
+        .powers()
+        .map(|(variable, power)| match u8::try_from(power) {
+            Ok(power) => Expression::monomial(1, variable, power),
+            // Monomial powers are stored on a u8, larger powers are kept as power nodes.
+            Err(_) => Expression::binary(
+                BinaryOperationType::Power,
+                Expression::monomial(1, variable, 1),
+                Expression::Constant(i64::from(power).into()),
+            ),
+        });
+    let first = match factors.next() {
+        Some(Expression::Monomial(m)) => Expression::Monomial(Monomial {
+            factor: coefficient,
+            ..m
+        }),
+        Some(factor) if coefficient.is_one() => factor,
+        Some(factor) => Expression::binary(
+            BinaryOperationType::Multiplication,
+            Expression::Constant(coefficient),
+            factor,
+        ),
+        None => return Expression::Constant(coefficient),
+    };
+    factors.fold(first, |acc, factor| {
+        Expression::binary(BinaryOperationType::Multiplication, acc, factor)
+    })
+}
+
+impl TryFrom<&Expression> for Polynomial {
+    type Error = PolynomialConversionError;
+
+    fn try_from(expression: &Expression) -> Result<Polynomial, PolynomialConversionError> {
+        use PolynomialConversionError::Overflow;
+        match expression {
+            Expression::Constant(value) => Ok(Polynomial::constant(*value)),
+            Expression::Monomial(m) => Ok(Polynomial::term(
+                m.factor,
+                PowerProduct::variable(m.variable, m.power as u32),
+            )),
+            Expression::BinaryOperation(operation) => {
+                let left = Polynomial::try_from(&*operation.left_value)?;
+                let right = Polynomial::try_from(&*operation.right_value)?;
+                match operation.operation {
+                    BinaryOperationType::Addition => left.checked_add(&right).ok_or(Overflow),
+                    BinaryOperationType::Substraction => left.checked_sub(&right).ok_or(Overflow),
+                    BinaryOperationType::Multiplication => left.checked_mul(&right).ok_or(Overflow),
+                    BinaryOperationType::Division => {
+                        if !right.is_constant() {
+                            Err(PolynomialConversionError::NonPolynomialSubexpression(
+                                expression.clone(),
+                            ))
+                        } else if right.is_zero() {
+                            Err(PolynomialConversionError::DivisionByZero)
+                        } else {
+                            left.checked_mul(&Polynomial::constant(right.constant_term().recip()))
+                                .ok_or(Overflow)
+                        }
+                    }
+                    BinaryOperationType::Power => match right.to_natural_number() {
+                        Some(exponent) => left.checked_pow(exponent).ok_or(Overflow),
+                        None => Err(PolynomialConversionError::NonPolynomialSubexpression(
+                            expression.clone(),
+                        )),
+                    },
+                }
+            }
+            Expression::Sum(terms) => terms.iter().try_fold(Polynomial::zero(), |sum, term| {
+                sum.checked_add(&Polynomial::try_from(term)?)
+                    .ok_or(Overflow)
+            }),
+            Expression::Product(factors) => {
+                factors
+                    .iter()
+                    .try_fold(Polynomial::constant(Rational::one()), |product, factor| {
+                        product
+                            .checked_mul(&Polynomial::try_from(factor)?)
+                            .ok_or(Overflow)
+                    })
+            }
+            Expression::NamedConstant(_) | Expression::Function { .. } => Err(
+                PolynomialConversionError::NonPolynomialSubexpression(expression.clone()),
+            ),
+        }
+    }
+}
+
+impl From<&Polynomial> for Expression {
+    fn from(polynomial: &Polynomial) -> Expression {
+        polynomial.to_expression()
+    }
+}
+
+impl Add for &Polynomial {
+    type Output = Polynomial;
+
+    fn add(self, other: &Polynomial) -> Polynomial {
+        self.checked_add(other)
+            .expect("polynomial coefficient overflow")
+    }
+}
+
+impl Sub for &Polynomial {
+    type Output = Polynomial;
+
+    fn sub(self, other: &Polynomial) -> Polynomial {
+        self.checked_sub(other)
+            .expect("polynomial coefficient overflow")
+    }
+}
+
+impl Neg for &Polynomial {
+    type Output = Polynomial;
+
+    fn neg(self) -> Polynomial {
+        self.checked_neg().expect("polynomial coefficient overflow")
+    }
+}
+
+impl Mul for &Polynomial {
+    type Output = Polynomial;
+
+    fn mul(self, other: &Polynomial) -> Polynomial {
+        self.checked_mul(other)
+            .expect("polynomial coefficient overflow")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn xy2() -> PowerProduct {
+        &PowerProduct::variable('x', 1) * &PowerProduct::variable('y', 2)
+    }
+
+    #[test]
+    fn test_power_product_order() {
+        let x = PowerProduct::variable('x', 1);
+        let y5 = PowerProduct::variable('y', 5);
+        assert!(x > y5);
+        assert!(xy2() > y5);
+        assert!(xy2() < PowerProduct::variable('x', 2));
+        assert!(PowerProduct::one() < y5);
+    }
+
+    #[test]
+    fn test_expansion_and_like_terms_collection() {
+        // (x + y) * (x - y) + y^2
+        let expr = Expression::binary(
+            BinaryOperationType::Addition,
+            Expression::binary(
+                BinaryOperationType::Multiplication,
+                Expression::binary(
+                    BinaryOperationType::Addition,
+                    Expression::monomial(1, 'x', 1),
+                    Expression::monomial(1, 'y', 1),
+                ),
+                Expression::binary(
+                    BinaryOperationType::Substraction,
+                    Expression::monomial(1, 'x', 1),
+                    Expression::monomial(1, 'y', 1),
+                ),
+            ),
+            Expression::monomial(1, 'y', 2),
+        );
+        let polynomial = Polynomial::try_from(&expr).unwrap();
+        assert_eq!(
+            polynomial,
+            Polynomial::term(Rational::one(), PowerProduct::variable('x', 2))
+        );
+        assert_eq!(polynomial.to_expression(), Expression::monomial(1, 'x', 2));
+    }
+
+    #[test]
+    fn test_exact_coefficients() {
+        let expr = Expression::binary(
+            BinaryOperationType::Division,
+            Expression::monomial(1, 'x', 1),
+            Expression::Constant(3.into()),
+        );
+        let polynomial = Polynomial::try_from(&expr).unwrap();
+        let tripled = &(&(&polynomial + &polynomial) + &polynomial) - &Polynomial::variable('x');
+        assert!(tripled.is_zero());
+        assert_eq!(
+            polynomial.coefficient(&PowerProduct::variable('x', 1)),
+            Rational::new(1, 3)
+        );
+    }
+
+    #[test]
+    fn test_non_polynomial_expressions_are_rejected() {
+        let sine = Expression::Function {
+            function: crate::expression::Function::Sine,
+            expression: Box::new(Expression::monomial(1, 'x', 1)),
+        };
+        assert_eq!(
+            Polynomial::try_from(&Expression::binary(
+                BinaryOperationType::Addition,
+                Expression::monomial(1, 'x', 1),
+                sine.clone()
+            )),
+            Err(PolynomialConversionError::NonPolynomialSubexpression(sine))
+        );
+        assert_eq!(
+            Polynomial::try_from(&Expression::binary(
+                BinaryOperationType::Division,
+                Expression::Constant(1.into()),
+                Expression::Constant(0.into())
+            )),
+            Err(PolynomialConversionError::DivisionByZero)
+        );
+    }
+
+    #[test]
+    fn test_multiplication() {
+        let x_plus_y = &Polynomial::variable('x') + &Polynomial::variable('y');
+        let square = x_plus_y.pow(2);
+        assert_eq!(square.degree(), Some(2));
+        assert_eq!(
+            square
+                .coefficient(&(&PowerProduct::variable('x', 1) * &PowerProduct::variable('y', 1))),
+            Rational::from_integer(2)
+        );
+        assert_eq!(&x_plus_y * &Polynomial::zero(), Polynomial::zero());
+    }
+
+    #[test]
+    fn test_gcd() {
+        let x = Polynomial::variable('x');
+        let y = Polynomial::variable('y');
+        let one = Polynomial::constant(Rational::one());
+        // gcd(x^2 - 1, 2x^2 + 4x + 2) = x + 1
+        let two = Polynomial::constant(Rational::from_integer(2));
+        assert_eq!(
+            (&x.pow(2) - &one).gcd(&(&two * &(&x + &one).pow(2))),
+            Some(&x + &one)
+        );
+        // gcd((x + y)(x - y), (x + y)^2 * y) = x + y
+        assert_eq!(
+            (&(&x + &y) * &(&x - &y)).gcd(&(&(&x + &y).pow(2) * &y)),
+            Some(&x + &y)
+        );
+        assert_eq!((&x + &one).gcd(&y), Some(one));
+        assert_eq!(Polynomial::zero().gcd(&(&two * &y)), Some(y));
+    }
+
+    #[test]
+    fn test_division_with_remainder() {
+        let x = Polynomial::variable('x');
+        let y = Polynomial::variable('y');
+        let one = Polynomial::constant(Rational::one());
+        // x^2*y + x*y^2 + y^2 = (x + y) * (x*y - 1) + (x + y^2 + y)
+        let dividend = &(&(&x.pow(2) * &y) + &(&x * &y.pow(2))) + &y.pow(2);
+        let divisor = &(&x * &y) - &one;
+        let (quotient, remainder) = dividend.div_rem(&divisor);
+        assert_eq!(quotient, &x + &y);
+        assert_eq!(remainder, &(&x + &y.pow(2)) + &y);
+        assert_eq!(&(&quotient * &divisor) + &remainder, dividend);
+
+        let (quotient, remainder) = x.pow(2).div_rem(&(&x - &one));
+        assert_eq!(quotient, &x + &one);
+        assert_eq!(remainder, one);
+    }
+
+    #[test]
+    fn test_coefficient_overflow() {
+        let x_plus_one = &Polynomial::variable('x') + &Polynomial::constant(Rational::one());
+        // The central binomial coefficient C(70, 35) does not fit in an i64, C(60, 30) does.
+        assert!(x_plus_one.checked_pow(60).is_some());
+        assert_eq!(x_plus_one.checked_pow(70), None);
+        let huge = Polynomial::constant(i64::MAX.into());
+        assert_eq!(huge.checked_add(&huge), None);
+        assert_eq!(huge.checked_mul(&huge), None);
+        assert_eq!(Polynomial::constant(i64::MIN.into()).checked_neg(), None);
+        assert_eq!(huge.checked_neg(), Some(-&huge));
+
+        let power = Expression::binary(
+            BinaryOperationType::Power,
+            Expression::binary(
+                BinaryOperationType::Addition,
+                Expression::monomial(1, 'x', 1),
+                Expression::Constant(Rational::one()),
+            ),
+            Expression::Constant(70.into()),
+        );
+        assert_eq!(
+            Polynomial::try_from(&power),
+            Err(PolynomialConversionError::Overflow)
+        );
+    }
+
+    #[test]
+    fn test_powers_beyond_monomials() {
+        let term = Polynomial::term(3.into(), PowerProduct::variable('x', 1000));
+        assert_eq!(
+            term.to_expression(),
+            Expression::binary(
+                BinaryOperationType::Multiplication,
+                Expression::Constant(3.into()),
+                Expression::binary(
+                    BinaryOperationType::Power,
+                    Expression::monomial(1, 'x', 1),
+                    Expression::Constant(1000.into())
+                )
+            )
+        );
+        assert_eq!(Polynomial::try_from(&term.to_expression()), Ok(term));
+    }
+}
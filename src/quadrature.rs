@@ -0,0 +1,462 @@
+use std::collections::HashMap;
+
+use crate::compilable::Compilable;
+use crate::derivable::Derivable;
+use crate::evaluable::EvaluationError;
+use crate::expression::{BinaryOperationType, Expression, Rational, Variable};
+use crate::surface::Surface;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum QuadratureMethod {
+    /// Gauss–Legendre rule with the given number of points, refined by bisection. Integrating
+    /// with no point panics.
+    GaussLegendre(usize),
+    AdaptiveSimpson,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QuadratureOptions {
+    pub method: QuadratureMethod,
+    /// Absolute error allowed on each integral, split between the halves of a bisection.
+    pub tolerance: f64,
+    /// Number of bisections after which the current estimate is accepted. A non-finite estimate
+    /// is returned without further bisection.
+    pub max_depth: usize,
+}
+
+impl Default for QuadratureOptions {
+    fn default() -> Self {
+        QuadratureOptions {
+            method: QuadratureMethod::GaussLegendre(8),
+            tolerance: 1e-10,
+            max_depth: 16,
+        }
+    }
+}
+
+impl QuadratureOptions {
+    pub fn with_method(self, method: QuadratureMethod) -> QuadratureOptions {
+        QuadratureOptions { method, ..self }
+    }
+
+    pub fn with_tolerance(self, tolerance: f64) -> QuadratureOptions {
+        QuadratureOptions { tolerance, ..self }
+    }
+
+    pub fn with_max_depth(self, max_depth: usize) -> QuadratureOptions {
+        QuadratureOptions { max_depth, ..self }
+    }
+}
+
+/// Parameter domain `[u_min, u_max] × [v_min, v_max]`, `u` and `v` being the coordinates of
+/// the surface in alphabetical order.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rectangle {
+    pub u: (f64, f64),
+    pub v: (f64, f64),
+}
+
+impl Rectangle {
+    /// The product of the domains of the two coordinates of `surface`.
+    ///
+    /// # Panics
+    ///
+    /// Panics unless the surface has two coordinates, each with a domain.
+    pub fn of_surface(
+        surface: &Surface,
+        parameters: &HashMap<Variable, f64>,
+    ) -> Result<Rectangle, EvaluationError> {
+        let coordinates = surface.coordinates();
+        assert_eq!(coordinates.len(), 2, "a surface has two coordinates");
+        let bounds = |variable: Variable| {
+            surface
+                .domain(variable)
+                .unwrap_or_else(|| panic!("{variable} has no domain"))
+                .bounds(parameters)
+        };
+        Ok(Rectangle {
+            u: bounds(coordinates[0])?,
+            v: bounds(coordinates[1])?,
+        })
+    }
+}
+
+/// Nodes and weights of the Gauss–Legendre rule on `[-1, 1]`, the roots of the Legendre
+/// polynomial found by Newton's method.
+fn gauss_legendre_rule(points: usize) -> Vec<(f64, f64)> {
+    (0..points)
+        .map(|i| {
+            let mut x = f64::cos(std::f64::consts::PI * (i as f64 + 0.75) / (points as f64 + 0.5));
+            loop {
+                // P_n(x) and P_{n-1}(x) by the three-term recurrence
+                let (mut p, mut previous) = (1., 0.);
+                for k in 1..=points {
+                    let k = k as f64;
+                    (p, previous) = (((2. * k - 1.) * x * p - (k - 1.) * previous) / k, p);
+                }
+                let derivative = points as f64 * (x * p - previous) / (x * x - 1.);
+                let step = p / derivative;
+                x -= step;
+                if step.abs() < 1e-15 {
+                    break (x, 2. / ((1. - x * x) * derivative * derivative));
+                }
+            }
+        })
+        .collect()
+}
+
+/// Quadrature rule prepared once for all the integrals of a computation.
+struct Quadrature {
+    options: QuadratureOptions,
+    gauss_legendre: Vec<(f64, f64)>,
+}
+
+impl Quadrature {
+    fn new(options: &QuadratureOptions) -> Quadrature {
+        let gauss_legendre = match options.method {
+            QuadratureMethod::GaussLegendre(points) => {
+                assert!(points > 0, "a Gauss–Legendre rule has at least one point");
+                gauss_legendre_rule(points)
+            }
+            QuadratureMethod::AdaptiveSimpson => vec![],
+        };
+        Quadrature {
+            options: *options,
+            gauss_legendre,
+        }
+    }
+
+    fn integrate<F: Fn(f64) -> f64>(&self, f: &F, (a, b): (f64, f64)) -> f64 {
+        match self.options.method {
+            QuadratureMethod::GaussLegendre(_) => {
+                let estimate = self.gauss_legendre(f, a, b);
+                self.adaptive_gauss_legendre(f, a, b, estimate, self.options.tolerance, 0)
+            }
+            QuadratureMethod::AdaptiveSimpson => {
+                let (fa, fm, fb) = (f(a), f((a + b) / 2.), f(b));
+                let estimate = simpson(f, (a, fa), (b, fb), fm);
+                self.adaptive_simpson(f, (a, fa), (b, fb), fm, estimate, self.options.tolerance, 0)
+            }
+        }
+    }
+
+    fn gauss_legendre<F: Fn(f64) -> f64>(&self, f: &F, a: f64, b: f64) -> f64 {
+        let (center, radius) = ((a + b) / 2., (b - a) / 2.);
+        radius
+            * self
+                .gauss_legendre
+                .iter()
+                .map(|(node, weight)| weight * f(center + radius * node))
+                .sum::<f64>()
+    }
+
+    fn adaptive_gauss_legendre<F: Fn(f64) -> f64>(
+        &self,
+        f: &F,
+        a: f64,
+        b: f64,
+        estimate: f64,
+        tolerance: f64,
+        depth: usize,
+    ) -> f64 {
+        let middle = (a + b) / 2.;
+        let (left, right) = (
+            self.gauss_legendre(f, a, middle),
+            self.gauss_legendre(f, middle, b),
+        );
+        let refined = left + right;
+        if !refined.is_finite()
+            || (refined - estimate).abs() <= tolerance
+            || depth >= self.options.max_depth
+        {
+            refined
+        } else {
+            self.adaptive_gauss_legendre(f, a, middle, left, tolerance / 2., depth + 1)
+                + self.adaptive_gauss_legendre(f, middle, b, right, tolerance / 2., depth + 1)
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn adaptive_simpson<F: Fn(f64) -> f64>(
+        &self,
+        f: &F,
+        (a, fa): (f64, f64),
+        (b, fb): (f64, f64),
+        fm: f64,
+        estimate: f64,
+        tolerance: f64,
+        depth: usize,
+    ) -> f64 {
+        let middle = (a + b) / 2.;
+        let (fl, fr) = (f((a + middle) / 2.), f((middle + b) / 2.));
+        let left = simpson(f, (a, fa), (middle, fm), fl);
+        let right = simpson(f, (middle, fm), (b, fb), fr);
+        let error = left + right - estimate;
+        if !error.is_finite() || error.abs() <= 15. * tolerance || depth >= self.options.max_depth {
+            // Richardson extrapolation
+            left + right + error / 15.
+        } else {
+            self.adaptive_simpson(
+                f,
+                (a, fa),
+                (middle, fm),
+                fl,
+                left,
+                tolerance / 2.,
+                depth + 1,
+            ) + self.adaptive_simpson(
+                f,
+                (middle, fm),
+                (b, fb),
+                fr,
+                right,
+                tolerance / 2.,
+                depth + 1,
+            )
+        }
+    }
+}
+
+/// Simpson's rule on `[a, b]` with `fm = f((a + b) / 2)`, or Milne's open rule when `f` is not
+/// finite at an end, as at the poles of a parametrization.
+fn simpson<F: Fn(f64) -> f64>(f: &F, (a, fa): (f64, f64), (b, fb): (f64, f64), fm: f64) -> f64 {
+    if fa.is_finite() && fb.is_finite() {
+        (b - a) / 6. * (fa + 4. * fm + fb)
+    } else {
+        let quarter = (b - a) / 4.;
+        (b - a) / 3. * (2. * f(a + quarter) - fm + 2. * f(b - quarter))
+    }
+}
+
+/// `∫_a^b f(x) dx`.
+pub fn integrate<F: Fn(f64) -> f64>(f: F, bounds: (f64, f64), options: &QuadratureOptions) -> f64 {
+    Quadrature::new(options).integrate(&f, bounds)
+}
+
+/// `∫∫ f(u, v) du dv` over `domain`, as iterated one-dimensional integrals.
+pub fn integrate_2d<F: Fn(f64, f64) -> f64>(
+    f: F,
+    domain: &Rectangle,
+    options: &QuadratureOptions,
+) -> f64 {
+    let quadrature = Quadrature::new(options);
+    quadrature.integrate(&|u| quadrature.integrate(&|v| f(u, v), domain.v), domain.u)
+}
+
+/// Compiles `expression` as a function of `coordinates`, the other variables taking their
+/// value in `parameters`.
+fn compile(
+    expression: &Expression,
+    coordinates: &[Variable],
+    parameters: &HashMap<Variable, f64>,
+) -> Result<impl Fn(&[f64]) -> f64, EvaluationError> {
+    let mut names: Vec<Variable> = parameters.keys().copied().collect();
+    names.sort();
+    let values: Vec<f64> = names.iter().map(|name| parameters[name]).collect();
+    let compiled = expression.compile(&[coordinates, &names].concat())?;
+    Ok(move |point: &[f64]| {
+        let mut arguments = point.to_vec();
+        arguments.extend_from_slice(&values);
+        compiled.evaluate(&arguments)
+    })
+}
+
+/// `∫_a^b expression d variable`, the other variables taking their value in `parameters`.
+pub fn integrate_expression(
+    expression: &Expression,
+    variable: Variable,
+    bounds: (f64, f64),
+    parameters: &HashMap<Variable, f64>,
+    options: &QuadratureOptions,
+) -> Result<f64, EvaluationError> {
+    let f = compile(expression, &[variable], parameters)?;
+    Ok(integrate(|x| f(&[x]), bounds, options))
+}
+
+/// Integral of `density` over `domain` with respect to the coordinates of `surface`.
+pub fn integrate_over_surface(
+    surface: &Surface,
+    density: &Expression,
+    domain: &Rectangle,
+    parameters: &HashMap<Variable, f64>,
+    options: &QuadratureOptions,
+) -> Result<f64, EvaluationError> {
+    let f = compile(density, &surface.coordinates(), parameters)?;
+    Ok(integrate_2d(|u, v| f(&[u, v]), domain, options))
+}
+
+fn product(left: Expression, right: Expression) -> Expression {
+    Expression::binary(BinaryOperationType::Multiplication, left, right)
+}
+
+/// `∫ sqrt(EG - F^2) du dv`.
+pub fn surface_area(
+    surface: &Surface,
+    domain: &Rectangle,
+    parameters: &HashMap<Variable, f64>,
+    options: &QuadratureOptions,
+) -> Result<f64, EvaluationError> {
+    integrate_over_surface(
+        surface,
+        &surface.area_element(),
+        domain,
+        parameters,
+        options,
+    )
+}
+
+/// `∫ K dA`, which is `2π χ` over a closed surface by the Gauss–Bonnet theorem.
+pub fn total_gaussian_curvature(
+    surface: &Surface,
+    domain: &Rectangle,
+    parameters: &HashMap<Variable, f64>,
+    options: &QuadratureOptions,
+) -> Result<f64, EvaluationError> {
+    let density = product(surface.gaussian_curvature(), surface.area_element());
+    integrate_over_surface(surface, &density, domain, parameters, options)
+}
+
+/// `∫ H^2 dA`, the Willmore energy, `4π` for a round sphere.
+pub fn willmore_energy(
+    surface: &Surface,
+    domain: &Rectangle,
+    parameters: &HashMap<Variable, f64>,
+    options: &QuadratureOptions,
+) -> Result<f64, EvaluationError> {
+    let mean_curvature = surface.mean_curvature();
+    let density = product(
+        product(mean_curvature.clone(), mean_curvature),
+        surface.area_element(),
+    );
+    integrate_over_surface(surface, &density, domain, parameters, options)
+}
+
+/// `∫_a^b |c'(t)| dt` for the curve `c` with components `curve` in the variable `variable`.
+pub fn arc_length(
+    curve: &[Expression],
+    variable: Variable,
+    bounds: (f64, f64),
+    parameters: &HashMap<Variable, f64>,
+    options: &QuadratureOptions,
+) -> Result<f64, EvaluationError> {
+    let speed = Expression::binary(
+        BinaryOperationType::Power,
+        Expression::Sum(
+            curve
+                .iter()
+                .map(|component| {
+                    let derivative = component.derive(variable);
+                    product(derivative.clone(), derivative)
+                })
+                .collect(),
+        ),
+        Expression::Constant(Rational::new(1, 2)),
+    );
+    integrate_expression(&speed, variable, bounds, parameters, options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::f64::consts::PI;
+
+    use crate::expression::Function;
+
+    #[test]
+    fn test_gauss_legendre_rule() {
+        let rule = gauss_legendre_rule(3);
+        let mut nodes: Vec<f64> = rule.iter().map(|(node, _)| *node).collect();
+        nodes.sort_by(f64::total_cmp);
+        let expected = [-f64::sqrt(0.6), 0., f64::sqrt(0.6)];
+        for (node, expected) in nodes.iter().zip(expected) {
+            assert!((node - expected).abs() < 1e-14);
+        }
+        let weights: f64 = rule.iter().map(|(_, weight)| weight).sum();
+        assert!((weights - 2.).abs() < 1e-14);
+    }
+
+    #[test]
+    fn test_integrate() {
+        for method in [
+            QuadratureMethod::GaussLegendre(5),
+            QuadratureMethod::AdaptiveSimpson,
+        ] {
+            let options = QuadratureOptions::default().with_method(method);
+            assert!((integrate(f64::sin, (0., PI), &options) - 2.).abs() < 1e-9);
+            // peaked integrand needing refinement near 0
+            let integral = integrate(|x| 1. / (1e-4 + x * x), (-1., 1.), &options);
+            let expected = 2. / 1e-2 * f64::atan(1. / 1e-2);
+            assert!((integral - expected).abs() < 1e-7, "{method:?}: {integral}");
+            let domain = Rectangle {
+                u: (0., 1.),
+                v: (0., 2.),
+            };
+            let integral = integrate_2d(|u, v| u * v * v, &domain, &options);
+            assert!((integral - 4. / 3.).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_non_finite_integrands() {
+        for method in [
+            QuadratureMethod::GaussLegendre(8),
+            QuadratureMethod::AdaptiveSimpson,
+        ] {
+            let options = QuadratureOptions::default().with_method(method);
+            let evaluations = std::cell::Cell::new(0);
+            let square_root = |x: f64| {
+                evaluations.set(evaluations.get() + 1);
+                x.sqrt()
+            };
+            assert!(integrate(square_root, (-1., 1.), &options).is_nan());
+            assert!(evaluations.get() < 100, "{method:?}: {}", evaluations.get());
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one point")]
+    fn test_gauss_legendre_without_points() {
+        let options = QuadratureOptions::default().with_method(QuadratureMethod::GaussLegendre(0));
+        integrate(f64::sin, (0., PI), &options);
+    }
+
+    #[test]
+    fn test_arc_length() {
+        // helix (r cos t, r sin t, h t) of speed sqrt(r^2 + h^2)
+        let curve = vec![
+            product(
+                Expression::monomial(1, 'r', 1),
+                Expression::function(Function::Cosine, Expression::monomial(1, 't', 1)),
+            ),
+            product(
+                Expression::monomial(1, 'r', 1),
+                Expression::function(Function::Sine, Expression::monomial(1, 't', 1)),
+            ),
+            product(
+                Expression::monomial(1, 'h', 1),
+                Expression::monomial(1, 't', 1),
+            ),
+        ];
+        let parameters = HashMap::from([('r', 3.), ('h', 4.)]);
+        let length = arc_length(
+            &curve,
+            't',
+            (0., 2. * PI),
+            &parameters,
+            &QuadratureOptions::default(),
+        )
+        .unwrap();
+        assert!((length - 10. * PI).abs() < 1e-9);
+        assert_eq!(
+            arc_length(
+                &curve,
+                't',
+                (0., 1.),
+                &HashMap::from([('h', 4.)]),
+                &QuadratureOptions::default()
+            ),
+            Err(EvaluationError::UnboundVariable('r'))
+        );
+    }
+}
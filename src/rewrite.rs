@@ -0,0 +1,754 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+use crate::expression::{BinaryOperation, BinaryOperationType, Expression, Function, Monomial};
+
+/// Subexpressions captured by the wildcards of a pattern, by wildcard name.
+pub type Bindings = HashMap<String, Expression>;
+
+/// Rebuilds an n-ary sum or product from its operands.
+type Rebuild = fn(Vec<Expression>) -> Expression;
+
+/// Side condition a match has to satisfy for a rule to fire.
+pub type Condition = Arc<dyn Fn(&Bindings) -> bool + Send + Sync>;
+
+/// Expression template with named holes, used both to match and to build expressions.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Pattern {
+    /// Matches any subexpression; repeated names must match equal subexpressions.
+    Wildcard(String),
+    /// Matches rational constants only.
+    ConstantWildcard(String),
+    Exact(Expression),
+    BinaryOperation {
+        operation: BinaryOperationType,
+        left: Box<Pattern>,
+        right: Box<Pattern>,
+    },
+    Function {
+        function: Function,
+        argument: Box<Pattern>,
+    },
+}
+
+impl Pattern {
+    pub fn wildcard(name: &str) -> Pattern {
+        Pattern::Wildcard(name.to_string())
+    }
+
+    pub fn constant(name: &str) -> Pattern {
+        Pattern::ConstantWildcard(name.to_string())
+    }
+
+    pub fn integer(value: i64) -> Pattern {
+        Pattern::Exact(Expression::Constant(value.into()))
+    }
+
+    pub fn operation(operation: BinaryOperationType, left: Pattern, right: Pattern) -> Pattern {
+        Pattern::BinaryOperation {
+            operation,
+            left: Box::new(left),
+            right: Box::new(right),
+        }
+    }
+
+    pub fn function(function: Function, argument: Pattern) -> Pattern {
+        Pattern::Function {
+            function,
+            argument: Box::new(argument),
+        }
+    }
+
+    /// Every way `expression` matches the pattern at its root. Additions and multiplications
+    /// match in both operand orders, n-ary sums and products match any split of their operands,
+    /// and monomials also match as `c * x^p` and `x^p`.
+    pub fn matches(&self, expression: &Expression) -> Vec<Bindings> {
+        self.match_with(expression, &Bindings::new())
+    }
+
+    fn match_with(&self, expression: &Expression, bindings: &Bindings) -> Vec<Bindings> {
+        match self {
+            Pattern::Wildcard(name) => bind(name, expression, bindings).into_iter().collect(),
+            Pattern::ConstantWildcard(name) => match expression {
+                Expression::Constant(_) => bind(name, expression, bindings).into_iter().collect(),
+                _ => vec![],
+            },
+            Pattern::Exact(exact) => {
+                if exact == expression {
+                    vec![bindings.clone()]
+                } else {
+                    vec![]
+                }
+            }
+            Pattern::BinaryOperation {
+                operation,
+                left,
+                right,
+            } => binary_views(expression, *operation)
+                .iter()
+                .flat_map(|(left_value, right_value)| {
+                    left.match_with(left_value, bindings)
+                        .into_iter()
+                        .flat_map(|bindings| right.match_with(right_value, &bindings))
+                        .collect::<Vec<Bindings>>()
+                })
+                .collect(),
+            Pattern::Function { function, argument } => match expression {
+                Expression::Function {
+                    function: f,
+                    expression,
+                } if f == function => argument.match_with(expression, bindings),
+                _ => vec![],
+            },
+        }
+    }
+
+    /// Builds the expression described by the pattern, `None` if a wildcard is unbound.
+    pub fn instantiate(&self, bindings: &Bindings) -> Option<Expression> {
+        match self {
+            Pattern::Wildcard(name) | Pattern::ConstantWildcard(name) => {
+                bindings.get(name).cloned()
+            }
+            Pattern::Exact(expression) => Some(expression.clone()),
+            Pattern::BinaryOperation {
+                operation,
+                left,
+                right,
+            } => Some(Expression::BinaryOperation(BinaryOperation {
+                operation: *operation,
+                left_value: Box::new(left.instantiate(bindings)?),
+                right_value: Box::new(right.instantiate(bindings)?),
+            })),
+            Pattern::Function { function, argument } => Some(Expression::Function {
+                function: *function,
+                expression: Box::new(argument.instantiate(bindings)?),
+            }),
+        }
+    }
+}
+
+fn bind(name: &str, expression: &Expression, bindings: &Bindings) -> Option<Bindings> {
+    match bindings.get(name) {
+        Some(bound) if bound != expression => None,
+        Some(_) => Some(bindings.clone()),
+        None => {
+            let mut bindings = bindings.clone();
+            bindings.insert(name.to_string(), expression.clone());
+            Some(bindings)
+        }
+    }
+}
+
+/// The `(left, right)` operand pairs under which `expression` can be read as `operation`.
+fn binary_views(
+    expression: &Expression,
+    operation: BinaryOperationType,
+) -> Vec<(Expression, Expression)> {
+    let mut views = vec![];
+    match expression {
+        Expression::BinaryOperation(o) if o.operation == operation => {
+            views.push((*o.left_value.clone(), *o.right_value.clone()));
+            if operation.is_commutative() {
+                views.push((*o.right_value.clone(), *o.left_value.clone()));
+            }
+        }
+        Expression::Sum(terms) if operation == BinaryOperationType::Addition => {
+            views.extend(splits(terms, Expression::Sum));
+        }
+        Expression::Product(factors) if operation == BinaryOperationType::Multiplication => {
+            views.extend(splits(factors, Expression::Product));
+        }
+        Expression::Monomial(m)
+            if operation == BinaryOperationType::Multiplication && m.factor != 1.into() =>
+        {
+            let constant = Expression::Constant(m.factor);
+            let power = Expression::Monomial(Monomial {
+                factor: 1.into(),
+                variable: m.variable,
+                power: m.power,
+            });
+            views.push((constant.clone(), power.clone()));
+            views.push((power, constant));
+        }
+        Expression::Monomial(m)
+            if operation == BinaryOperationType::Power && m.factor == 1.into() && m.power >= 2 =>
+        {
+            views.push((
+                Expression::Monomial(Monomial {
+                    factor: 1.into(),
+                    variable: m.variable,
+                    power: 1,
+                }),
+                Expression::Constant(i64::from(m.power).into()),
+            ));
+        }
+        _ => {}
+    }
+    views
+}
+
+fn splits(operands: &[Expression], rebuild: Rebuild) -> Vec<(Expression, Expression)> {
+    if operands.len() < 2 {
+        return vec![];
+    }
+    (0..operands.len())
+        .flat_map(|i| {
+            let mut rest: Vec<Expression> = operands.to_vec();
+            let operand = rest.remove(i);
+            let rest = if rest.len() == 1 {
+                rest.remove(0)
+            } else {
+                rebuild(rest)
+            };
+            [(operand.clone(), rest.clone()), (rest, operand)]
+        })
+        .collect()
+}
+
+/// A named `pattern -> replacement` rewrite with an optional side condition.
+#[derive(Clone)]
+pub struct RewriteRule {
+    pub name: String,
+    pub pattern: Pattern,
+    pub replacement: Pattern,
+    pub condition: Option<Condition>,
+}
+
+impl fmt::Debug for RewriteRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RewriteRule")
+            .field("name", &self.name)
+            .field("pattern", &self.pattern)
+            .field("replacement", &self.replacement)
+            .field("conditional", &self.condition.is_some())
+            .finish()
+    }
+}
+
+impl RewriteRule {
+    pub fn new(name: &str, pattern: Pattern, replacement: Pattern) -> RewriteRule {
+        RewriteRule {
+            name: name.to_string(),
+            pattern,
+            replacement,
+            condition: None,
+        }
+    }
+
+    pub fn with_condition<F: Fn(&Bindings) -> bool + Send + Sync + 'static>(
+        self,
+        condition: F,
+    ) -> RewriteRule {
+        RewriteRule {
+            condition: Some(Arc::new(condition)),
+            ..self
+        }
+    }
+
+    /// Rewrites `expression` if the rule matches at its root. Inside sums and products with
+    /// more than two operands the rule is also tried on every pair of operands.
+    pub fn apply(&self, expression: &Expression) -> Option<Expression> {
+        if let Some(rewritten) = self.apply_at_root(expression) {
+            return Some(rewritten);
+        }
+        let (operands, rebuild): (&Vec<Expression>, Rebuild) = match expression {
+            Expression::Sum(terms) if terms.len() > 2 => (terms, Expression::Sum),
+            Expression::Product(factors) if factors.len() > 2 => (factors, Expression::Product),
+            _ => return None,
+        };
+        for i in 0..operands.len() {
+            for j in i + 1..operands.len() {
+                let pair = rebuild(vec![operands[i].clone(), operands[j].clone()]);
+                if let Some(rewritten) = self.apply_at_root(&pair) {
+                    let mut operands = operands.clone();
+                    operands.remove(j);
+                    operands[i] = rewritten;
+                    return Some(rebuild(operands));
+                }
+            }
+        }
+        None
+    }
+
+    fn apply_at_root(&self, expression: &Expression) -> Option<Expression> {
+        self.pattern
+            .matches(expression)
+            .into_iter()
+            .filter(|bindings| {
+                self.condition
+                    .as_ref()
+                    .is_none_or(|condition| condition(bindings))
+            })
+            .filter_map(|bindings| self.replacement.instantiate(&bindings))
+            .find(|rewritten| rewritten != expression)
+    }
+}
+
+/// Named collection of rules, selected together when simplifying.
+#[derive(Debug, Clone)]
+pub struct RuleSet {
+    pub name: String,
+    pub rules: Vec<RewriteRule>,
+}
+
+impl RuleSet {
+    pub fn new(name: &str, rules: Vec<RewriteRule>) -> RuleSet {
+        RuleSet {
+            name: name.to_string(),
+            rules,
+        }
+    }
+
+    pub fn push(&mut self, rule: RewriteRule) {
+        self.rules.push(rule);
+    }
+
+    /// The built-in rule set called `name`: `"trig"`, `"polynomial"` or `"log-exp"`.
+    pub fn named(name: &str) -> Option<RuleSet> {
+        match name {
+            "trig" => Some(RuleSet::trigonometric()),
+            "polynomial" => Some(RuleSet::polynomial()),
+            "log-exp" => Some(RuleSet::logarithm_exponential()),
+            _ => None,
+        }
+    }
+
+    pub fn builtin() -> Vec<RuleSet> {
+        vec![
+            RuleSet::trigonometric(),
+            RuleSet::polynomial(),
+            RuleSet::logarithm_exponential(),
+        ]
+    }
+
+    pub fn trigonometric() -> RuleSet {
+        let x = || Pattern::wildcard("x");
+        let pi = || Pattern::Exact(Expression::NamedConstant("pi".to_string()));
+        let is_negative = |bindings: &Bindings| matches!(bindings.get("c"), Some(Expression::Constant(c)) if *c < 0.into());
+        RuleSet::new(
+            "trig",
+            vec![
+                RewriteRule::new(
+                    "pythagorean-identity",
+                    add(
+                        pow(sin(x()), Pattern::integer(2)),
+                        pow(cos(x()), Pattern::integer(2)),
+                    ),
+                    Pattern::integer(1),
+                ),
+                RewriteRule::new(
+                    "pythagorean-cosine",
+                    sub(Pattern::integer(1), pow(sin(x()), Pattern::integer(2))),
+                    pow(cos(x()), Pattern::integer(2)),
+                ),
+                RewriteRule::new(
+                    "pythagorean-sine",
+                    sub(Pattern::integer(1), pow(cos(x()), Pattern::integer(2))),
+                    pow(sin(x()), Pattern::integer(2)),
+                ),
+                RewriteRule::new(
+                    "sine-of-zero",
+                    sin(Pattern::integer(0)),
+                    Pattern::integer(0),
+                ),
+                RewriteRule::new(
+                    "cosine-of-zero",
+                    cos(Pattern::integer(0)),
+                    Pattern::integer(1),
+                ),
+                RewriteRule::new("sine-of-pi", sin(pi()), Pattern::integer(0)),
+                RewriteRule::new("cosine-of-pi", cos(pi()), Pattern::integer(-1)),
+                RewriteRule::new(
+                    "sine-parity",
+                    sin(mul(Pattern::constant("c"), x())),
+                    mul(
+                        Pattern::integer(-1),
+                        sin(mul(mul(Pattern::integer(-1), Pattern::constant("c")), x())),
+                    ),
+                )
+                .with_condition(is_negative),
+                RewriteRule::new(
+                    "cosine-parity",
+                    cos(mul(Pattern::constant("c"), x())),
+                    cos(mul(mul(Pattern::integer(-1), Pattern::constant("c")), x())),
+                )
+                .with_condition(is_negative),
+                RewriteRule::new(
+                    "double-angle-sine",
+                    mul(mul(Pattern::integer(2), sin(x())), cos(x())),
+                    sin(mul(Pattern::integer(2), x())),
+                ),
+            ],
+        )
+    }
+
+    pub fn polynomial() -> RuleSet {
+        let x = || Pattern::wildcard("x");
+        let a = || Pattern::constant("a");
+        let b = || Pattern::constant("b");
+        RuleSet::new(
+            "polynomial",
+            vec![
+                RewriteRule::new("square", mul(x(), x()), pow(x(), Pattern::integer(2))),
+                RewriteRule::new(
+                    "power-times-base",
+                    mul(pow(x(), a()), x()),
+                    pow(x(), add(a(), Pattern::integer(1))),
+                ),
+                RewriteRule::new(
+                    "power-times-power",
+                    mul(pow(x(), a()), pow(x(), b())),
+                    pow(x(), add(a(), b())),
+                ),
+                RewriteRule::new(
+                    "power-of-power",
+                    pow(pow(x(), a()), b()),
+                    pow(x(), mul(a(), b())),
+                )
+                .with_condition(|bindings| {
+                    matches!(bindings.get("b"), Some(Expression::Constant(b)) if b.is_integer())
+                }),
+                RewriteRule::new("double", add(x(), x()), mul(Pattern::integer(2), x())),
+                RewriteRule::new(
+                    "like-terms",
+                    add(mul(a(), x()), mul(b(), x())),
+                    mul(add(a(), b()), x()),
+                ),
+                RewriteRule::new(
+                    "like-term",
+                    add(mul(a(), x()), x()),
+                    mul(add(a(), Pattern::integer(1)), x()),
+                ),
+                RewriteRule::new("difference-of-equals", sub(x(), x()), Pattern::integer(0)),
+                RewriteRule::new("quotient-of-equals", div(x(), x()), Pattern::integer(1))
+                    .with_condition(|bindings| {
+                        bindings.get("x") != Some(&Expression::Constant(0.into()))
+                    }),
+            ],
+        )
+    }
+
+    pub fn logarithm_exponential() -> RuleSet {
+        let x = || Pattern::wildcard("x");
+        RuleSet::new(
+            "log-exp",
+            vec![
+                RewriteRule::new("logarithm-of-exponential", ln(exp(x())), x()),
+                RewriteRule::new("exponential-of-logarithm", exp(ln(x())), x()),
+                RewriteRule::new(
+                    "exponential-of-zero",
+                    exp(Pattern::integer(0)),
+                    Pattern::integer(1),
+                ),
+                RewriteRule::new(
+                    "logarithm-of-one",
+                    ln(Pattern::integer(1)),
+                    Pattern::integer(0),
+                ),
+                RewriteRule::new(
+                    "logarithm-of-e",
+                    ln(Pattern::Exact(Expression::NamedConstant("e".to_string()))),
+                    Pattern::integer(1),
+                ),
+                RewriteRule::new(
+                    "exponential-product",
+                    mul(exp(Pattern::wildcard("a")), exp(Pattern::wildcard("b"))),
+                    exp(add(Pattern::wildcard("a"), Pattern::wildcard("b"))),
+                ),
+                RewriteRule::new(
+                    "exponential-power",
+                    pow(exp(x()), Pattern::constant("n")),
+                    exp(mul(Pattern::constant("n"), x())),
+                ),
+            ],
+        )
+    }
+}
+
+/// Rewrites the first subexpression, in post-order, that a rule of `rule_sets` applies to.
+/// Returns the rewritten expression and the rule used.
+pub fn rewrite_once<'a>(
+    expression: &Expression,
+    rule_sets: &'a [RuleSet],
+) -> Option<(Expression, &'a RewriteRule)> {
+    if let Some(rewritten) = rewrite_children(expression, rule_sets) {
+        return Some(rewritten);
+    }
+    rule_sets
+        .iter()
+        .flat_map(|rule_set| &rule_set.rules)
+        .find_map(|rule| rule.apply(expression).map(|rewritten| (rewritten, rule)))
+}
+
+fn rewrite_children<'a>(
+    expression: &Expression,
+    rule_sets: &'a [RuleSet],
+) -> Option<(Expression, &'a RewriteRule)> {
+    match expression {
+        Expression::Constant(_) | Expression::NamedConstant(_) | Expression::Monomial(_) => None,
+        Expression::BinaryOperation(operation) => {
+            if let Some((left, rule)) = rewrite_once(&operation.left_value, rule_sets) {
+                return Some((
+                    Expression::BinaryOperation(BinaryOperation {
+                        operation: operation.operation,
+                        left_value: Box::new(left),
+                        right_value: operation.right_value.clone(),
+                    }),
+                    rule,
+                ));
+            }
+            rewrite_once(&operation.right_value, rule_sets).map(|(right, rule)| {
+                (
+                    Expression::BinaryOperation(BinaryOperation {
+                        operation: operation.operation,
+                        left_value: operation.left_value.clone(),
+                        right_value: Box::new(right),
+                    }),
+                    rule,
+                )
+            })
+        }
+        Expression::Function {
+            function,
+            expression,
+        } => rewrite_once(expression, rule_sets).map(|(argument, rule)| {
+            (
+                Expression::Function {
+                    function: *function,
+                    expression: Box::new(argument),
+                },
+                rule,
+            )
+        }),
+        Expression::Sum(operands) | Expression::Product(operands) => {
+            operands.iter().enumerate().find_map(|(i, operand)| {
+                rewrite_once(operand, rule_sets).map(|(rewritten, rule)| {
+                    let mut operands = operands.clone();
+                    operands[i] = rewritten;
+                    let rewritten = match expression {
+                        Expression::Sum(_) => Expression::Sum(operands),
+                        _ => Expression::Product(operands),
+                    };
+                    (rewritten, rule)
+                })
+            })
+        }
+    }
+}
+
+pub(crate) fn add(left: Pattern, right: Pattern) -> Pattern {
+    Pattern::operation(BinaryOperationType::Addition, left, right)
+}
+
+pub(crate) fn sub(left: Pattern, right: Pattern) -> Pattern {
+    Pattern::operation(BinaryOperationType::Substraction, left, right)
+}
+
+pub(crate) fn mul(left: Pattern, right: Pattern) -> Pattern {
+    Pattern::operation(BinaryOperationType::Multiplication, left, right)
+}
+
+pub(crate) fn div(left: Pattern, right: Pattern) -> Pattern {
+    Pattern::operation(BinaryOperationType::Division, left, right)
+}
+
+pub(crate) fn pow(left: Pattern, right: Pattern) -> Pattern {
+    Pattern::operation(BinaryOperationType::Power, left, right)
+}
+
+pub(crate) fn sin(argument: Pattern) -> Pattern {
+    Pattern::function(Function::Sine, argument)
+}
+
+pub(crate) fn cos(argument: Pattern) -> Pattern {
+    Pattern::function(Function::Cosine, argument)
+}
+
+pub(crate) fn exp(argument: Pattern) -> Pattern {
+    Pattern::function(Function::Exponential, argument)
+}
+
+pub(crate) fn ln(argument: Pattern) -> Pattern {
+    Pattern::function(Function::Logarithm, argument)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::simplifiable::Simplifiable;
+
+    fn x() -> Expression {
+        Expression::Monomial(Monomial {
+            factor: 1.into(),
+            variable: 'x',
+            power: 1,
+        })
+    }
+
+    #[test]
+    fn test_match_commutative_and_repeated_wildcards() {
+        let pattern = add(Pattern::wildcard("a"), sin(Pattern::wildcard("a")));
+        // sin(x) + x
+        let expr = Expression::binary(
+            BinaryOperationType::Addition,
+            Expression::function(Function::Sine, x()),
+            x(),
+        );
+        assert_eq!(
+            pattern.matches(&expr),
+            vec![Bindings::from([("a".to_string(), x())])]
+        );
+        // sin(x) + 2
+        let expr = Expression::binary(
+            BinaryOperationType::Addition,
+            Expression::function(Function::Sine, x()),
+            Expression::Constant(2.into()),
+        );
+        assert!(pattern.matches(&expr).is_empty());
+        assert!(Pattern::constant("c").matches(&x()).is_empty());
+    }
+
+    #[test]
+    fn test_match_monomial_views() {
+        let pattern = mul(
+            Pattern::constant("c"),
+            pow(Pattern::wildcard("x"), Pattern::constant("n")),
+        );
+        let expr = Expression::Monomial(Monomial {
+            factor: 3.into(),
+            variable: 'x',
+            power: 2,
+        });
+        assert_eq!(
+            pattern.matches(&expr),
+            vec![Bindings::from([
+                ("c".to_string(), Expression::Constant(3.into())),
+                ("x".to_string(), x()),
+                ("n".to_string(), Expression::Constant(2.into())),
+            ])]
+        );
+    }
+
+    #[test]
+    fn test_builtin_rule_sets() {
+        let y = Expression::Monomial(Monomial {
+            factor: 1.into(),
+            variable: 'y',
+            power: 1,
+        });
+        // y + sin(x)^2 + cos(x)^2
+        let expr = Expression::Sum(vec![
+            y.clone(),
+            Expression::binary(
+                BinaryOperationType::Power,
+                Expression::function(Function::Sine, x()),
+                Expression::Constant(2.into()),
+            ),
+            Expression::binary(
+                BinaryOperationType::Power,
+                Expression::function(Function::Cosine, x()),
+                Expression::Constant(2.into()),
+            ),
+        ]);
+        assert_eq!(
+            expr.simplify_with_rules(&[RuleSet::named("trig").unwrap()]),
+            Expression::Sum(vec![y.clone(), Expression::Constant(1.into())])
+        );
+
+        // ln(exp(x * x)) + x^2
+        let expr = Expression::binary(
+            BinaryOperationType::Addition,
+            Expression::function(
+                Function::Logarithm,
+                Expression::function(
+                    Function::Exponential,
+                    Expression::binary(BinaryOperationType::Multiplication, x(), x()),
+                ),
+            ),
+            Expression::Monomial(Monomial {
+                factor: 1.into(),
+                variable: 'x',
+                power: 2,
+            }),
+        );
+        assert_eq!(
+            expr.simplify_with_rules(&RuleSet::builtin()),
+            Expression::Monomial(Monomial {
+                factor: 2.into(),
+                variable: 'x',
+                power: 2,
+            })
+        );
+
+        // cos(-2x)
+        let expr = Expression::function(
+            Function::Cosine,
+            Expression::Monomial(Monomial {
+                factor: (-2).into(),
+                variable: 'x',
+                power: 1,
+            }),
+        );
+        assert_eq!(
+            expr.simplify_with_rules(&RuleSet::builtin()),
+            Expression::function(
+                Function::Cosine,
+                Expression::Monomial(Monomial {
+                    factor: 2.into(),
+                    variable: 'x',
+                    power: 1,
+                })
+            )
+        );
+        assert!(RuleSet::named("unknown").is_none());
+    }
+
+    #[test]
+    fn test_user_defined_rule_with_condition() {
+        // x^n / x -> x^(n - 1) for n > 1
+        let rule = RewriteRule::new(
+            "reduce-power",
+            div(
+                pow(Pattern::wildcard("x"), Pattern::constant("n")),
+                Pattern::wildcard("x"),
+            ),
+            pow(
+                Pattern::wildcard("x"),
+                sub(Pattern::constant("n"), Pattern::integer(1)),
+            ),
+        )
+        .with_condition(
+            |bindings| matches!(bindings.get("n"), Some(Expression::Constant(n)) if *n > 1.into()),
+        );
+        let mut rule_set = RuleSet::new("user", vec![]);
+        rule_set.push(rule);
+
+        let expr = |power: u8| {
+            Expression::binary(
+                BinaryOperationType::Division,
+                Expression::Monomial(Monomial {
+                    factor: 1.into(),
+                    variable: 'x',
+                    power,
+                }),
+                x(),
+            )
+        };
+        assert_eq!(
+            expr(3).simplify_with_rules(std::slice::from_ref(&rule_set)),
+            Expression::Monomial(Monomial {
+                factor: 1.into(),
+                variable: 'x',
+                power: 2,
+            })
+        );
+        assert_eq!(
+            rewrite_once(&expr(3), std::slice::from_ref(&rule_set)).map(|(_, rule)| &rule.name),
+            Some(&"reduce-power".to_string())
+        );
+        // x / x only matches through the monomial view x^1, which is not offered
+        assert_eq!(expr(1).simplify_with_rules(&[rule_set]), expr(1));
+    }
+}
@@ -1,88 +1,257 @@
-use crate::expression::{BinaryOperation, BinaryOperationType, Expression, Function, Monomial};
+use std::time::{Duration, Instant};
+
+use num_traits::{CheckedAdd, CheckedDiv, CheckedMul, CheckedSub, One, Zero};
+
+use crate::assumptions::Assumptions;
+use crate::expression::{
+    BinaryOperation, BinaryOperationType, Expression, Function, Monomial, Rational,
+};
+use crate::rewrite::{rewrite_once, RuleSet};
+
+/// Rewritten expression and the name of the built-in rule that produced it.
+type Step = (Expression, &'static str);
+
+/// Bounds and rules of a `simplify_with` run.
+#[derive(Debug, Clone)]
+pub struct SimplificationOptions {
+    /// Applied after the built-in rules stop changing the expression.
+    pub rule_sets: Vec<RuleSet>,
+    /// Maximum number of rewrites, built-in or from `rule_sets`.
+    pub max_iterations: usize,
+    pub time_budget: Option<Duration>,
+    /// Enables the rewrites of `Assumptions::rule_set`.
+    pub assumptions: Assumptions,
+    /// Whether to record every rewrite in `Simplification::trace`.
+    pub trace: bool,
+}
+
+impl Default for SimplificationOptions {
+    fn default() -> Self {
+        SimplificationOptions {
+            rule_sets: vec![],
+            max_iterations: 10_000,
+            time_budget: None,
+            assumptions: Assumptions::new(),
+            trace: false,
+        }
+    }
+}
+
+impl SimplificationOptions {
+    pub fn with_rule_sets(self, rule_sets: Vec<RuleSet>) -> SimplificationOptions {
+        SimplificationOptions { rule_sets, ..self }
+    }
+
+    pub fn with_max_iterations(self, max_iterations: usize) -> SimplificationOptions {
+        SimplificationOptions {
+            max_iterations,
+            ..self
+        }
+    }
+
+    pub fn with_time_budget(self, time_budget: Duration) -> SimplificationOptions {
+        SimplificationOptions {
+            time_budget: Some(time_budget),
+            ..self
+        }
+    }
+
+    pub fn with_assumptions(self, assumptions: Assumptions) -> SimplificationOptions {
+        SimplificationOptions {
+            assumptions,
+            ..self
+        }
+    }
+
+    pub fn with_trace(self) -> SimplificationOptions {
+        SimplificationOptions {
+            trace: true,
+            ..self
+        }
+    }
+}
+
+/// One rewrite of a `simplify_with` run, `before` and `after` being whole expressions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RewriteStep {
+    pub rule: String,
+    pub before: Expression,
+    pub after: Expression,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SimplificationStop {
+    /// No rule applies anymore.
+    Converged,
+    IterationLimit,
+    TimeBudget,
+}
+
+#[derive(Debug, Clone)]
+pub struct Simplification {
+    pub expression: Expression,
+    pub iterations: usize,
+    pub stop: SimplificationStop,
+    /// Empty unless `SimplificationOptions::trace` is set.
+    pub trace: Vec<RewriteStep>,
+}
 
 pub trait Simplifiable {
     fn simplify_expression(&self) -> Self;
+    /// Alternates the built-in simplification with the rules of `rule_sets` until neither
+    /// changes the expression.
+    fn simplify_with_rules(&self, rule_sets: &[RuleSet]) -> Self;
+    /// Rewrites one subexpression at a time, built-in rules first, until no rule applies or
+    /// the budget of `options` is spent.
+    fn simplify_with(&self, options: &SimplificationOptions) -> Simplification;
 }
 
 impl Simplifiable for Expression {
     fn simplify_expression(&self) -> Self {
-        // println!("simplify_expression({:?}", &expression);
-        match do_simplify_expression(self) {
-            Some(exp) => exp.simplify_expression(),
-            None => self.clone(),
-        }
+        self.simplify_with(&SimplificationOptions::default())
+            .expression
     }
-}
 
-fn simplify_function_subexpression(
-    function: Function,
-    expression: &Expression,
-) -> Option<Expression> {
-    do_simplify_expression(&expression).map(|exp| Expression::Function {
-        function: function,
-        expression: Box::new(exp),
-    })
-}
+    fn simplify_with_rules(&self, rule_sets: &[RuleSet]) -> Self {
+        self.simplify_with(&SimplificationOptions::default().with_rule_sets(rule_sets.to_vec()))
+            .expression
+    }
 
-fn simplify_function(function: &Function, expression: &Expression) -> Option<Expression> {
-    println!("simplify_function({:?}, {:?})", function, &expression);
-    match function {
-        Function::Cosine => simplify_function_subexpression(*function, expression),
-        Function::Sine => {
-            if *expression == Expression::Constant(0.) {
-                Some(Expression::Constant(0.))
-            } else {
-                simplify_function_subexpression(*function, expression)
+    fn simplify_with(&self, options: &SimplificationOptions) -> Simplification {
+        let start = Instant::now();
+        let mut rule_sets = options.rule_sets.clone();
+        if !options.assumptions.is_empty() {
+            rule_sets.push(options.assumptions.rule_set());
+        }
+        let mut expression = self.clone();
+        let mut trace = vec![];
+        let mut iterations = 0;
+        let stop = loop {
+            if iterations == options.max_iterations {
+                break SimplificationStop::IterationLimit;
+            }
+            if options
+                .time_budget
+                .is_some_and(|budget| start.elapsed() >= budget)
+            {
+                break SimplificationStop::TimeBudget;
+            }
+            let step = do_simplify_expression(&expression)
+                .map(|(rewritten, rule)| (rewritten, rule.to_string()))
+                .or_else(|| {
+                    rewrite_once(&expression, &rule_sets)
+                        .map(|(rewritten, rule)| (rewritten, rule.name.clone()))
+                });
+            let Some((rewritten, rule)) = step else {
+                break SimplificationStop::Converged;
+            };
+            iterations += 1;
+            if options.trace {
+                trace.push(RewriteStep {
+                    rule,
+                    before: expression,
+                    after: rewritten.clone(),
+                });
             }
+            expression = rewritten;
+        };
+        Simplification {
+            expression,
+            iterations,
+            stop,
+            trace,
         }
     }
 }
 
-fn simplify_operation(operation: &BinaryOperation) -> Option<Expression> {
-    println!(
-        "simplify_operation({:?}, {:?}, {:?})",
-        operation, &operation.left_value, &operation.right_value
-    );
-    if *operation.left_value == operation.operation.identity_element() {
-        return Some(*operation.right_value.clone());
+fn simplify_function(function: &Function, expression: &Expression) -> Option<Step> {
+    if *function == Function::Sine && *expression == Expression::Constant(0.into()) {
+        return Some((Expression::Constant(0.into()), "sine-of-zero"));
+    }
+    do_simplify_expression(expression).map(|(exp, rule)| {
+        (
+            Expression::Function {
+                function: *function,
+                expression: Box::new(exp),
+            },
+            rule,
+        )
+    })
+}
+
+fn simplify_operation(operation: &BinaryOperation) -> Option<Step> {
+    if operation.operation.is_commutative()
+        && *operation.left_value == operation.operation.identity_element()
+    {
+        return Some((*operation.right_value.clone(), "left-identity"));
     }
     if *operation.right_value == operation.operation.identity_element() {
-        return Some(*operation.left_value.clone());
+        return Some((*operation.left_value.clone(), "right-identity"));
     }
     if operation.operation == BinaryOperationType::Multiplication
-        && (*operation.left_value == Expression::Constant(0.)
-            || *operation.right_value == Expression::Constant(0.))
+        && (*operation.left_value == Expression::Constant(0.into())
+            || *operation.right_value == Expression::Constant(0.into()))
     {
-        return Some(Expression::Constant(0.));
+        return Some((Expression::Constant(0.into()), "multiply-zero"));
     }
     if operation.operation == BinaryOperationType::Division
-        && *operation.left_value == Expression::Constant(0.)
+        && *operation.left_value == Expression::Constant(0.into())
     {
-        return Some(Expression::Constant(0.));
+        return Some((Expression::Constant(0.into()), "divide-zero"));
+    }
+    if operation.operation == BinaryOperationType::Power
+        && *operation.right_value == Expression::Constant(0.into())
+    {
+        return Some((Expression::Constant(1.into()), "power-zero"));
+    }
+    if let (Expression::Constant(left), Expression::Constant(right)) =
+        (&*operation.left_value, &*operation.right_value)
+    {
+        if let Some(value) = fold_constants(operation.operation, left, right) {
+            return Some((Expression::Constant(value), "fold-constants"));
+        }
+    }
+    if operation.operation == BinaryOperationType::Power {
+        if let (Expression::Monomial(m), Expression::Constant(exponent)) =
+            (&*operation.left_value, &*operation.right_value)
+        {
+            if let Some(power) = monomial_power(m, exponent) {
+                return Some((Expression::Monomial(power), "monomial-power"));
+            }
+        }
     }
     if operation.operation == BinaryOperationType::Addition {
-        match (&*operation.left_value, &*operation.right_value) {
-            (Expression::Monomial(m1), Expression::Monomial(m2)) => {
-                if m1.variable == m2.variable && m1.power == m2.power {
-                    return Some(Expression::Monomial(Monomial {
-                        factor: m1.factor + m2.factor,
-                        variable: m1.variable,
-                        power: m1.power,
-                    }));
+        if let (Expression::Monomial(m1), Expression::Monomial(m2)) =
+            (&*operation.left_value, &*operation.right_value)
+        {
+            if m1.variable == m2.variable && m1.power == m2.power {
+                if let Some(factor) = m1.factor.checked_add(&m2.factor) {
+                    return Some((
+                        Expression::Monomial(Monomial {
+                            factor,
+                            variable: m1.variable,
+                            power: m1.power,
+                        }),
+                        "add-monomials",
+                    ));
                 }
             }
-            (_, _) => {}
         }
     }
     if operation.operation == BinaryOperationType::Multiplication {
         match (&*operation.left_value, &*operation.right_value) {
             (Expression::Monomial(m), Expression::Constant(constant))
             | (Expression::Constant(constant), Expression::Monomial(m)) => {
-                return Some(Expression::Monomial(Monomial {
-                    factor: m.factor * constant,
-                    variable: m.variable,
-                    power: m.power,
-                }))
+                if let Some(factor) = m.factor.checked_mul(constant) {
+                    return Some((
+                        Expression::Monomial(Monomial {
+                            factor,
+                            variable: m.variable,
+                            power: m.power,
+                        }),
+                        "scale-monomial",
+                    ));
+                }
             }
             (_, _) => {}
         }
@@ -90,53 +259,171 @@ fn simplify_operation(operation: &BinaryOperation) -> Option<Expression> {
     simplify_operation_operands(operation)
 }
 
-fn simplify_operation_operands(operation: &BinaryOperation) -> Option<Expression> {
-    match (
-        do_simplify_expression(&*operation.left_value),
-        do_simplify_expression(&*operation.right_value),
-    ) {
-        (Some(left), Some(right)) => Some(Expression::BinaryOperation(BinaryOperation {
-            operation: operation.operation,
-            left_value: Box::new(left),
-            right_value: Box::new(right),
-        })),
-        (Some(left), None) => Some(Expression::BinaryOperation(BinaryOperation {
-            operation: operation.operation,
-            left_value: Box::new(left),
-            right_value: Box::new(*operation.right_value.clone()),
-        })),
-        (None, Some(right)) => Some(Expression::BinaryOperation(BinaryOperation {
-            operation: operation.operation,
-            left_value: Box::new(*operation.left_value.clone()),
-            right_value: Box::new(right),
-        })),
-        (None, None) => None,
-    }
-}
-
-fn do_simplify_expression(expr: &Expression) -> Option<Expression> {
+/// Exact arithmetic on two constants; `None` when the result is not rational (fractional
+/// powers), undefined (division by zero) or does not fit.
+pub(crate) fn fold_constants(
+    operation: BinaryOperationType,
+    left: &Rational,
+    right: &Rational,
+) -> Option<Rational> {
+    match operation {
+        BinaryOperationType::Addition => left.checked_add(right),
+        BinaryOperationType::Substraction => left.checked_sub(right),
+        BinaryOperationType::Multiplication => left.checked_mul(right),
+        BinaryOperationType::Division => left.checked_div(right),
+        BinaryOperationType::Power => {
+            if !right.is_integer() || (left.is_zero() && *right.numer() < 0) {
+                return None;
+            }
+            let base = if *right.numer() < 0 {
+                // The reciprocal of i64::MIN has no positive denominator.
+                if *left.numer() == i64::MIN {
+                    return None;
+                }
+                left.recip()
+            } else {
+                *left
+            };
+            checked_pow(base, right.numer().unsigned_abs())
+        }
+    }
+}
+
+/// Exponentiation by squaring, `None` on overflow.
+fn checked_pow(base: Rational, mut exponent: u64) -> Option<Rational> {
+    if exponent == 0 || base.is_one() {
+        return Some(Rational::one());
+    }
+    if base.is_zero() {
+        return Some(base);
+    }
+    if base == -Rational::one() {
+        return Some(if exponent.is_multiple_of(2) {
+            -base
+        } else {
+            base
+        });
+    }
+    // Any other base has a numerator or denominator of at least 2 in absolute value.
+    if exponent > i64::BITS as u64 {
+        return None;
+    }
+    let (mut power, mut square) = (Rational::one(), base);
+    loop {
+        if exponent % 2 == 1 {
+            power = power.checked_mul(&square)?;
+        }
+        exponent /= 2;
+        if exponent == 0 {
+            return Some(power);
+        }
+        square = square.checked_mul(&square)?;
+    }
+}
+
+pub(crate) fn monomial_power(monomial: &Monomial, exponent: &Rational) -> Option<Monomial> {
+    if !exponent.is_integer() || *exponent.numer() < 1 {
+        return None;
+    }
+    let power = u8::try_from(*exponent.numer())
+        .ok()?
+        .checked_mul(monomial.power)?;
+    Some(Monomial {
+        factor: fold_constants(BinaryOperationType::Power, &monomial.factor, exponent)?,
+        variable: monomial.variable,
+        power,
+    })
+}
+
+fn simplify_operation_operands(operation: &BinaryOperation) -> Option<Step> {
+    if let Some((left, rule)) = do_simplify_expression(&operation.left_value) {
+        return Some((
+            Expression::BinaryOperation(BinaryOperation {
+                operation: operation.operation,
+                left_value: Box::new(left),
+                right_value: operation.right_value.clone(),
+            }),
+            rule,
+        ));
+    }
+    do_simplify_expression(&operation.right_value).map(|(right, rule)| {
+        (
+            Expression::BinaryOperation(BinaryOperation {
+                operation: operation.operation,
+                left_value: operation.left_value.clone(),
+                right_value: Box::new(right),
+            }),
+            rule,
+        )
+    })
+}
+
+/// Applies the first built-in rule matching the expression, at its root or, failing that, in
+/// the first operand where one matches.
+fn do_simplify_expression(expr: &Expression) -> Option<Step> {
     match expr {
         Expression::Function {
             function,
             expression,
-        } => simplify_function(function, &*expression),
+        } => simplify_function(function, expression),
         Expression::BinaryOperation(operation) => simplify_operation(operation),
+        Expression::Sum(terms) => simplify_operands(BinaryOperationType::Addition, terms),
+        Expression::Product(factors) => {
+            if factors.contains(&Expression::Constant(0.into())) {
+                return Some((Expression::Constant(0.into()), "multiply-zero"));
+            }
+            simplify_operands(BinaryOperationType::Multiplication, factors)
+        }
         _ => None,
     }
 }
 
+fn simplify_operands(operation: BinaryOperationType, operands: &[Expression]) -> Option<Step> {
+    let identity = operation.identity_element();
+    if operands.len() < 2 || operands.contains(&identity) {
+        let mut remaining = operands.iter().filter(|operand| **operand != identity);
+        let simplified = match (remaining.next(), remaining.next()) {
+            (None, _) => identity,
+            (Some(operand), None) => operand.clone(),
+            (Some(_), Some(_)) => n_ary(
+                operation,
+                operands
+                    .iter()
+                    .filter(|operand| **operand != identity)
+                    .cloned()
+                    .collect(),
+            ),
+        };
+        return Some((simplified, "remove-identities"));
+    }
+    operands.iter().enumerate().find_map(|(i, operand)| {
+        do_simplify_expression(operand).map(|(simplified, rule)| {
+            let mut operands = operands.to_vec();
+            operands[i] = simplified;
+            (n_ary(operation, operands), rule)
+        })
+    })
+}
+
+fn n_ary(operation: BinaryOperationType, operands: Vec<Expression>) -> Expression {
+    if operation == BinaryOperationType::Addition {
+        Expression::Sum(operands)
+    } else {
+        Expression::Product(operands)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::simplifiable::*;
 
     #[test]
     fn test_simplify_expression() {
-        let expr = Expression::Constant(5.);
+        let expr = Expression::Constant(5.into());
         assert_eq!(expr.simplify_expression(), expr);
 
         let expr = Expression::Monomial(Monomial {
-            factor: 5.,
+            factor: 5.into(),
             variable: 'x',
             power: 2,
         });
@@ -144,39 +431,171 @@ mod tests {
 
         let expr = Expression::Function {
             function: Function::Cosine,
-            expression: Box::new(Expression::Constant(5.)),
+            expression: Box::new(Expression::Constant(5.into())),
         };
         assert_eq!(expr.simplify_expression(), expr);
         assert_eq!(
             Expression::Function {
                 function: Function::Sine,
-                expression: Box::new(Expression::Constant(0.))
+                expression: Box::new(Expression::Constant(0.into()))
             }
             .simplify_expression(),
-            Expression::Constant(0.)
+            Expression::Constant(0.into())
         );
 
-        let value = Expression::Constant(5.);
+        let value = Expression::Constant(5.into());
         let expr = Expression::BinaryOperation(BinaryOperation {
             operation: BinaryOperationType::Addition,
             left_value: Box::new(value.clone()),
-            right_value: Box::new(Expression::Constant(0.)),
+            right_value: Box::new(Expression::Constant(0.into())),
         });
         assert_eq!(expr.simplify_expression(), value);
 
         let expr = Expression::BinaryOperation(BinaryOperation {
             operation: BinaryOperationType::Multiplication,
             left_value: Box::new(value.clone()),
-            right_value: Box::new(Expression::Constant(1.)),
+            right_value: Box::new(Expression::Constant(1.into())),
         });
         assert_eq!(expr.simplify_expression(), value);
 
         let expr = Expression::BinaryOperation(BinaryOperation {
             operation: BinaryOperationType::Multiplication,
             left_value: Box::new(value.clone()),
-            right_value: Box::new(Expression::Constant(0.)),
+            right_value: Box::new(Expression::Constant(0.into())),
+        });
+        assert_eq!(expr.simplify_expression(), Expression::Constant(0.into()));
+    }
+
+    #[test]
+    fn test_simplify_with_trace() {
+        // (0 + 2x) * 1
+        let expr = Expression::BinaryOperation(BinaryOperation {
+            operation: BinaryOperationType::Multiplication,
+            left_value: Box::new(Expression::BinaryOperation(BinaryOperation {
+                operation: BinaryOperationType::Addition,
+                left_value: Box::new(Expression::Constant(0.into())),
+                right_value: Box::new(Expression::Monomial(Monomial {
+                    factor: 2.into(),
+                    variable: 'x',
+                    power: 1,
+                })),
+            })),
+            right_value: Box::new(Expression::Constant(1.into())),
+        });
+        let simplification = expr.simplify_with(&SimplificationOptions::default().with_trace());
+        assert_eq!(simplification.stop, SimplificationStop::Converged);
+        assert_eq!(simplification.iterations, 2);
+        let rules: Vec<&str> = simplification
+            .trace
+            .iter()
+            .map(|step| step.rule.as_str())
+            .collect();
+        assert_eq!(rules, ["right-identity", "left-identity"]);
+        assert_eq!(simplification.trace[0].before, expr);
+        assert_eq!(
+            simplification.trace[0].after,
+            simplification.trace[1].before
+        );
+        assert_eq!(simplification.trace[1].after, simplification.expression);
+        assert!(expr
+            .simplify_with(&SimplificationOptions::default())
+            .trace
+            .is_empty());
+    }
+
+    #[test]
+    fn test_simplify_with_budget() {
+        use crate::rewrite::{Pattern, RewriteRule};
+
+        // x + y -> y + x never stops changing the expression
+        let swap = RewriteRule::new(
+            "swap",
+            Pattern::operation(
+                BinaryOperationType::Addition,
+                Pattern::wildcard("a"),
+                Pattern::wildcard("b"),
+            ),
+            Pattern::operation(
+                BinaryOperationType::Addition,
+                Pattern::wildcard("b"),
+                Pattern::wildcard("a"),
+            ),
+        );
+        let options = SimplificationOptions::default()
+            .with_rule_sets(vec![RuleSet::new("swap", vec![swap])])
+            .with_max_iterations(5)
+            .with_trace();
+        let expr = Expression::BinaryOperation(BinaryOperation {
+            operation: BinaryOperationType::Addition,
+            left_value: Box::new(Expression::NamedConstant("pi".to_string())),
+            right_value: Box::new(Expression::NamedConstant("e".to_string())),
+        });
+        let simplification = expr.simplify_with(&options);
+        assert_eq!(simplification.stop, SimplificationStop::IterationLimit);
+        assert_eq!(simplification.trace.len(), 5);
+        assert!(simplification.trace.iter().all(|step| step.rule == "swap"));
+
+        let simplification = expr.simplify_with(
+            &options
+                .with_max_iterations(usize::MAX)
+                .with_time_budget(Duration::from_millis(10)),
+        );
+        assert_eq!(simplification.stop, SimplificationStop::TimeBudget);
+    }
+
+    #[test]
+    fn test_fold_constants() {
+        let operation = |operation, left: Rational, right: Rational| {
+            Expression::BinaryOperation(BinaryOperation {
+                operation,
+                left_value: Box::new(Expression::Constant(left)),
+                right_value: Box::new(Expression::Constant(right)),
+            })
+        };
+        assert_eq!(
+            operation(
+                BinaryOperationType::Addition,
+                Rational::new(1, 2),
+                Rational::new(1, 3)
+            )
+            .simplify_expression(),
+            Expression::Constant(Rational::new(5, 6))
+        );
+        assert_eq!(
+            operation(BinaryOperationType::Power, Rational::new(2, 3), (-2).into())
+                .simplify_expression(),
+            Expression::Constant(Rational::new(9, 4))
+        );
+        let square_root = operation(BinaryOperationType::Power, 2.into(), Rational::new(1, 2));
+        assert_eq!(square_root.simplify_expression(), square_root);
+        let undefined = operation(BinaryOperationType::Division, 1.into(), 0.into());
+        assert_eq!(undefined.simplify_expression(), undefined);
+    }
+
+    #[test]
+    fn test_fold_large_powers() {
+        let power = |base: Rational, exponent: i64| {
+            fold_constants(BinaryOperationType::Power, &base, &exponent.into())
+        };
+        assert_eq!(power(1.into(), 4_000_000_000), Some(1.into()));
+        assert_eq!(power(1.into(), i64::MIN), Some(1.into()));
+        assert_eq!(power((-1).into(), i64::MAX), Some((-1).into()));
+        assert_eq!(power((-1).into(), 4_000_000_000), Some(1.into()));
+        assert_eq!(power(0.into(), i64::MAX), Some(0.into()));
+        assert_eq!(power(0.into(), -1), None);
+        assert_eq!(power(2.into(), 62), Some((1i64 << 62).into()));
+        assert_eq!(power(2.into(), 63), None);
+        assert_eq!(power(Rational::new(-1, 3), -5), Some((-243).into()));
+        assert_eq!(power(Rational::new(1, 2), 4_000_000_000), None);
+        assert_eq!(power(i64::MIN.into(), -1), None);
+        assert_eq!(power(i64::MIN.into(), 1), Some(i64::MIN.into()));
+
+        let huge = Expression::BinaryOperation(BinaryOperation {
+            operation: BinaryOperationType::Power,
+            left_value: Box::new(Expression::Constant(1.into())),
+            right_value: Box::new(Expression::Constant(4_000_000_000i64.into())),
         });
-        assert_eq!(expr.simplify_expression(), Expression::Constant(0.));
+        assert_eq!(huge.simplify_expression(), Expression::Constant(1.into()));
     }
 
     #[test]
@@ -184,12 +603,12 @@ mod tests {
         let expr = Expression::BinaryOperation(BinaryOperation {
             operation: BinaryOperationType::Addition,
             left_value: Box::new(Expression::Monomial(Monomial {
-                factor: 5.,
+                factor: 5.into(),
                 variable: 'x',
                 power: 1,
             })),
             right_value: Box::new(Expression::Monomial(Monomial {
-                factor: 3.,
+                factor: 3.into(),
                 variable: 'x',
                 power: 1,
             })),
@@ -197,7 +616,7 @@ mod tests {
         assert_eq!(
             expr.simplify_expression(),
             Expression::Monomial(Monomial {
-                factor: 8.,
+                factor: 8.into(),
                 variable: 'x',
                 power: 1,
             })
@@ -209,16 +628,16 @@ mod tests {
         assert_eq!(
             Expression::BinaryOperation(BinaryOperation {
                 operation: BinaryOperationType::Multiplication,
-                left_value: Box::new(Expression::Constant(5.)),
+                left_value: Box::new(Expression::Constant(5.into())),
                 right_value: Box::new(Expression::Monomial(Monomial {
-                    factor: 3.,
+                    factor: 3.into(),
                     variable: 'x',
                     power: 2
                 }))
             })
             .simplify_expression(),
             Expression::Monomial(Monomial {
-                factor: 15.,
+                factor: 15.into(),
                 variable: 'x',
                 power: 2
             })
@@ -228,18 +647,39 @@ mod tests {
             Expression::BinaryOperation(BinaryOperation {
                 operation: BinaryOperationType::Multiplication,
                 left_value: Box::new(Expression::Monomial(Monomial {
-                    factor: 3.,
+                    factor: 3.into(),
                     variable: 'x',
                     power: 2
                 })),
-                right_value: Box::new(Expression::Constant(5.)),
+                right_value: Box::new(Expression::Constant(5.into())),
             })
             .simplify_expression(),
             Expression::Monomial(Monomial {
-                factor: 15.,
+                factor: 15.into(),
                 variable: 'x',
                 power: 2
             })
         );
     }
+
+    #[test]
+    fn test_monomial_arithmetic_overflow_is_not_folded() {
+        let huge = Expression::Monomial(Monomial {
+            factor: i64::MAX.into(),
+            variable: 'x',
+            power: 1,
+        });
+        let sum = BinaryOperation {
+            operation: BinaryOperationType::Addition,
+            left_value: Box::new(huge.clone()),
+            right_value: Box::new(huge.clone()),
+        };
+        assert_eq!(simplify_operation(&sum), None);
+        let product = Expression::BinaryOperation(BinaryOperation {
+            operation: BinaryOperationType::Multiplication,
+            left_value: Box::new(Expression::Constant(2.into())),
+            right_value: Box::new(huge),
+        });
+        assert_eq!(product.simplify_expression(), product);
+    }
 }
@@ -1,10 +1,159 @@
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashMap, HashSet};
 
-use crate::expression::{Expression, Variable};
+use crate::assumptions::Assumptions;
+use crate::derivable::Derivable;
+use crate::evaluable::{Evaluable, EvaluationError};
+use crate::expression::{BinaryOperationType, Expression, Rational, Variable};
+use crate::matrix::SymbolicMatrix;
+use crate::simplifiable::{Simplifiable, SimplificationOptions};
+use crate::trigonometric::Trigonometric;
 
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Surface {
     pub surface_variables: HashSet<Variable>,
     pub parametric_variables: HashSet<Variable>,
+    pub embedding: Vec<Expression>,
+    /// Consulted when simplifying the geometric quantities, e.g. `R > r > 0` for a torus.
+    pub assumptions: Assumptions,
+    /// Ranges of the surface variables, a variable without domain ranging over all reals.
+    pub domains: BTreeMap<Variable, Domain>,
+}
+
+/// Range of a surface variable, whose bounds may depend on the parametric variables.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Domain {
+    pub lower: Expression,
+    pub upper: Expression,
+    pub lower_closed: bool,
+    pub upper_closed: bool,
+    /// Both ends are the same point of the surface, as for the angles of a torus.
+    pub periodic: bool,
+}
+
+/// Coefficients of the first fundamental form `E du^2 + 2F du dv + G dv^2`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FirstFundamentalForm {
+    pub e: Expression,
+    pub f: Expression,
+    pub g: Expression,
+}
+
+impl FirstFundamentalForm {
+    /// The metric tensor `[[E, F], [F, G]]`.
+    pub fn matrix(&self) -> SymbolicMatrix {
+        SymbolicMatrix::from_rows(vec![
+            vec![self.e.clone(), self.f.clone()],
+            vec![self.f.clone(), self.g.clone()],
+        ])
+    }
+
+    /// `EG - F^2`, the determinant of the metric.
+    fn determinant(&self) -> Expression {
+        Expression::binary(
+            BinaryOperationType::Substraction,
+            Expression::binary(
+                BinaryOperationType::Multiplication,
+                self.e.clone(),
+                self.g.clone(),
+            ),
+            Expression::binary(
+                BinaryOperationType::Power,
+                self.f.clone(),
+                Expression::Constant(2.into()),
+            ),
+        )
+    }
+}
+
+/// Coefficients of the second fundamental form `L du^2 + 2M du dv + N dv^2`, relative to the
+/// unit normal `x_u × x_v / |x_u × x_v|`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SecondFundamentalForm {
+    pub l: Expression,
+    pub m: Expression,
+    pub n: Expression,
+}
+
+impl SecondFundamentalForm {
+    /// The shape tensor `[[L, M], [M, N]]`.
+    pub fn matrix(&self) -> SymbolicMatrix {
+        SymbolicMatrix::from_rows(vec![
+            vec![self.l.clone(), self.m.clone()],
+            vec![self.m.clone(), self.n.clone()],
+        ])
+    }
+}
+
+impl Domain {
+    pub fn open(lower: Expression, upper: Expression) -> Domain {
+        Domain {
+            lower,
+            upper,
+            lower_closed: false,
+            upper_closed: false,
+            periodic: false,
+        }
+    }
+
+    pub fn closed(lower: Expression, upper: Expression) -> Domain {
+        Domain {
+            lower,
+            upper,
+            lower_closed: true,
+            upper_closed: true,
+            periodic: false,
+        }
+    }
+
+    /// `[lower, upper)`, the upper end being identified with the lower one.
+    pub fn periodic(lower: Expression, upper: Expression) -> Domain {
+        Domain {
+            lower,
+            upper,
+            lower_closed: true,
+            upper_closed: false,
+            periodic: true,
+        }
+    }
+
+    /// Numerical values of the bounds.
+    pub fn bounds(
+        &self,
+        parameters: &HashMap<Variable, f64>,
+    ) -> Result<(f64, f64), EvaluationError> {
+        Ok((
+            self.lower.evaluate(parameters)?,
+            self.upper.evaluate(parameters)?,
+        ))
+    }
+
+    pub fn contains(
+        &self,
+        value: f64,
+        parameters: &HashMap<Variable, f64>,
+    ) -> Result<bool, EvaluationError> {
+        let (lower, upper) = self.bounds(parameters)?;
+        Ok((value > lower || (self.lower_closed && value == lower))
+            && (value < upper || (self.upper_closed && value == upper)))
+    }
+
+    /// The representative of `value` in `[lower, upper)` for a periodic domain, `value` itself
+    /// otherwise.
+    pub fn wrap(
+        &self,
+        value: f64,
+        parameters: &HashMap<Variable, f64>,
+    ) -> Result<f64, EvaluationError> {
+        if !self.periodic {
+            return Ok(value);
+        }
+        let (lower, upper) = self.bounds(parameters)?;
+        Ok(lower + (value - lower).rem_euclid(upper - lower))
+    }
 }
 
 impl Surface {
@@ -21,6 +170,275 @@ impl Surface {
                 .difference(&surface_variables)
                 .copied()
                 .collect(),
+            embedding,
+            assumptions: Assumptions::new(),
+            domains: BTreeMap::new(),
+        }
+    }
+
+    /// Restricts `variable` to `domain`, the variables of its bounds becoming parametric.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `variable` is not a surface variable.
+    pub fn with_domain(mut self, variable: Variable, domain: Domain) -> Surface {
+        assert!(
+            self.surface_variables.contains(&variable),
+            "only surface variables have a domain"
+        );
+        self.parametric_variables.extend(
+            domain
+                .lower
+                .variables()
+                .into_iter()
+                .chain(domain.upper.variables())
+                .filter(|variable| !self.surface_variables.contains(variable)),
+        );
+        self.domains.insert(variable, domain);
+        self
+    }
+
+    pub fn domain(&self, variable: Variable) -> Option<&Domain> {
+        self.domains.get(&variable)
+    }
+
+    /// Whether every coordinate goes around the surface, the ends of its domain being glued.
+    pub fn is_periodic(&self) -> bool {
+        self.surface_variables
+            .iter()
+            .all(|variable| self.domain(*variable).is_some_and(|domain| domain.periodic))
+    }
+
+    /// Whether the coordinates `point`, in the order of [`Surface::coordinates`], lie in the
+    /// domains of the surface.
+    pub fn contains(
+        &self,
+        point: &[f64],
+        parameters: &HashMap<Variable, f64>,
+    ) -> Result<bool, EvaluationError> {
+        for (variable, value) in self.coordinates().iter().zip(point) {
+            if let Some(domain) = self.domain(*variable) {
+                if !domain.contains(*value, parameters)? {
+                    return Ok(false);
+                }
+            }
+        }
+        Ok(true)
+    }
+
+    /// Brings the periodic coordinates of `point` back into their domain, e.g. after a step
+    /// of a geodesic crossed the seam of a torus.
+    pub fn wrap(
+        &self,
+        point: &[f64],
+        parameters: &HashMap<Variable, f64>,
+    ) -> Result<Vec<f64>, EvaluationError> {
+        self.coordinates()
+            .iter()
+            .zip(point)
+            .map(|(variable, value)| match self.domain(*variable) {
+                Some(domain) => domain.wrap(*value, parameters),
+                None => Ok(*value),
+            })
+            .collect()
+    }
+
+    pub fn with_assumptions(self, assumptions: Assumptions) -> Surface {
+        Surface {
+            assumptions,
+            ..self
+        }
+    }
+
+    /// Simplifies `expression` under the assumptions of the surface, coordinates being real.
+    pub fn simplify(&self, expression: &Expression) -> Expression {
+        let assumptions = self
+            .surface_variables
+            .iter()
+            .fold(self.assumptions.clone(), |assumptions, variable| {
+                assumptions.real(*variable)
+            });
+        expression
+            .simplify_with(&SimplificationOptions::default().with_assumptions(assumptions))
+            .expression
+            .simplify_trigonometry()
+    }
+
+    /// Surface variables in alphabetical order, which is the `(u, v)` order of the usual
+    /// parametrizations.
+    pub fn coordinates(&self) -> Vec<Variable> {
+        let mut coordinates: Vec<Variable> = self.surface_variables.iter().copied().collect();
+        coordinates.sort();
+        coordinates
+    }
+
+    /// Partial derivative of the embedding with respect to `variable`.
+    pub fn tangent_vector(&self, variable: Variable) -> Vec<Expression> {
+        self.embedding
+            .iter()
+            .map(|component| component.derive(variable))
+            .collect()
+    }
+
+    /// # Panics
+    ///
+    /// Panics if the surface does not have exactly two surface variables.
+    pub fn first_fundamental_form(&self) -> FirstFundamentalForm {
+        let coordinates = self.coordinates();
+        assert_eq!(coordinates.len(), 2, "a surface has two coordinates");
+        let x_u = self.tangent_vector(coordinates[0]);
+        let x_v = self.tangent_vector(coordinates[1]);
+        FirstFundamentalForm {
+            e: self.simplify(&dot_product(&x_u, &x_u)),
+            f: self.simplify(&dot_product(&x_u, &x_v)),
+            g: self.simplify(&dot_product(&x_v, &x_v)),
+        }
+    }
+
+    /// `sqrt(EG - F^2)`, the density of the area measure in the coordinates.
+    pub fn area_element(&self) -> Expression {
+        self.area_element_of(&self.first_fundamental_form())
+    }
+
+    fn area_element_of(&self, first: &FirstFundamentalForm) -> Expression {
+        self.simplify(&Expression::binary(
+            BinaryOperationType::Power,
+            first.determinant(),
+            Expression::Constant(Rational::new(1, 2)),
+        ))
+    }
+
+    /// `x_u × x_v`, normal to the surface with length the area element.
+    fn normal_vector(&self) -> Vec<Expression> {
+        let coordinates = self.coordinates();
+        assert_eq!(coordinates.len(), 2, "a surface has two coordinates");
+        cross_product(
+            &self.tangent_vector(coordinates[0]),
+            &self.tangent_vector(coordinates[1]),
+        )
+        .iter()
+        .map(|component| self.simplify(component))
+        .collect()
+    }
+
+    /// `x_u × x_v / |x_u × x_v|`.
+    ///
+    /// # Panics
+    ///
+    /// Panics unless the surface has two coordinates and is embedded in three dimensions.
+    pub fn unit_normal(&self) -> Vec<Expression> {
+        let area_element = self.area_element();
+        self.normal_vector()
+            .into_iter()
+            .map(|component| {
+                self.simplify(&Expression::binary(
+                    BinaryOperationType::Division,
+                    component,
+                    area_element.clone(),
+                ))
+            })
+            .collect()
+    }
+
+    /// # Panics
+    ///
+    /// Panics unless the surface has two coordinates and is embedded in three dimensions.
+    pub fn second_fundamental_form(&self) -> SecondFundamentalForm {
+        self.second_fundamental_form_of(&self.area_element())
+    }
+
+    fn second_fundamental_form_of(&self, area_element: &Expression) -> SecondFundamentalForm {
+        let coordinates = self.coordinates();
+        let normal = self.normal_vector();
+        // second derivative of the embedding projected on the unit normal
+        let coefficient = |first: Variable, second: Variable| {
+            let derivative: Vec<Expression> = self
+                .embedding
+                .iter()
+                .map(|component| component.derive(first).derive(second))
+                .collect();
+            self.simplify(&Expression::binary(
+                BinaryOperationType::Division,
+                self.simplify(&dot_product(&derivative, &normal)),
+                area_element.clone(),
+            ))
+        };
+        SecondFundamentalForm {
+            l: coefficient(coordinates[0], coordinates[0]),
+            m: coefficient(coordinates[0], coordinates[1]),
+            n: coefficient(coordinates[1], coordinates[1]),
         }
     }
+
+    /// `K = (LN - M^2) / (EG - F^2)`.
+    pub fn gaussian_curvature(&self) -> Expression {
+        let first = self.first_fundamental_form();
+        let SecondFundamentalForm { l, m, n } =
+            self.second_fundamental_form_of(&self.area_element_of(&first));
+        self.simplify(&Expression::binary(
+            BinaryOperationType::Division,
+            Expression::binary(
+                BinaryOperationType::Substraction,
+                Expression::binary(BinaryOperationType::Multiplication, l, n),
+                Expression::binary(
+                    BinaryOperationType::Power,
+                    m,
+                    Expression::Constant(2.into()),
+                ),
+            ),
+            first.determinant(),
+        ))
+    }
+
+    /// `H = (EN - 2FM + GL) / 2(EG - F^2)`, the mean of the principal curvatures with respect
+    /// to the unit normal.
+    pub fn mean_curvature(&self) -> Expression {
+        let first = self.first_fundamental_form();
+        let SecondFundamentalForm { l, m, n } =
+            self.second_fundamental_form_of(&self.area_element_of(&first));
+        let numerator = Expression::Sum(vec![
+            Expression::Product(vec![first.e.clone(), n]),
+            Expression::Product(vec![Expression::Constant((-2).into()), first.f.clone(), m]),
+            Expression::Product(vec![first.g.clone(), l]),
+        ]);
+        self.simplify(&Expression::binary(
+            BinaryOperationType::Division,
+            numerator,
+            Expression::Product(vec![Expression::Constant(2.into()), first.determinant()]),
+        ))
+    }
+}
+
+fn cross_product(left: &[Expression], right: &[Expression]) -> Vec<Expression> {
+    assert_eq!(
+        left.len(),
+        3,
+        "the cross product is defined in three dimensions"
+    );
+    (0..3)
+        .map(|i| {
+            let (j, k) = ((i + 1) % 3, (i + 2) % 3);
+            Expression::binary(
+                BinaryOperationType::Substraction,
+                Expression::binary(
+                    BinaryOperationType::Multiplication,
+                    left[j].clone(),
+                    right[k].clone(),
+                ),
+                Expression::binary(
+                    BinaryOperationType::Multiplication,
+                    left[k].clone(),
+                    right[j].clone(),
+                ),
+            )
+        })
+        .collect()
+}
+
+pub(crate) fn dot_product(left: &[Expression], right: &[Expression]) -> Expression {
+    left.iter()
+        .zip(right)
+        .map(|(l, r)| Expression::binary(BinaryOperationType::Multiplication, l.clone(), r.clone()))
+        .reduce(|acc, product| Expression::binary(BinaryOperationType::Addition, acc, product))
+        .unwrap_or(Expression::Constant(0.into()))
 }
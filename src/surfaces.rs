@@ -0,0 +1,177 @@
+use std::collections::HashSet;
+
+use crate::assumptions::Assumptions;
+use crate::expression::{BinaryOperationType, Expression, Function, Rational, Variable};
+use crate::surface::{Domain, Surface};
+
+fn variable(variable: Variable) -> Expression {
+    scaled(1, variable)
+}
+
+fn scaled(factor: impl Into<Rational>, variable: Variable) -> Expression {
+    Expression::monomial(factor, variable, 1)
+}
+
+fn constant(value: impl Into<Rational>) -> Expression {
+    Expression::Constant(value.into())
+}
+
+fn multiply(left: Expression, right: Expression) -> Expression {
+    Expression::binary(BinaryOperationType::Multiplication, left, right)
+}
+
+fn add(left: Expression, right: Expression) -> Expression {
+    Expression::binary(BinaryOperationType::Addition, left, right)
+}
+
+fn cos(expression: Expression) -> Expression {
+    Expression::function(Function::Cosine, expression)
+}
+
+fn sin(expression: Expression) -> Expression {
+    Expression::function(Function::Sine, expression)
+}
+
+fn two_pi() -> Expression {
+    multiply(constant(2), Expression::NamedConstant("pi".to_string()))
+}
+
+/// `[-extent, extent]`
+fn symmetric(extent: Variable) -> Domain {
+    Domain::closed(scaled(-1, extent), variable(extent))
+}
+
+/// `(radius cos u, radius sin u, height)`, the surface of revolution of a profile curve.
+fn revolution(radius: Expression, height: Expression) -> Vec<Expression> {
+    vec![
+        multiply(radius.clone(), cos(variable('u'))),
+        multiply(radius, sin(variable('u'))),
+        height,
+    ]
+}
+
+fn surface(embedding: Vec<Expression>) -> Surface {
+    Surface::from_embedding(HashSet::from(['u', 'v']), embedding)
+}
+
+/// Sphere of radius `r`, `u ∈ [0, π]` being the polar angle and `v ∈ [0, 2π)` the longitude.
+pub fn sphere() -> Surface {
+    let r = variable('r');
+    surface(vec![
+        multiply(r.clone(), multiply(sin(variable('u')), cos(variable('v')))),
+        multiply(r.clone(), multiply(sin(variable('u')), sin(variable('v')))),
+        multiply(r, cos(variable('u'))),
+    ])
+    .with_assumptions(Assumptions::new().positive('r'))
+    .with_domain(
+        'u',
+        Domain::closed(constant(0), Expression::NamedConstant("pi".to_string())),
+    )
+    .with_domain('v', Domain::periodic(constant(0), two_pi()))
+}
+
+/// Torus of tube radius `r` around a circle of radius `R > r`, both angles in `[0, 2π)`.
+pub fn torus() -> Surface {
+    let radius = add(variable('R'), multiply(variable('r'), cos(variable('v'))));
+    surface(revolution(
+        radius,
+        multiply(variable('r'), sin(variable('v'))),
+    ))
+    .with_assumptions(Assumptions::new().positive('r').greater_than('R', 'r'))
+    .with_domain('u', Domain::periodic(constant(0), two_pi()))
+    .with_domain('v', Domain::periodic(constant(0), two_pi()))
+}
+
+/// Cylinder of radius `r` and height `h`, `v ∈ [0, h]` running along the axis.
+pub fn cylinder() -> Surface {
+    surface(revolution(variable('r'), variable('v')))
+        .with_assumptions(Assumptions::new().positive('r').positive('h'))
+        .with_domain('u', Domain::periodic(constant(0), two_pi()))
+        .with_domain('v', Domain::closed(constant(0), variable('h')))
+}
+
+/// Cone `z = a ρ` of slope `a`, `v ∈ (0, h]` being the distance to the axis, the apex excluded.
+pub fn cone() -> Surface {
+    surface(revolution(
+        variable('v'),
+        multiply(variable('a'), variable('v')),
+    ))
+    .with_assumptions(Assumptions::new().positive('a').positive('h'))
+    .with_domain('u', Domain::periodic(constant(0), two_pi()))
+    .with_domain(
+        'v',
+        Domain {
+            upper_closed: true,
+            ..Domain::open(constant(0), variable('h'))
+        },
+    )
+}
+
+/// Catenoid `(c cosh v cos u, c cosh v sin u, c v)` of waist radius `c`, cut at `v ∈ [-h, h]`.
+pub fn catenoid() -> Surface {
+    let cosh = multiply(
+        constant(Rational::new(1, 2)),
+        add(
+            Expression::function(Function::Exponential, variable('v')),
+            Expression::function(Function::Exponential, scaled(-1, 'v')),
+        ),
+    );
+    surface(revolution(
+        multiply(variable('c'), cosh),
+        multiply(variable('c'), variable('v')),
+    ))
+    .with_assumptions(Assumptions::new().positive('c').positive('h'))
+    .with_domain('u', Domain::periodic(constant(0), two_pi()))
+    .with_domain('v', symmetric('h'))
+}
+
+/// One turn `u ∈ [0, 2π]` of the helicoid `(v cos u, v sin u, c u)` of pitch `2π c`, with
+/// `v ∈ [-r, r]`.
+pub fn helicoid() -> Surface {
+    surface(revolution(
+        variable('v'),
+        multiply(variable('c'), variable('u')),
+    ))
+    .with_assumptions(Assumptions::new().positive('c').positive('r'))
+    .with_domain('u', Domain::closed(constant(0), two_pi()))
+    .with_domain('v', symmetric('r'))
+}
+
+/// Enneper surface `(u - u³/3 + u v², v - v³/3 + v u², u² - v²)` over `[-r, r]²`.
+pub fn enneper() -> Surface {
+    let cube = |variable| Expression::monomial(Rational::new(-1, 3), variable, 3);
+    let square = |factor: i64, variable| Expression::monomial(factor, variable, 2);
+    surface(vec![
+        Expression::Sum(vec![
+            variable('u'),
+            cube('u'),
+            multiply(variable('u'), square(1, 'v')),
+        ]),
+        Expression::Sum(vec![
+            variable('v'),
+            cube('v'),
+            multiply(variable('v'), square(1, 'u')),
+        ]),
+        add(square(1, 'u'), square(-1, 'v')),
+    ])
+    .with_assumptions(Assumptions::new().positive('r'))
+    .with_domain('u', symmetric('r'))
+    .with_domain('v', symmetric('r'))
+}
+
+/// Möbius strip of radius `R` and half-width `w < R`, `v ∈ [-w, w]` running across the band.
+///
+/// `(u + 2π, v)` and `(u, -v)` are the same point, so `u ∈ [0, 2π]` is not marked periodic.
+pub fn mobius_strip() -> Surface {
+    let half_angle = scaled(Rational::new(1, 2), 'u');
+    surface(revolution(
+        add(
+            variable('R'),
+            multiply(variable('v'), cos(half_angle.clone())),
+        ),
+        multiply(variable('v'), sin(half_angle)),
+    ))
+    .with_assumptions(Assumptions::new().positive('w').greater_than('R', 'w'))
+    .with_domain('u', Domain::closed(constant(0), two_pi()))
+    .with_domain('v', symmetric('w'))
+}
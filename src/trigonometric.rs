@@ -0,0 +1,987 @@
+use num_traits::{CheckedAdd, CheckedDiv, CheckedMul, CheckedSub, One, Signed, Zero};
+
+use crate::expandable::{group_factors, same_factors, Expandable};
+use crate::expression::{
+    BinaryOperation, BinaryOperationType, Expression, Function, Monomial, Rational,
+};
+use crate::simplifiable::{monomial_power, Simplifiable};
+
+pub trait Trigonometric {
+    /// Applies parity, special values at rational multiples of pi and the Pythagorean identity.
+    fn simplify_trigonometry(&self) -> Self;
+    /// Rewrites sines and cosines of sums and integer multiples with the angle-addition formulas.
+    fn expand_trigonometry(&self) -> Self;
+    /// Rewrites products and powers of sines and cosines as sums (product-to-sum, half-angle).
+    fn linearize_trigonometry(&self) -> Self;
+    /// Rewrites sums of two sines or two cosines as products (sum-to-product).
+    fn factor_trigonometry(&self) -> Self;
+}
+
+impl Trigonometric for Expression {
+    fn simplify_trigonometry(&self) -> Expression {
+        let expression = self.map_children(Expression::simplify_trigonometry);
+        let simplified = match &expression {
+            Expression::Function {
+                function,
+                expression,
+            } if function.is_trigonometric() => {
+                simplify_trigonometric_function(*function, expression)
+            }
+            Expression::BinaryOperation(operation)
+                if matches!(
+                    operation.operation,
+                    BinaryOperationType::Addition | BinaryOperationType::Substraction
+                ) =>
+            {
+                simplify_trigonometric_sum(&expression)
+            }
+            Expression::Sum(_) => simplify_trigonometric_sum(&expression),
+            _ => None,
+        };
+        simplified.unwrap_or(expression).simplify_expression()
+    }
+
+    fn expand_trigonometry(&self) -> Expression {
+        let expression = self.map_children(Expression::expand_trigonometry);
+        match &expression {
+            Expression::Function {
+                function,
+                expression: angle,
+            } if function.is_trigonometric() && split_angle(angle).is_some() => {
+                let (sine, cosine) = sine_and_cosine(angle);
+                match function {
+                    Function::Sine => sine,
+                    _ => cosine,
+                }
+            }
+            _ => expression,
+        }
+    }
+
+    fn linearize_trigonometry(&self) -> Expression {
+        let mut expression = self
+            .map_children(Expression::linearize_trigonometry)
+            .expand();
+        loop {
+            let mut summands = summands(&expression);
+            let position = summands
+                .iter()
+                .position(|summand| trigonometric_factors(summand).len() >= 2);
+            let index = match position {
+                Some(index) => index,
+                None => break,
+            };
+            let summand = summands.remove(index);
+            let trigonometric = trigonometric_factors(&summand);
+            let (first, second) = (trigonometric[0], trigonometric[1]);
+            let mut rest = summand.factors.clone();
+            rest.remove(second);
+            rest.remove(first);
+            let linearized = Expression::binary(
+                BinaryOperationType::Multiplication,
+                Summand {
+                    coefficient: summand.coefficient,
+                    factors: rest,
+                }
+                .to_expression(),
+                product_to_sum(&summand.factors[first], &summand.factors[second]),
+            );
+            expression = Expression::binary(
+                BinaryOperationType::Addition,
+                summands_to_expression(&summands),
+                linearized,
+            )
+            .expand();
+        }
+        expression.simplify_trigonometry()
+    }
+
+    fn factor_trigonometry(&self) -> Expression {
+        let expression = self.map_children(Expression::factor_trigonometry);
+        if !is_sum(&expression) {
+            return expression;
+        }
+        let mut summands = summands(&expression);
+        let mut changed = false;
+        'search: for i in 0..summands.len() {
+            for j in (i + 1)..summands.len() {
+                if let Some(factored) = sum_to_product(&summands[i], &summands[j]) {
+                    summands.remove(j);
+                    summands[i] = factored;
+                    changed = true;
+                    break 'search;
+                }
+            }
+        }
+        if changed {
+            summands_to_expression(&summands).factor_trigonometry()
+        } else {
+            expression
+        }
+    }
+}
+
+/// Product of a rational coefficient and of factors that are neither constants nor products.
+///
+/// Monomials are split into their factor and repeated occurrences of their variable. A constant
+/// that would overflow the coefficient is kept as a factor.
+#[derive(Debug, Clone, PartialEq)]
+struct Summand {
+    coefficient: Rational,
+    factors: Vec<Expression>,
+}
+
+impl Summand {
+    fn from_expression(expression: &Expression) -> Summand {
+        let mut summand = Summand {
+            coefficient: Rational::one(),
+            factors: vec![],
+        };
+        summand.push_factor(expression);
+        summand
+    }
+
+    fn push_factor(&mut self, expression: &Expression) {
+        match expression {
+            Expression::Constant(value) => self.scale(*value),
+            Expression::Monomial(m) => {
+                self.scale(m.factor);
+                self.factors.extend(std::iter::repeat_n(
+                    Expression::Monomial(Monomial {
+                        factor: Rational::one(),
+                        variable: m.variable,
+                        power: 1,
+                    }),
+                    m.power as usize,
+                ));
+            }
+            Expression::BinaryOperation(operation)
+                if operation.operation == BinaryOperationType::Multiplication =>
+            {
+                self.push_factor(&operation.left_value);
+                self.push_factor(&operation.right_value);
+            }
+            Expression::Product(factors) => {
+                for factor in factors {
+                    self.push_factor(factor);
+                }
+            }
+            Expression::BinaryOperation(operation)
+                if operation.operation == BinaryOperationType::Power =>
+            {
+                match &*operation.right_value {
+                    Expression::Constant(exponent)
+                        if exponent.is_integer()
+                            && exponent.is_positive()
+                            && *exponent <= Rational::from(u8::MAX as i64) =>
+                    {
+                        for _ in 0..exponent.to_integer() {
+                            self.push_factor(&operation.left_value);
+                        }
+                    }
+                    _ => self.factors.push(expression.clone()),
+                }
+            }
+            _ => self.factors.push(expression.clone()),
+        }
+    }
+
+    fn scale(&mut self, value: Rational) {
+        match self.coefficient.checked_mul(&value) {
+            Some(coefficient) => self.coefficient = coefficient,
+            None => self.factors.push(Expression::Constant(value)),
+        }
+    }
+
+    fn is_like(&self, other: &Summand) -> bool {
+        same_factors(&self.factors, &other.factors)
+    }
+
+    /// Returns the other factors if `factor` appears at least twice.
+    fn without_square_of(&self, factor: &Expression) -> Option<Vec<Expression>> {
+        let mut factors = self.factors.clone();
+        for _ in 0..2 {
+            let index = factors.iter().position(|f| f == factor)?;
+            factors.remove(index);
+        }
+        Some(factors)
+    }
+
+    fn to_expression(&self) -> Expression {
+        let mut factors = group_factors(&self.factors)
+            .into_iter()
+            .map(|factor| match factor {
+                Expression::BinaryOperation(BinaryOperation {
+                    operation: BinaryOperationType::Power,
+                    left_value,
+                    right_value,
+                }) => match (*left_value, *right_value) {
+                    (Expression::Monomial(m), Expression::Constant(power)) => {
+                        match monomial_power(&m, &power) {
+                            Some(m) => Expression::Monomial(m),
+                            None => Expression::binary(
+                                BinaryOperationType::Power,
+                                Expression::Monomial(m),
+                                Expression::Constant(power),
+                            ),
+                        }
+                    }
+                    (left_value, right_value) => {
+                        Expression::binary(BinaryOperationType::Power, left_value, right_value)
+                    }
+                },
+                factor => factor,
+            })
+            .collect::<Vec<_>>();
+        if factors.is_empty() {
+            return Expression::Constant(self.coefficient);
+        }
+        if !self.coefficient.is_one() {
+            match &mut factors[0] {
+                Expression::Monomial(m) if m.factor.checked_mul(&self.coefficient).is_some() => {
+                    m.factor *= self.coefficient
+                }
+                _ => factors.insert(0, Expression::Constant(self.coefficient)),
+            }
+        }
+        factors
+            .into_iter()
+            .reduce(|acc, factor| {
+                Expression::binary(BinaryOperationType::Multiplication, acc, factor)
+            })
+            .unwrap()
+    }
+}
+
+fn is_sum(expression: &Expression) -> bool {
+    matches!(
+        expression,
+        Expression::BinaryOperation(operation)
+            if matches!(
+                operation.operation,
+                BinaryOperationType::Addition | BinaryOperationType::Substraction
+            )
+    ) || matches!(expression, Expression::Sum(_))
+}
+
+fn summands(expression: &Expression) -> Vec<Summand> {
+    match expression {
+        Expression::BinaryOperation(op) if is_sum(expression) => {
+            let mut left = summands(&op.left_value);
+            let right = summands(&op.right_value);
+            if op.operation == BinaryOperationType::Substraction {
+                left.extend(right.into_iter().map(|summand| Summand {
+                    coefficient: -summand.coefficient,
+                    ..summand
+                }));
+            } else {
+                left.extend(right);
+            }
+            left
+        }
+        Expression::Sum(terms) => terms.iter().flat_map(summands).collect(),
+        Expression::Constant(value) if value.is_zero() => vec![],
+        _ => vec![Summand::from_expression(expression)],
+    }
+}
+
+fn summands_to_expression(summands: &[Summand]) -> Expression {
+    let mut summands = summands.iter();
+    let first = match summands.next() {
+        Some(summand) => summand.to_expression(),
+        None => return Expression::Constant(0.into()),
+    };
+    summands.fold(first, |acc, summand| {
+        if summand.coefficient.is_negative() {
+            let positive = Summand {
+                coefficient: -summand.coefficient,
+                factors: summand.factors.clone(),
+            };
+            Expression::binary(
+                BinaryOperationType::Substraction,
+                acc,
+                positive.to_expression(),
+            )
+        } else {
+            Expression::binary(BinaryOperationType::Addition, acc, summand.to_expression())
+        }
+    })
+}
+
+fn trigonometric_function(function: Function, angle: &Expression) -> Expression {
+    Expression::Function {
+        function,
+        expression: Box::new(angle.clone()),
+    }
+}
+
+fn simplify_trigonometric_function(function: Function, angle: &Expression) -> Option<Expression> {
+    if let Some(multiple) = pi_multiple(angle) {
+        let multiple = match function {
+            Function::Sine => Some(multiple),
+            _ => multiple.checked_add(&Rational::new(1, 2)),
+        };
+        if let Some(value) = multiple.and_then(sine_of_pi_multiple) {
+            return Some(value);
+        }
+    }
+    let opposite = negated(angle)?;
+    Some(match function {
+        Function::Sine => Expression::binary(
+            BinaryOperationType::Multiplication,
+            Expression::Constant((-1).into()),
+            trigonometric_function(Function::Sine, &opposite),
+        ),
+        _ => trigonometric_function(Function::Cosine, &opposite),
+    })
+}
+
+/// Returns `k` if `angle` is `k * pi`.
+fn pi_multiple(angle: &Expression) -> Option<Rational> {
+    match angle.expand() {
+        Expression::Constant(value) if value.is_zero() => Some(Rational::zero()),
+        Expression::NamedConstant(name) if name == "pi" => Some(Rational::one()),
+        Expression::BinaryOperation(BinaryOperation {
+            operation: BinaryOperationType::Multiplication,
+            left_value,
+            right_value,
+        }) => match (*left_value, *right_value) {
+            (Expression::Constant(multiple), Expression::NamedConstant(name))
+            | (Expression::NamedConstant(name), Expression::Constant(multiple))
+                if name == "pi" =>
+            {
+                Some(multiple)
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// `None` when the value is not one of the tabulated ones, or when reducing the multiple
+/// overflows.
+fn sine_of_pi_multiple(multiple: Rational) -> Option<Expression> {
+    let two = Rational::from(2);
+    let multiple = multiple.checked_sub(&multiple.checked_div(&two)?.floor().checked_mul(&two)?)?;
+    let (multiple, sign) = if multiple >= Rational::one() {
+        (multiple.checked_sub(&Rational::one())?, -Rational::one())
+    } else {
+        (multiple, Rational::one())
+    };
+    let multiple = if multiple > Rational::new(1, 2) {
+        Rational::one().checked_sub(&multiple)?
+    } else {
+        multiple
+    };
+    let square_root = |n: i64| {
+        Expression::binary(
+            BinaryOperationType::Multiplication,
+            Expression::Constant(sign * Rational::new(1, 2)),
+            Expression::binary(
+                BinaryOperationType::Power,
+                Expression::Constant(n.into()),
+                Expression::Constant(Rational::new(1, 2)),
+            ),
+        )
+    };
+    if multiple.is_zero() {
+        Some(Expression::Constant(Rational::zero()))
+    } else if multiple == Rational::new(1, 6) {
+        Some(Expression::Constant(sign * Rational::new(1, 2)))
+    } else if multiple == Rational::new(1, 4) {
+        Some(square_root(2))
+    } else if multiple == Rational::new(1, 3) {
+        Some(square_root(3))
+    } else if multiple == Rational::new(1, 2) {
+        Some(Expression::Constant(sign))
+    } else {
+        None
+    }
+}
+
+/// Returns `-angle` if `angle` carries an explicit negative factor.
+fn negated(angle: &Expression) -> Option<Expression> {
+    match angle {
+        Expression::Monomial(m) if m.factor.is_negative() => Some(Expression::Monomial(Monomial {
+            factor: -m.factor,
+            ..m.clone()
+        })),
+        Expression::BinaryOperation(BinaryOperation {
+            operation: BinaryOperationType::Multiplication,
+            left_value,
+            right_value,
+        }) => match (&**left_value, &**right_value) {
+            (Expression::Constant(factor), other) | (other, Expression::Constant(factor))
+                if factor.is_negative() =>
+            {
+                Some(
+                    Expression::binary(
+                        BinaryOperationType::Multiplication,
+                        Expression::Constant(-factor),
+                        other.clone(),
+                    )
+                    .simplify_expression(),
+                )
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn simplify_trigonometric_sum(expression: &Expression) -> Option<Expression> {
+    let mut summands = summands(expression);
+    let mut changed = false;
+    while merge_like_summands(&mut summands) || apply_pythagorean_identity(&mut summands) {
+        changed = true;
+    }
+    changed.then(|| summands_to_expression(&summands))
+}
+
+fn merge_like_summands(summands: &mut Vec<Summand>) -> bool {
+    for i in 0..summands.len() {
+        for j in (i + 1)..summands.len() {
+            if !summands[i].is_like(&summands[j]) {
+                continue;
+            }
+            if let Some(coefficient) = summands[i]
+                .coefficient
+                .checked_add(&summands[j].coefficient)
+            {
+                summands.remove(j);
+                summands[i].coefficient = coefficient;
+                if coefficient.is_zero() {
+                    summands.remove(i);
+                }
+                return true;
+            }
+        }
+    }
+    false
+}
+
+/// Rewrites `K sin^2 + K cos^2` as `K`, and `K - K sin^2` (resp. `cos^2`) as `K cos^2`
+/// (resp. `sin^2`).
+fn apply_pythagorean_identity(summands: &mut Vec<Summand>) -> bool {
+    for i in 0..summands.len() {
+        let squares = summands[i]
+            .factors
+            .iter()
+            .filter_map(|factor| match factor {
+                Expression::Function {
+                    function,
+                    expression,
+                } if function.is_trigonometric() => Some((*function, (**expression).clone())),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        for (function, angle) in squares {
+            let rest =
+                match summands[i].without_square_of(&trigonometric_function(function, &angle)) {
+                    Some(rest) => rest,
+                    None => continue,
+                };
+            let complement = match function {
+                Function::Sine => Function::Cosine,
+                _ => Function::Sine,
+            };
+            let complement_square = trigonometric_function(complement, &angle);
+            let coefficient = summands[i].coefficient;
+            for j in 0..summands.len() {
+                if i == j {
+                    continue;
+                }
+                let other = &summands[j];
+                if other.coefficient == coefficient {
+                    if let Some(other_rest) = other.without_square_of(&complement_square) {
+                        if same_factors(&rest, &other_rest) {
+                            summands[i] = Summand {
+                                coefficient,
+                                factors: rest,
+                            };
+                            summands.remove(j);
+                            return true;
+                        }
+                    }
+                }
+                if other.coefficient == -coefficient && same_factors(&rest, &other.factors) {
+                    let mut factors = rest;
+                    factors.push(complement_square.clone());
+                    factors.push(complement_square);
+                    summands[j] = Summand {
+                        coefficient: -coefficient,
+                        factors,
+                    };
+                    summands.remove(i);
+                    return true;
+                }
+            }
+        }
+    }
+    false
+}
+
+/// Splits an angle into two parts whose sum it is, `a + b`, `a - b` or `(k - 1)x + x`.
+fn split_angle(angle: &Expression) -> Option<(Expression, Expression)> {
+    let angle = angle.expand();
+    match &angle {
+        Expression::BinaryOperation(op) if is_sum(&angle) => {
+            let right = if op.operation == BinaryOperationType::Substraction {
+                Expression::binary(
+                    BinaryOperationType::Multiplication,
+                    Expression::Constant((-1).into()),
+                    (*op.right_value).clone(),
+                )
+                .expand()
+            } else {
+                (*op.right_value).clone()
+            };
+            Some(((*op.left_value).clone(), right))
+        }
+        _ => {
+            let summand = Summand::from_expression(&angle);
+            let multiple = summand.coefficient.abs();
+            if multiple.is_integer() && multiple > Rational::one() {
+                let unit = Summand {
+                    coefficient: summand.coefficient / multiple,
+                    factors: summand.factors,
+                };
+                let rest = Summand {
+                    coefficient: unit.coefficient * (multiple - Rational::one()),
+                    factors: unit.factors.clone(),
+                };
+                Some((rest.to_expression(), unit.to_expression()))
+            } else {
+                None
+            }
+        }
+    }
+}
+
+/// `(sin angle, cos angle)` expanded with the angle-addition formulas.
+///
+/// Both are computed together so that each part of the angle, in particular each `(k - 1)x` of
+/// a multiple `kx`, is expanded once.
+fn sine_and_cosine(angle: &Expression) -> (Expression, Expression) {
+    let Some((a, b)) = split_angle(angle) else {
+        return (
+            trigonometric_function(Function::Sine, angle),
+            trigonometric_function(Function::Cosine, angle),
+        );
+    };
+    let (sin_a, cos_a) = sine_and_cosine(&a);
+    let (sin_b, cos_b) = sine_and_cosine(&b);
+    let multiply = |left: &Expression, right: &Expression| {
+        Expression::binary(
+            BinaryOperationType::Multiplication,
+            left.clone(),
+            right.clone(),
+        )
+    };
+    (
+        Expression::binary(
+            BinaryOperationType::Addition,
+            multiply(&sin_a, &cos_b),
+            multiply(&cos_a, &sin_b),
+        )
+        .expand(),
+        Expression::binary(
+            BinaryOperationType::Substraction,
+            multiply(&cos_a, &cos_b),
+            multiply(&sin_a, &sin_b),
+        )
+        .expand(),
+    )
+}
+
+fn trigonometric_factors(summand: &Summand) -> Vec<usize> {
+    summand
+        .factors
+        .iter()
+        .enumerate()
+        .filter(|(_, factor)| {
+            matches!(factor, Expression::Function { function, .. } if function.is_trigonometric())
+        })
+        .map(|(index, _)| index)
+        .collect()
+}
+
+fn angle_of(expression: &Expression) -> (Function, Expression) {
+    match expression {
+        Expression::Function {
+            function,
+            expression,
+        } => (*function, (**expression).clone()),
+        _ => unreachable!("not a trigonometric function"),
+    }
+}
+
+fn product_to_sum(first: &Expression, second: &Expression) -> Expression {
+    let (first_function, a) = angle_of(first);
+    let (second_function, b) = angle_of(second);
+    let sum = Expression::binary(BinaryOperationType::Addition, a.clone(), b.clone()).expand();
+    let difference =
+        Expression::binary(BinaryOperationType::Substraction, a.clone(), b.clone()).expand();
+    let half = |expression| {
+        Expression::binary(
+            BinaryOperationType::Multiplication,
+            Expression::Constant(Rational::new(1, 2)),
+            expression,
+        )
+    };
+    let sin = |angle: &Expression| trigonometric_function(Function::Sine, angle);
+    let cos = |angle: &Expression| trigonometric_function(Function::Cosine, angle);
+    half(match (first_function, second_function) {
+        (Function::Sine, Function::Sine) => Expression::binary(
+            BinaryOperationType::Substraction,
+            cos(&difference),
+            cos(&sum),
+        ),
+        (Function::Cosine, Function::Cosine) => {
+            Expression::binary(BinaryOperationType::Addition, cos(&difference), cos(&sum))
+        }
+        (Function::Sine, Function::Cosine) => {
+            Expression::binary(BinaryOperationType::Addition, sin(&sum), sin(&difference))
+        }
+        (Function::Cosine, Function::Sine) => Expression::binary(
+            BinaryOperationType::Substraction,
+            sin(&sum),
+            sin(&difference),
+        ),
+        _ => unreachable!("not a trigonometric function"),
+    })
+}
+
+fn sum_to_product(first: &Summand, second: &Summand) -> Option<Summand> {
+    let single_function = |summand: &Summand| match summand.factors.as_slice() {
+        [function @ Expression::Function { function: f, .. }] if f.is_trigonometric() => {
+            Some(angle_of(function))
+        }
+        _ => None,
+    };
+    let (first_function, a) = single_function(first)?;
+    let (second_function, b) = single_function(second)?;
+    if first_function != second_function || first.coefficient.abs() != second.coefficient.abs() {
+        return None;
+    }
+    let halved = |operation_type| {
+        Expression::binary(
+            BinaryOperationType::Division,
+            Expression::binary(operation_type, a.clone(), b.clone()),
+            Expression::Constant(2.into()),
+        )
+        .expand()
+    };
+    let half_sum = halved(BinaryOperationType::Addition);
+    let half_difference = halved(BinaryOperationType::Substraction);
+    let sin = |angle: &Expression| trigonometric_function(Function::Sine, angle);
+    let cos = |angle: &Expression| trigonometric_function(Function::Cosine, angle);
+    let is_sum = first.coefficient == second.coefficient;
+    let (coefficient, factors) = match (first_function, is_sum) {
+        (Function::Sine, true) => (2, vec![sin(&half_sum), cos(&half_difference)]),
+        (Function::Sine, false) => (2, vec![cos(&half_sum), sin(&half_difference)]),
+        (Function::Cosine, true) => (2, vec![cos(&half_sum), cos(&half_difference)]),
+        (Function::Cosine, false) => (-2, vec![sin(&half_sum), sin(&half_difference)]),
+        _ => return None,
+    };
+    Some(Summand {
+        coefficient: first
+            .coefficient
+            .checked_mul(&Rational::from(coefficient))?,
+        factors,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    use crate::evaluable::Evaluable;
+
+    fn x() -> Expression {
+        Expression::Monomial(Monomial {
+            factor: 1.into(),
+            variable: 'x',
+            power: 1,
+        })
+    }
+
+    fn y() -> Expression {
+        Expression::Monomial(Monomial {
+            factor: 1.into(),
+            variable: 'y',
+            power: 1,
+        })
+    }
+
+    fn sin(angle: Expression) -> Expression {
+        trigonometric_function(Function::Sine, &angle)
+    }
+
+    fn cos(angle: Expression) -> Expression {
+        trigonometric_function(Function::Cosine, &angle)
+    }
+
+    fn square(expression: Expression) -> Expression {
+        Expression::binary(
+            BinaryOperationType::Power,
+            expression,
+            Expression::Constant(2.into()),
+        )
+    }
+
+    fn pi_fraction(numerator: i64, denominator: i64) -> Expression {
+        Expression::binary(
+            BinaryOperationType::Multiplication,
+            Expression::Constant(Rational::new(numerator, denominator)),
+            Expression::NamedConstant("pi".to_string()),
+        )
+    }
+
+    fn assert_numerically_equal(left: &Expression, right: &Expression) {
+        for (x, y) in [(0.3, -1.2), (1.7, 0.4), (-2.5, 3.1)] {
+            let values = HashMap::from([('x', x), ('y', y)]);
+            let (left, right) = (
+                left.evaluate(&values).unwrap(),
+                right.evaluate(&values).unwrap(),
+            );
+            assert!((left - right).abs() < 1e-12, "{} != {}", left, right);
+        }
+    }
+
+    #[test]
+    fn test_pythagorean_identity() {
+        let expr = Expression::binary(
+            BinaryOperationType::Addition,
+            square(sin(x())),
+            square(cos(x())),
+        );
+        assert_eq!(expr.simplify_trigonometry(), Expression::Constant(1.into()));
+
+        // y*sin(x)^2 + cos(x)*y*cos(x)
+        let expr = Expression::binary(
+            BinaryOperationType::Addition,
+            Expression::binary(BinaryOperationType::Multiplication, y(), square(sin(x()))),
+            Expression::binary(
+                BinaryOperationType::Multiplication,
+                Expression::binary(BinaryOperationType::Multiplication, cos(x()), y()),
+                cos(x()),
+            ),
+        );
+        assert_eq!(expr.simplify_trigonometry(), y());
+
+        // 3 - 3 cos(x)^2
+        let expr = Expression::binary(
+            BinaryOperationType::Substraction,
+            Expression::Constant(3.into()),
+            Expression::binary(
+                BinaryOperationType::Multiplication,
+                Expression::Constant(3.into()),
+                square(cos(x())),
+            ),
+        );
+        assert_eq!(
+            expr.simplify_trigonometry(),
+            Expression::binary(
+                BinaryOperationType::Multiplication,
+                Expression::Constant(3.into()),
+                square(sin(x()))
+            )
+        );
+    }
+
+    #[test]
+    fn test_parity() {
+        let minus_x = Expression::Monomial(Monomial {
+            factor: (-1).into(),
+            variable: 'x',
+            power: 1,
+        });
+        assert_eq!(
+            sin(minus_x.clone()).simplify_trigonometry(),
+            Expression::binary(
+                BinaryOperationType::Multiplication,
+                Expression::Constant((-1).into()),
+                sin(x())
+            )
+        );
+        assert_eq!(cos(minus_x).simplify_trigonometry(), cos(x()));
+    }
+
+    #[test]
+    fn test_special_values() {
+        let half = Expression::Constant(Rational::new(1, 2));
+        assert_eq!(sin(pi_fraction(1, 6)).simplify_trigonometry(), half);
+        assert_eq!(cos(pi_fraction(1, 3)).simplify_trigonometry(), half);
+        assert_eq!(
+            cos(Expression::NamedConstant("pi".to_string())).simplify_trigonometry(),
+            Expression::Constant((-1).into())
+        );
+        assert_eq!(
+            sin(pi_fraction(7, 2)).simplify_trigonometry(),
+            Expression::Constant((-1).into())
+        );
+        assert_eq!(
+            cos(pi_fraction(3, 4)).simplify_trigonometry(),
+            Expression::binary(
+                BinaryOperationType::Multiplication,
+                Expression::Constant(Rational::new(-1, 2)),
+                Expression::binary(
+                    BinaryOperationType::Power,
+                    Expression::Constant(2.into()),
+                    half
+                )
+            )
+        );
+        assert_eq!(
+            sin(pi_fraction(1, 5)).simplify_trigonometry(),
+            sin(pi_fraction(1, 5))
+        );
+    }
+
+    #[test]
+    fn test_special_values_without_overflow() {
+        // pi / (2^62 + 1) is shifted by pi / 2 for the cosine, beyond the range of i64
+        let angle = pi_fraction(1, (1 << 62) + 1);
+        assert_eq!(
+            cos(angle.clone()).simplify_trigonometry(),
+            cos(angle.clone())
+        );
+        assert_eq!(sin(angle.clone()).simplify_trigonometry(), sin(angle));
+        assert_eq!(
+            sin(pi_fraction(i64::MAX, 1)).simplify_trigonometry(),
+            Expression::Constant(0.into())
+        );
+    }
+
+    #[test]
+    fn test_expand_trigonometry() {
+        let double_x = Expression::Monomial(Monomial {
+            factor: 2.into(),
+            variable: 'x',
+            power: 1,
+        });
+        assert_eq!(
+            sin(double_x.clone()).expand_trigonometry(),
+            Expression::binary(
+                BinaryOperationType::Multiplication,
+                Expression::binary(
+                    BinaryOperationType::Multiplication,
+                    Expression::Constant(2.into()),
+                    sin(x())
+                ),
+                cos(x())
+            )
+        );
+        assert_eq!(
+            cos(double_x).expand_trigonometry(),
+            Expression::binary(
+                BinaryOperationType::Substraction,
+                square(cos(x())),
+                square(sin(x()))
+            )
+        );
+
+        let expr = cos(Expression::binary(
+            BinaryOperationType::Substraction,
+            Expression::Monomial(Monomial {
+                factor: 3.into(),
+                variable: 'x',
+                power: 1,
+            }),
+            y(),
+        ));
+        assert_numerically_equal(&expr.expand_trigonometry(), &expr);
+    }
+
+    #[test]
+    fn test_summand_powers_beyond_monomials() {
+        let summand = Summand::from_expression(&Expression::binary(
+            BinaryOperationType::Multiplication,
+            Expression::monomial(3, 'x', 200),
+            Expression::monomial(1, 'x', 100),
+        ));
+        assert_eq!(
+            summand.to_expression(),
+            Expression::binary(
+                BinaryOperationType::Multiplication,
+                Expression::Constant(3.into()),
+                Expression::binary(
+                    BinaryOperationType::Power,
+                    x(),
+                    Expression::Constant(300.into())
+                )
+            )
+        );
+        assert_eq!(
+            Summand::from_expression(&Expression::monomial(3, 'x', 200)).to_expression(),
+            Expression::monomial(3, 'x', 200)
+        );
+    }
+
+    #[test]
+    fn test_expand_large_multiple_angles() {
+        // Re-expanding sin((k - 1)x) and cos((k - 1)x) separately takes 2^k steps.
+        for (multiple, function) in [(12, Function::Sine), (40, Function::Cosine)] {
+            let expr = Expression::function(function, Expression::monomial(multiple, 'x', 1));
+            let expanded = expr.expand_trigonometry();
+            for x in [0.3, 1.7, -2.5] {
+                let values = HashMap::from([('x', x)]);
+                let (left, right) = (
+                    expanded.evaluate(&values).unwrap(),
+                    expr.evaluate(&values).unwrap(),
+                );
+                assert!((left - right).abs() < 1e-6, "{} != {}", left, right);
+            }
+        }
+    }
+
+    #[test]
+    fn test_linearize_trigonometry() {
+        let expr = Expression::binary(BinaryOperationType::Multiplication, sin(x()), cos(x()));
+        let linearized = expr.linearize_trigonometry();
+        assert_eq!(
+            linearized,
+            Expression::binary(
+                BinaryOperationType::Multiplication,
+                Expression::Constant(Rational::new(1, 2)),
+                sin(Expression::Monomial(Monomial {
+                    factor: 2.into(),
+                    variable: 'x',
+                    power: 1,
+                }))
+            )
+        );
+
+        // half-angle: sin(x)^2 = 1/2 - cos(2x)/2
+        let linearized = square(sin(x())).linearize_trigonometry();
+        assert_numerically_equal(&linearized, &square(sin(x())));
+        assert!(Summand::from_expression(&linearized).factors.len() <= 1);
+
+        let expr = Expression::binary(
+            BinaryOperationType::Multiplication,
+            Expression::binary(BinaryOperationType::Multiplication, cos(x()), cos(y())),
+            sin(y()),
+        );
+        let linearized = expr.linearize_trigonometry();
+        assert_numerically_equal(&linearized, &expr);
+        assert!(summands(&linearized)
+            .iter()
+            .all(|summand| trigonometric_factors(summand).len() <= 1));
+    }
+
+    #[test]
+    fn test_factor_trigonometry() {
+        let expr = Expression::binary(BinaryOperationType::Addition, sin(x()), sin(y()));
+        let factored = expr.factor_trigonometry();
+        assert_numerically_equal(&factored, &expr);
+        assert_eq!(summands(&factored).len(), 1);
+
+        let expr = Expression::binary(BinaryOperationType::Substraction, cos(x()), cos(y()));
+        let factored = expr.factor_trigonometry();
+        assert_numerically_equal(&factored, &expr);
+        assert_eq!(summands(&factored).len(), 1);
+    }
+}
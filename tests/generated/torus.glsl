@@ -0,0 +1,48 @@
+uniform float R;
+uniform float r;
+
+vec3 torus_position(float u, float v) {
+    float t0 = R + r * cos(v);
+    return vec3(cos(u) * t0, sin(u) * t0, r * sin(v));
+}
+
+vec3 torus_normal(float u, float v) {
+    float t0 = cos(v);
+    float t1 = R + r * t0;
+    float t2 = t0 * r;
+    float t3 = t1 * r;
+    return vec3(cos(u) * t1 * t2 / t3, (0.0 - -sin(u) * t1 * t2) / t3, t1 * sin(v) * r / t3);
+}
+
+float torus_gaussian_curvature(float u, float v) {
+    float t0 = cos(u);
+    float t1 = cos(v);
+    float t2 = R + r * t1;
+    float t3 = t1 * r;
+    float t4 = t0 * t2 * t3;
+    float t5 = sin(u);
+    float t6 = -t5;
+    float t7 = t6 * t2;
+    float t8 = 0.0 - t7 * t3;
+    float t9 = t2 * r;
+    float t10 = -t1 * r;
+    float t11 = sin(v);
+    float t12 = -t11 * r;
+    return ((-t0 * t2 * t4 + t7 * t8) / t9 * ((t10 * t0 * t4 + t10 * t5 * t8 + t12 * (t2 * t11 * r)) / t9) - ((t12 * t6 * t4 + t12 * t0 * t8) / t9) * ((t12 * t6 * t4 + t12 * t0 * t8) / t9)) / (t2 * t2 * (r * r));
+}
+
+float torus_mean_curvature(float u, float v) {
+    float t0 = cos(v);
+    float t1 = R + r * t0;
+    float t2 = t1 * t1;
+    float t3 = -t0 * r;
+    float t4 = cos(u);
+    float t5 = t0 * r;
+    float t6 = t4 * t1 * t5;
+    float t7 = sin(u);
+    float t8 = -t7 * t1;
+    float t9 = 0.0 - t8 * t5;
+    float t10 = sin(v);
+    float t11 = t1 * r;
+    return (t2 * ((t3 * t4 * t6 + t3 * t7 * t9 + -t10 * r * (t1 * t10 * r)) / t11) + r * r * ((-t4 * t1 * t6 + t8 * t9) / t11)) / (2.0 * (t2 * (r * r)));
+}
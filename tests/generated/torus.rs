@@ -0,0 +1,28 @@
+// Generated by tests/test_codegen.rs, do not edit.
+
+#[allow(non_snake_case, unused_variables)]
+pub fn torus_metric(u: f64, v: f64, R: f64, r: f64) -> [[f64; 2]; 2] {
+    [
+        [f64::powi(R + r * f64::cos(v), 2), 0.0],
+        [0.0, f64::powi(r, 2)],
+    ]
+}
+
+#[allow(non_snake_case, unused_variables)]
+pub fn torus_area_element(u: f64, v: f64, R: f64, r: f64) -> f64 {
+    f64::sqrt(f64::powi(R + r * f64::cos(v), 2) * f64::powi(r, 2))
+}
+
+#[allow(non_snake_case, unused_variables)]
+pub fn torus_area_element_hessian(u: f64, v: f64, R: f64, r: f64) -> [[f64; 2]; 2] {
+    let t0 = f64::cos(v);
+    let t1 = R + r * t0;
+    let t2 = f64::powi(t1, 2) * f64::powi(r, 2);
+    let t3 = 2.0 * t1;
+    let t4 = -f64::sin(v) * r;
+    let t5 = t3 * t4 * f64::powi(r, 2);
+    [
+        [0.0, 0.0],
+        [0.0, -0.5 * f64::powf(t2, -1.5) * t5 * 0.5 * t5 + (t4 * 2.0 * t4 + -t0 * r * t3) * f64::powi(r, 2) * (0.5 * f64::powf(t2, -0.5))],
+    ]
+}
@@ -0,0 +1,60 @@
+struct TorusParameters {
+    R: f32,
+    r: f32,
+}
+
+@group(0) @binding(0) var<uniform> torus_parameters: TorusParameters;
+
+fn torus_position(u: f32, v: f32) -> vec3<f32> {
+    let R = torus_parameters.R;
+    let r = torus_parameters.r;
+    let t0 = R + r * cos(v);
+    return vec3<f32>(cos(u) * t0, sin(u) * t0, r * sin(v));
+}
+
+fn torus_normal(u: f32, v: f32) -> vec3<f32> {
+    let R = torus_parameters.R;
+    let r = torus_parameters.r;
+    let t0 = cos(v);
+    let t1 = R + r * t0;
+    let t2 = t0 * r;
+    let t3 = t1 * r;
+    return vec3<f32>(cos(u) * t1 * t2 / t3, (0.0 - -sin(u) * t1 * t2) / t3, t1 * sin(v) * r / t3);
+}
+
+fn torus_gaussian_curvature(u: f32, v: f32) -> f32 {
+    let R = torus_parameters.R;
+    let r = torus_parameters.r;
+    let t0 = cos(u);
+    let t1 = cos(v);
+    let t2 = R + r * t1;
+    let t3 = t1 * r;
+    let t4 = t0 * t2 * t3;
+    let t5 = sin(u);
+    let t6 = -t5;
+    let t7 = t6 * t2;
+    let t8 = 0.0 - t7 * t3;
+    let t9 = t2 * r;
+    let t10 = -t1 * r;
+    let t11 = sin(v);
+    let t12 = -t11 * r;
+    return ((-t0 * t2 * t4 + t7 * t8) / t9 * ((t10 * t0 * t4 + t10 * t5 * t8 + t12 * (t2 * t11 * r)) / t9) - ((t12 * t6 * t4 + t12 * t0 * t8) / t9) * ((t12 * t6 * t4 + t12 * t0 * t8) / t9)) / (t2 * t2 * (r * r));
+}
+
+fn torus_mean_curvature(u: f32, v: f32) -> f32 {
+    let R = torus_parameters.R;
+    let r = torus_parameters.r;
+    let t0 = cos(v);
+    let t1 = R + r * t0;
+    let t2 = t1 * t1;
+    let t3 = -t0 * r;
+    let t4 = cos(u);
+    let t5 = t0 * r;
+    let t6 = t4 * t1 * t5;
+    let t7 = sin(u);
+    let t8 = -t7 * t1;
+    let t9 = 0.0 - t8 * t5;
+    let t10 = sin(v);
+    let t11 = t1 * r;
+    return (t2 * ((t3 * t4 * t6 + t3 * t7 * t9 + -t10 * r * (t1 * t10 * r)) / t11) + r * r * ((-t4 * t1 * t6 + t8 * t9) / t11)) / (2.0 * (t2 * (r * r)));
+}
@@ -0,0 +1,134 @@
+use std::collections::{HashMap, HashSet};
+
+use rust_differential_geometry::assumptions::Assumptions;
+use rust_differential_geometry::calculus::hessian;
+use rust_differential_geometry::codegen::{
+    generate, generate_matrix, generate_surface_shader, Language,
+};
+use rust_differential_geometry::evaluable::Evaluable;
+use rust_differential_geometry::expression::{BinaryOperationType, Expression, Function};
+use rust_differential_geometry::surface::Surface;
+
+mod generated {
+    include!("generated/torus.rs");
+}
+
+fn variable(variable: char) -> Expression {
+    Expression::monomial(1, variable, 1)
+}
+
+fn multiply(left: Expression, right: Expression) -> Expression {
+    Expression::binary(BinaryOperationType::Multiplication, left, right)
+}
+
+fn torus() -> Surface {
+    let radius = Expression::Sum(vec![
+        variable('R'),
+        multiply(
+            variable('r'),
+            Expression::function(Function::Cosine, variable('v')),
+        ),
+    ]);
+    Surface::from_embedding(
+        HashSet::from(['u', 'v']),
+        vec![
+            multiply(
+                Expression::function(Function::Cosine, variable('u')),
+                radius.clone(),
+            ),
+            multiply(Expression::function(Function::Sine, variable('u')), radius),
+            multiply(
+                variable('r'),
+                Expression::function(Function::Sine, variable('v')),
+            ),
+        ],
+    )
+}
+
+const VARIABLES: [char; 4] = ['u', 'v', 'R', 'r'];
+
+fn torus_source() -> String {
+    let torus = torus();
+    let metric = generate_matrix(
+        Language::Rust,
+        "torus_metric",
+        &VARIABLES,
+        &torus.first_fundamental_form().matrix(),
+    )
+    .unwrap();
+    let area_element = generate(
+        Language::Rust,
+        "torus_area_element",
+        &VARIABLES,
+        &torus.area_element(),
+    )
+    .unwrap();
+    let area_element_hessian = generate_matrix(
+        Language::Rust,
+        "torus_area_element_hessian",
+        &VARIABLES,
+        &hessian(&torus.area_element(), &['u', 'v']),
+    )
+    .unwrap();
+    format!(
+        "// Generated by tests/test_codegen.rs, do not edit.\n\n\
+         {metric}\n{area_element}\n{area_element_hessian}"
+    )
+}
+
+/// Compares `source` with the golden file `tests/generated/{file}`. Set `UPDATE_GOLDEN=1` to
+/// rewrite the file after a change to the generators.
+fn assert_golden(file: &str, source: &str) {
+    let path = format!("{}/tests/generated/{file}", env!("CARGO_MANIFEST_DIR"));
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        std::fs::write(&path, source).unwrap();
+    }
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), source);
+}
+
+#[test]
+fn test_generated_rust_is_up_to_date() {
+    assert_golden("torus.rs", &torus_source());
+}
+
+#[test]
+fn test_surface_shaders() {
+    let torus = torus().with_assumptions(Assumptions::new().positive('r').greater_than('R', 'r'));
+    assert_golden(
+        "torus.glsl",
+        &generate_surface_shader(Language::Glsl, "torus", &torus).unwrap(),
+    );
+    assert_golden(
+        "torus.wgsl",
+        &generate_surface_shader(Language::Wgsl, "torus", &torus).unwrap(),
+    );
+}
+
+#[test]
+fn test_generated_rust_matches_evaluation() {
+    let torus = torus();
+    let metric = torus.first_fundamental_form().matrix();
+    let area_element = torus.area_element();
+    let area_element_hessian = hessian(&area_element, &['u', 'v']);
+    for point in [[0.3, 1.1, 2., 0.5], [-2., 4., 3., 1.]] {
+        let values: HashMap<char, f64> = VARIABLES.into_iter().zip(point).collect();
+        let [u, v, big_r, r] = point;
+        let matrices = [
+            (generated::torus_metric(u, v, big_r, r), &metric),
+            (
+                generated::torus_area_element_hessian(u, v, big_r, r),
+                &area_element_hessian,
+            ),
+        ];
+        for (generated, matrix) in matrices {
+            let expected = matrix.evaluate(&values).unwrap();
+            for row in 0..2 {
+                for column in 0..2 {
+                    assert!((generated[row][column] - expected[[row, column]]).abs() < 1e-12);
+                }
+            }
+        }
+        let generated = generated::torus_area_element(u, v, big_r, r);
+        assert!((generated - area_element.evaluate(&values).unwrap()).abs() < 1e-12);
+    }
+}
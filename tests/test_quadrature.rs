@@ -0,0 +1,82 @@
+use std::collections::HashMap;
+use std::f64::consts::PI;
+
+use rust_differential_geometry::quadrature::{
+    surface_area, total_gaussian_curvature, willmore_energy, QuadratureMethod, QuadratureOptions,
+    Rectangle,
+};
+use rust_differential_geometry::surfaces::{sphere, torus};
+
+/// The Gauss–Legendre nodes avoid the poles of the sphere, where the curvatures are 0 / 0, and
+/// adaptive Simpson switches to an open rule next to them.
+fn methods() -> Vec<QuadratureOptions> {
+    vec![
+        QuadratureOptions::default(),
+        QuadratureOptions::default()
+            .with_method(QuadratureMethod::GaussLegendre(4))
+            .with_tolerance(1e-8),
+        QuadratureOptions::default().with_method(QuadratureMethod::AdaptiveSimpson),
+    ]
+}
+
+#[test]
+fn test_gauss_bonnet() {
+    let sphere = sphere();
+    let parameters = HashMap::from([('r', 1.5)]);
+    let domain = Rectangle::of_surface(&sphere, &parameters).unwrap();
+    assert_eq!(
+        domain,
+        Rectangle {
+            u: (0., PI),
+            v: (0., 2. * PI),
+        }
+    );
+    for options in methods() {
+        let total = total_gaussian_curvature(&sphere, &domain, &parameters, &options).unwrap();
+        assert!((total - 4. * PI).abs() < 1e-6, "{options:?}: {total}");
+    }
+
+    let torus = torus();
+    let parameters = HashMap::from([('R', 2.), ('r', 0.5)]);
+    let domain = Rectangle::of_surface(&torus, &parameters).unwrap();
+    for options in [
+        QuadratureOptions::default(),
+        QuadratureOptions::default().with_method(QuadratureMethod::AdaptiveSimpson),
+    ] {
+        let total = total_gaussian_curvature(&torus, &domain, &parameters, &options).unwrap();
+        assert!(total.abs() < 1e-6, "{options:?}: {total}");
+    }
+}
+
+#[test]
+fn test_area_and_willmore_energy() {
+    let torus = torus();
+    let domain = Rectangle {
+        u: (0., 2. * PI),
+        v: (0., 2. * PI),
+    };
+    let parameters = HashMap::from([('R', 2.), ('r', 0.5)]);
+    let options = QuadratureOptions::default().with_method(QuadratureMethod::AdaptiveSimpson);
+    let area = surface_area(&torus, &domain, &parameters, &options).unwrap();
+    assert!((area - 4. * PI * PI * 2. * 0.5).abs() < 1e-6);
+    // π^2 R / (r sqrt(1 - r^2 / R^2)) by the closed form of ∫ (R + 2r cos v)^2 / (R + r cos v)
+    let willmore = willmore_energy(&torus, &domain, &parameters, &options).unwrap();
+    let expected = PI * PI * 2. / (0.5 * f64::sqrt(1. - 0.25 / 4.));
+    assert!(
+        (willmore - expected).abs() < 1e-6,
+        "{willmore} != {expected}"
+    );
+
+    let sphere = sphere();
+    let domain = Rectangle {
+        u: (0., PI),
+        v: (0., 2. * PI),
+    };
+    let parameters = HashMap::from([('r', 1.5)]);
+    for options in methods() {
+        let area = surface_area(&sphere, &domain, &parameters, &options).unwrap();
+        assert!((area - 4. * PI * 1.5 * 1.5).abs() < 1e-6);
+        let willmore = willmore_energy(&sphere, &domain, &parameters, &options).unwrap();
+        assert!((willmore - 4. * PI).abs() < 1e-6, "{willmore}");
+    }
+}
@@ -0,0 +1,83 @@
+#![cfg(feature = "serde")]
+
+use std::collections::HashSet;
+
+use rust_differential_geometry::assumptions::Assumptions;
+use rust_differential_geometry::expression::{BinaryOperationType, Expression, Function};
+use rust_differential_geometry::matrix::SymbolicMatrix;
+use rust_differential_geometry::surface::{Domain, FirstFundamentalForm, Surface};
+
+fn variable(variable: char) -> Expression {
+    Expression::monomial(1, variable, 1)
+}
+
+fn multiply(left: Expression, right: Expression) -> Expression {
+    Expression::binary(BinaryOperationType::Multiplication, left, right)
+}
+
+/// Sphere of radius r.
+fn sphere() -> Surface {
+    Surface::from_embedding(
+        HashSet::from(['u', 'v']),
+        vec![
+            multiply(
+                variable('r'),
+                multiply(
+                    Expression::function(Function::Sine, variable('u')),
+                    Expression::function(Function::Cosine, variable('v')),
+                ),
+            ),
+            multiply(
+                variable('r'),
+                multiply(
+                    Expression::function(Function::Sine, variable('u')),
+                    Expression::function(Function::Sine, variable('v')),
+                ),
+            ),
+            multiply(
+                variable('r'),
+                Expression::function(Function::Cosine, variable('u')),
+            ),
+        ],
+    )
+    .with_assumptions(Assumptions::new().positive('r'))
+    .with_domain(
+        'v',
+        Domain::periodic(
+            Expression::Constant(0.into()),
+            multiply(
+                Expression::Constant(2.into()),
+                Expression::NamedConstant("pi".to_string()),
+            ),
+        ),
+    )
+}
+
+fn round_trip<T: serde::Serialize + serde::de::DeserializeOwned>(value: &T) -> T {
+    serde_json::from_str(&serde_json::to_string(value).unwrap()).unwrap()
+}
+
+#[test]
+fn test_expression_round_trip() {
+    let expression = Expression::Sum(vec![
+        multiply(Expression::NamedConstant("pi".to_string()), variable('x')),
+        Expression::Constant((-3, 4).into()),
+    ]);
+    assert_eq!(round_trip(&expression), expression);
+}
+
+#[test]
+fn test_surface_and_results_round_trip() {
+    let sphere = sphere();
+    assert_eq!(round_trip(&sphere), sphere);
+
+    let first_fundamental_form = sphere.first_fundamental_form();
+    let cached: FirstFundamentalForm = round_trip(&first_fundamental_form);
+    assert_eq!(cached, first_fundamental_form);
+    let matrix: SymbolicMatrix = round_trip(&first_fundamental_form.matrix());
+    assert_eq!(matrix, first_fundamental_form.matrix());
+    assert_eq!(
+        round_trip(&sphere.second_fundamental_form()),
+        sphere.second_fundamental_form()
+    );
+}
@@ -0,0 +1,150 @@
+use std::collections::{HashMap, HashSet};
+
+use rust_differential_geometry::evaluable::Evaluable;
+use rust_differential_geometry::expression::{Expression, Variable};
+use rust_differential_geometry::surface::Surface;
+use rust_differential_geometry::surfaces::{
+    catenoid, cone, cylinder, enneper, helicoid, mobius_strip, sphere, torus,
+};
+
+/// Points inside the domain of `surface`, sampled on a grid.
+fn sample_points(surface: &Surface, parameters: &HashMap<Variable, f64>) -> Vec<[f64; 2]> {
+    let fractions = [0.15, 0.4, 0.7, 0.9];
+    let [u, v] = [0, 1].map(|i| {
+        surface
+            .domain(surface.coordinates()[i])
+            .unwrap()
+            .bounds(parameters)
+            .unwrap()
+    });
+    fractions
+        .iter()
+        .flat_map(|s| {
+            fractions
+                .iter()
+                .map(move |t| [u.0 + s * (u.1 - u.0), v.0 + t * (v.1 - v.0)])
+        })
+        .collect()
+}
+
+/// Checks `expression` against `expected` over the sample points of `surface`.
+fn assert_values<F: Fn([f64; 2]) -> f64>(
+    surface: &Surface,
+    expression: &Expression,
+    parameters: &[(Variable, f64)],
+    expected: F,
+) {
+    let parameters: HashMap<Variable, f64> = parameters.iter().copied().collect();
+    for point in sample_points(surface, &parameters) {
+        assert_eq!(surface.contains(&point, &parameters), Ok(true));
+        let mut values = parameters.clone();
+        values.extend([('u', point[0]), ('v', point[1])]);
+        let value = expression.evaluate(&values).unwrap();
+        assert!(
+            (value - expected(point)).abs() < 1e-9,
+            "{value} != {} at {point:?}",
+            expected(point)
+        );
+    }
+}
+
+#[test]
+fn test_parametric_variables_and_domains() {
+    let expected = [
+        (sphere(), vec!['r']),
+        (torus(), vec!['R', 'r']),
+        (cylinder(), vec!['h', 'r']),
+        (cone(), vec!['a', 'h']),
+        (catenoid(), vec!['c', 'h']),
+        (helicoid(), vec!['c', 'r']),
+        (enneper(), vec!['r']),
+        (mobius_strip(), vec!['R', 'w']),
+    ];
+    for (surface, parameters) in expected {
+        assert_eq!(surface.surface_variables, HashSet::from(['u', 'v']));
+        assert_eq!(surface.parametric_variables, HashSet::from_iter(parameters));
+        assert!(surface.domain('u').is_some() && surface.domain('v').is_some());
+    }
+    assert!(torus().is_periodic());
+    assert!(!sphere().is_periodic());
+    assert!(!mobius_strip().is_periodic());
+    assert!(!cone()
+        .contains(&[1., 0.], &HashMap::from([('a', 1.), ('h', 2.)]))
+        .unwrap());
+}
+
+#[test]
+fn test_gaussian_curvature() {
+    let sphere = sphere();
+    assert_values(&sphere, &sphere.gaussian_curvature(), &[('r', 1.5)], |_| {
+        1. / (1.5 * 1.5)
+    });
+    let cylinder = cylinder();
+    assert_values(
+        &cylinder,
+        &cylinder.gaussian_curvature(),
+        &[('r', 1.5), ('h', 2.)],
+        |_| 0.,
+    );
+    let cone = cone();
+    assert_values(
+        &cone,
+        &cone.gaussian_curvature(),
+        &[('a', 0.7), ('h', 2.)],
+        |_| 0.,
+    );
+    // the minimal surfaces are saddles, K = -1 / (c^2 cosh^4 v) on the catenoid
+    let catenoid = catenoid();
+    assert_values(
+        &catenoid,
+        &catenoid.gaussian_curvature(),
+        &[('c', 0.5), ('h', 1.)],
+        |[_, v]| -1. / (0.25 * f64::cosh(v).powi(4)),
+    );
+}
+
+#[test]
+fn test_minimal_surfaces() {
+    let catenoid = catenoid();
+    assert_values(
+        &catenoid,
+        &catenoid.mean_curvature(),
+        &[('c', 0.5), ('h', 1.)],
+        |_| 0.,
+    );
+    let helicoid = helicoid();
+    assert_values(
+        &helicoid,
+        &helicoid.mean_curvature(),
+        &[('c', 0.5), ('r', 2.)],
+        |_| 0.,
+    );
+    let enneper = enneper();
+    assert_values(&enneper, &enneper.mean_curvature(), &[('r', 1.2)], |_| 0.);
+    // H = ±1/r on the sphere, the normal x_u × x_v pointing outwards
+    let sphere = sphere();
+    assert_values(&sphere, &sphere.mean_curvature(), &[('r', 1.5)], |_| {
+        -1. / 1.5
+    });
+}
+
+#[test]
+fn test_mobius_strip() {
+    // K = -1 / (4 R^2) on the center line v = 0, and K < 0 off it
+    let strip = mobius_strip();
+    let curvature = strip.gaussian_curvature();
+    let parameters = HashMap::from([('R', 2.), ('w', 0.5)]);
+    for u in [0.5, 1., 4.] {
+        let mut values = parameters.clone();
+        values.extend([('u', u), ('v', 0.)]);
+        let value = curvature.evaluate(&values).unwrap();
+        assert!((value + 1. / 16.).abs() < 1e-9, "{value}");
+    }
+    for [u, v] in sample_points(&strip, &parameters) {
+        assert_ne!(v, 0.);
+        let mut values = parameters.clone();
+        values.extend([('u', u), ('v', v)]);
+        let value = curvature.evaluate(&values).unwrap();
+        assert!(value < 0., "K({u}, {v}) = {value}");
+    }
+}
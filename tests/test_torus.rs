@@ -1,9 +1,13 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
+use rust_differential_geometry::assumptions::Assumptions;
+use rust_differential_geometry::canonical::Canonical;
+use rust_differential_geometry::evaluable::{Evaluable, EvaluationError};
 use rust_differential_geometry::expression::{
     BinaryOperation, BinaryOperationType, Expression, Function, Monomial,
 };
-use rust_differential_geometry::surface::Surface;
+use rust_differential_geometry::integrable::Integrable;
+use rust_differential_geometry::surface::{Domain, Surface};
 
 #[test]
 fn it_adds_two() {
@@ -15,7 +19,7 @@ fn it_adds_two() {
                 left_value: Box::new(Expression::Function {
                     function: Function::Cosine,
                     expression: Box::new(Expression::Monomial(Monomial {
-                        factor: 1.,
+                        factor: 1.into(),
                         variable: 'u',
                         power: 1,
                     })),
@@ -23,21 +27,21 @@ fn it_adds_two() {
                 right_value: Box::new(Expression::BinaryOperation(BinaryOperation {
                     operation: BinaryOperationType::Addition,
                     left_value: Box::new(Expression::Monomial(Monomial {
-                        factor: 1.,
+                        factor: 1.into(),
                         variable: 'R',
                         power: 1,
                     })),
                     right_value: Box::new(Expression::BinaryOperation(BinaryOperation {
                         operation: BinaryOperationType::Multiplication,
                         left_value: Box::new(Expression::Monomial(Monomial {
-                            factor: 1.,
+                            factor: 1.into(),
                             variable: 'r',
                             power: 1,
                         })),
                         right_value: Box::new(Expression::Function {
                             function: Function::Cosine,
                             expression: Box::new(Expression::Monomial(Monomial {
-                                factor: 1.,
+                                factor: 1.into(),
                                 variable: 'v',
                                 power: 1,
                             })),
@@ -50,7 +54,7 @@ fn it_adds_two() {
                 left_value: Box::new(Expression::Function {
                     function: Function::Cosine,
                     expression: Box::new(Expression::Monomial(Monomial {
-                        factor: 1.,
+                        factor: 1.into(),
                         variable: 'u',
                         power: 1,
                     })),
@@ -58,21 +62,21 @@ fn it_adds_two() {
                 right_value: Box::new(Expression::BinaryOperation(BinaryOperation {
                     operation: BinaryOperationType::Addition,
                     left_value: Box::new(Expression::Monomial(Monomial {
-                        factor: 1.,
+                        factor: 1.into(),
                         variable: 'R',
                         power: 1,
                     })),
                     right_value: Box::new(Expression::BinaryOperation(BinaryOperation {
                         operation: BinaryOperationType::Multiplication,
                         left_value: Box::new(Expression::Monomial(Monomial {
-                            factor: 1.,
+                            factor: 1.into(),
                             variable: 'r',
                             power: 1,
                         })),
                         right_value: Box::new(Expression::Function {
                             function: Function::Sine,
                             expression: Box::new(Expression::Monomial(Monomial {
-                                factor: 1.,
+                                factor: 1.into(),
                                 variable: 'v',
                                 power: 1,
                             })),
@@ -83,14 +87,14 @@ fn it_adds_two() {
             Expression::BinaryOperation(BinaryOperation {
                 operation: BinaryOperationType::Multiplication,
                 left_value: Box::new(Expression::Monomial(Monomial {
-                    factor: 1.,
+                    factor: 1.into(),
                     variable: 'r',
                     power: 1,
                 })),
                 right_value: Box::new(Expression::Function {
                     function: Function::Sine,
                     expression: Box::new(Expression::Monomial(Monomial {
-                        factor: 1.,
+                        factor: 1.into(),
                         variable: 'v',
                         power: 1,
                     })),
@@ -101,3 +105,177 @@ fn it_adds_two() {
     assert_eq!(torus.surface_variables, HashSet::from(['u', 'v']));
     assert_eq!(torus.parametric_variables, HashSet::from(['r', 'R']));
 }
+
+fn function(function: Function, variable: char) -> Expression {
+    Expression::function(function, Expression::monomial(1, variable, 1))
+}
+
+fn product(left_value: Expression, right_value: Expression) -> Expression {
+    Expression::binary(BinaryOperationType::Multiplication, left_value, right_value)
+}
+
+/// R + r cos(v), the distance to the axis of revolution.
+fn torus_radius() -> Expression {
+    Expression::BinaryOperation(BinaryOperation {
+        operation: BinaryOperationType::Addition,
+        left_value: Box::new(Expression::monomial(1, 'R', 1)),
+        right_value: Box::new(product(
+            Expression::monomial(1, 'r', 1),
+            function(Function::Cosine, 'v'),
+        )),
+    })
+}
+
+fn torus() -> Surface {
+    Surface::from_embedding(
+        HashSet::from(['u', 'v']),
+        vec![
+            product(function(Function::Cosine, 'u'), torus_radius()),
+            product(function(Function::Sine, 'u'), torus_radius()),
+            product(
+                Expression::monomial(1, 'r', 1),
+                function(Function::Sine, 'v'),
+            ),
+        ],
+    )
+}
+
+#[test]
+fn test_torus_first_fundamental_form() {
+    let form = torus().first_fundamental_form();
+    assert_eq!(
+        form.e,
+        Expression::BinaryOperation(BinaryOperation {
+            operation: BinaryOperationType::Power,
+            left_value: Box::new(torus_radius()),
+            right_value: Box::new(Expression::Constant(2.into())),
+        })
+    );
+    assert_eq!(form.f, Expression::Constant(0.into()));
+    assert_eq!(form.matrix()[(0, 0)], form.e);
+    assert_eq!(form.matrix()[(1, 0)], form.f);
+    assert_eq!(
+        form.g,
+        Expression::Monomial(Monomial {
+            factor: 1.into(),
+            variable: 'r',
+            power: 2,
+        })
+    );
+}
+
+#[test]
+fn test_torus_area_element() {
+    // without R > r > 0 the square root cannot be taken
+    let area_element = torus().area_element();
+    assert!(matches!(
+        area_element,
+        Expression::BinaryOperation(BinaryOperation {
+            operation: BinaryOperationType::Power,
+            ..
+        })
+    ));
+
+    let torus = torus().with_assumptions(Assumptions::new().positive('r').greater_than('R', 'r'));
+    assert_eq!(
+        torus.area_element().to_canonical_form(),
+        product(Expression::monomial(1, 'r', 1), torus_radius()).to_canonical_form()
+    );
+}
+
+#[test]
+fn test_torus_curvature() {
+    let torus = torus().with_assumptions(Assumptions::new().positive('r').greater_than('R', 'r'));
+    let gaussian_curvature = torus.gaussian_curvature();
+    let mean_curvature = torus.mean_curvature();
+    let normal = torus.unit_normal();
+    for (u, v) in [(0.3, 1.1), (2., -2.5), (-1., 0.)] {
+        let (big_r, r) = (2., 0.5);
+        let values = HashMap::from([('u', u), ('v', v), ('R', big_r), ('r', r)]);
+        let radius = big_r + r * f64::cos(v);
+        let expected = f64::cos(v) / (r * radius);
+        assert!((gaussian_curvature.evaluate(&values).unwrap() - expected).abs() < 1e-12);
+        // the normal x_u × x_v points outwards, so the mean curvature is negative
+        let expected = -(big_r + 2. * r * f64::cos(v)) / (2. * r * radius);
+        assert!((mean_curvature.evaluate(&values).unwrap() - expected).abs() < 1e-12);
+        let expected = [
+            f64::cos(u) * f64::cos(v),
+            f64::sin(u) * f64::cos(v),
+            f64::sin(v),
+        ];
+        for (component, expected) in normal.iter().zip(expected) {
+            assert!((component.evaluate(&values).unwrap() - expected).abs() < 1e-12);
+        }
+    }
+}
+
+#[test]
+fn test_torus_area_and_total_curvature() {
+    let torus = torus().with_assumptions(Assumptions::new().positive('r').greater_than('R', 'r'));
+    let zero = Expression::Constant(0.into());
+    let two_pi = product(
+        Expression::Constant(2.into()),
+        Expression::NamedConstant("pi".to_string()),
+    );
+    let integrate = |density: &Expression| {
+        density
+            .integrate_between_with('v', &zero, &two_pi, &torus.assumptions)
+            .and_then(|inner| inner.integrate_between_with('u', &zero, &two_pi, &torus.assumptions))
+            .unwrap()
+    };
+    let area_element = torus.area_element();
+    let area = integrate(&area_element);
+    let values = HashMap::from([('R', 2.), ('r', 0.5)]);
+    let expected = 4. * std::f64::consts::PI.powi(2) * 2. * 0.5;
+    assert!((area.evaluate(&values).unwrap() - expected).abs() < 1e-12);
+    // Gauss–Bonnet: the torus has Euler characteristic 0, with K = cos v / (r (R + r cos v))
+    let gaussian_curvature = Expression::BinaryOperation(BinaryOperation {
+        operation: BinaryOperationType::Division,
+        left_value: Box::new(function(Function::Cosine, 'v')),
+        right_value: Box::new(product(Expression::monomial(1, 'r', 1), torus_radius())),
+    });
+    let total_curvature = integrate(&product(gaussian_curvature, area_element));
+    assert_eq!(total_curvature.to_canonical_form(), zero);
+}
+
+#[test]
+fn test_torus_domains() {
+    let two_pi = product(
+        Expression::Constant(2.into()),
+        Expression::NamedConstant("pi".to_string()),
+    );
+    let torus = torus().with_domain(
+        'u',
+        Domain::periodic(Expression::Constant(0.into()), two_pi.clone()),
+    );
+    assert!(!torus.is_periodic());
+    let torus = torus.with_domain(
+        'v',
+        Domain::periodic(Expression::Constant(0.into()), two_pi),
+    );
+    assert!(torus.is_periodic());
+
+    let parameters = HashMap::new();
+    let tau = 2. * std::f64::consts::PI;
+    assert_eq!(torus.contains(&[0., 1.], &parameters), Ok(true));
+    assert_eq!(torus.contains(&[tau, 1.], &parameters), Ok(false));
+    let wrapped = torus.wrap(&[tau + 0.5, -1.], &parameters).unwrap();
+    assert!((wrapped[0] - 0.5).abs() < 1e-12);
+    assert!((wrapped[1] - (tau - 1.)).abs() < 1e-12);
+
+    // the tube radius bounds a domain of the form [-r, r]
+    let domain = Domain::closed(
+        product(
+            Expression::Constant((-1).into()),
+            Expression::monomial(1, 'r', 1),
+        ),
+        Expression::monomial(1, 'r', 1),
+    );
+    let parameters = HashMap::from([('r', 0.5)]);
+    assert_eq!(domain.contains(0.5, &parameters), Ok(true));
+    assert_eq!(domain.wrap(0.7, &parameters), Ok(0.7));
+    assert_eq!(
+        domain.bounds(&HashMap::new()),
+        Err(EvaluationError::UnboundVariable('r'))
+    );
+}